@@ -0,0 +1,344 @@
+/// Protect the existing branches of all repositories in the organization the GitHub API client is
+/// authenticated for that would be protected when created, that is, the default branches and the
+/// branches matching `protected_branches` or `protected_branch_patterns` unless they match
+/// `branch_deny_patterns`, and print how many branches were protected per rule. If invoked from
+/// GitHub Actions, the branches protected or failing and the repositories skipped for errors are
+/// also listed in the job summary.
+///
+/// Branches that are protected already are skipped, regardless of their settings. Unlike handling
+/// webhook events, this doesn’t open issues. Failures are logged and counted but don’t abort the
+/// backfill, with repositories the GitHub App isn’t installed for (404 Not Found) or lacks
+/// permissions for (403 Forbidden) counted separately. Requests that exceed the GitHub API rate
+/// limit are retried after waiting for the rate limit to recover. To avoid triggering GitHub’s
+/// abuse detection mechanism, only a bounded number of repositories is processed at the same time.
+///
+/// # Arguments
+/// - `github_api_client`: A handle to the GitHub API client.
+/// - `config`: The configuration, including the branches to protect and their settings.
+/// - `concurrency`: How many repositories to process at the same time (at least 1).
+/// - `fail_on_error`: Whether to return an error if processing any repository failed, including
+///   repositories that were skipped for lack of access.
+pub async fn backfill(github_api_client: &crate::github_api::Client,
+	config: std::sync::Arc<crate::Config>, concurrency: usize, fail_on_error: bool)
+	-> Result<(), crate::Error>
+{
+	let organization_name = github_api_client.organization();
+
+	let repositories: Vec<crate::Repository> = crate::retry_if_rate_limited(
+		|| github_api_client.get_paginated(format!("orgs/{}/repos",
+			crate::github_api::encode_path_segment(organization_name)))).await?;
+
+	let repository_count = repositories.len();
+
+	log::info!("backfilling branch protection in {repository_count} repositories, {concurrency} at \
+		a time");
+
+	let mut counts = std::collections::BTreeMap::<String, BackfillCounts>::new();
+	let mut repository_counts = RepositoryCounts::default();
+	let mut summary_rows = Vec::new();
+	let mut tasks = tokio::task::JoinSet::new();
+	let mut repositories = repositories.into_iter();
+	let mut processed_count = 0;
+
+	loop
+	{
+		// Keep up to the given number of repositories in progress, starting the next one as soon
+		// as another one is done
+		while tasks.len() < concurrency
+		{
+			match repositories.next()
+			{
+				Some(repository) => tasks.spawn(backfill_repository(github_api_client.clone(),
+					config.clone(), repository)),
+				None => break,
+			};
+		}
+
+		let (repository_name, result) = match tasks.join_next().await
+		{
+			Some(result) =>
+				result.expect("this call is infallible because backfilling doesn’t panic"),
+			None => break,
+		};
+
+		let repository = format!("{organization_name}/{repository_name}");
+		let repository_row = |detail: String| crate::StepSummaryRow
+		{
+			repository: Some(repository.clone()),
+			branch: None,
+			outcome: "failed",
+			detail,
+		};
+
+		match result
+		{
+			Ok((branch_counts, branch_rows)) =>
+			{
+				summary_rows.extend(branch_rows);
+
+				if branch_counts.values().any(|counts| counts.failed > 0)
+				{
+					repository_counts.failed += 1;
+				}
+
+				for (rule, branch_counts) in branch_counts
+				{
+					counts.entry(rule).or_default().add(&branch_counts);
+				}
+			},
+			Err(error) if error.is_client_error_with_status(reqwest::StatusCode::NOT_FOUND) =>
+			{
+				log::warn!("skipping repository “{repository_name}”, as the GitHub App isn’t \
+					installed for it");
+				summary_rows.push(repository_row("GitHub App not installed".into()));
+				repository_counts.not_installed += 1;
+			},
+			Err(error) if error.is_client_error_with_status(reqwest::StatusCode::FORBIDDEN) =>
+			{
+				log::warn!("skipping repository “{repository_name}”, as the GitHub App lacks the \
+					required permissions: {error}");
+				summary_rows.push(repository_row(error.describe_with_sources()));
+				repository_counts.no_permission += 1;
+			},
+			Err(error) =>
+			{
+				log::error!("could not backfill repository “{repository_name}”: {error}");
+				summary_rows.push(repository_row(error.describe_with_sources()));
+				repository_counts.failed += 1;
+			},
+		}
+
+		processed_count += 1;
+
+		log::info!("processed {processed_count} of {repository_count} repositories");
+	}
+
+	let protected_description = match config.dry_run
+	{
+		true => "would be protected",
+		false => "protected",
+	};
+
+	for (rule, counts) in &counts
+	{
+		println!("{rule}: {} {protected_description}, {} already protected, {} failed",
+			counts.protected, counts.already_protected, counts.failed);
+	}
+
+	println!("repositories skipped because the GitHub App isn’t installed: {}",
+		repository_counts.not_installed);
+	println!("repositories skipped because the GitHub App lacks permissions: {}",
+		repository_counts.no_permission);
+	println!("repositories with failures: {}", repository_counts.failed);
+
+	crate::append_step_summary(&summary_rows);
+
+	let unsuccessful_repository_count = repository_counts.not_installed
+		+ repository_counts.no_permission + repository_counts.failed;
+
+	if fail_on_error && unsuccessful_repository_count > 0
+	{
+		return Err(crate::Error::BackfillFailed{repository_count: unsuccessful_repository_count});
+	}
+
+	Ok(())
+}
+
+/// Protect the existing branches of a single repository as described for [backfill], returning
+/// the name of the repository along with the counts per rule and the rows of the job summary
+/// describing the branches protected or failing. Errors that affect the whole
+/// repository, such as missing permissions, abort processing the repository.
+///
+/// # Arguments
+/// - `github_api_client`: A handle to the GitHub API client.
+/// - `config`: The configuration, including the branches to protect and their settings.
+/// - `repository`: The repository whose branches to protect.
+#[doc(hidden)]
+async fn backfill_repository(github_api_client: crate::github_api::Client,
+	config: std::sync::Arc<crate::Config>, repository: crate::Repository)
+	-> (String, Result<BranchResults, crate::Error>)
+{
+	let result = backfill_branches(&github_api_client, &config, &repository).await;
+
+	(repository.name, result)
+}
+
+/// Protect the existing branches of a repository (for arguments, see [backfill_repository]).
+#[doc(hidden)]
+async fn backfill_branches(github_api_client: &crate::github_api::Client,
+	config: &crate::Config, repository: &crate::Repository)
+	-> Result<BranchResults, crate::Error>
+{
+	let organization_name = github_api_client.organization();
+	let repository_name = &repository.name;
+
+	let mut counts = std::collections::BTreeMap::<String, BackfillCounts>::new();
+	let mut summary_rows = Vec::new();
+	let summary_row = |branch_name: &str, outcome, detail|
+		crate::StepSummaryRow
+		{
+			repository: Some(format!("{organization_name}/{repository_name}")),
+			branch: Some(branch_name.to_owned()),
+			outcome,
+			detail,
+		};
+
+	if let Some(reason) = repository_skip_reason(github_api_client, config, repository).await?
+	{
+		log::info!("skipping repository “{repository_name}”: {reason}");
+		return Ok((counts, summary_rows));
+	}
+
+	let branches = crate::retry_if_rate_limited(
+		|| github_api_client.list_branches(organization_name, repository_name)).await?;
+
+	for branch in branches
+	{
+		if let Some(pattern) = config.branch_deny_pattern(&branch.name)
+		{
+			log::info!("skipping branch “{}” in repository “{repository_name}”, which matches the \
+				deny pattern “{pattern}”", branch.name);
+			continue;
+		}
+
+		let rule = match repository.default_branch.as_ref() == Some(&branch.name)
+		{
+			true => DEFAULT_BRANCH_RULE,
+			false => match config.protected_branch_rule(&branch.name)
+			{
+				Some(rule) => rule,
+				None => continue,
+			},
+		};
+
+		let counts = counts.entry(rule.to_owned()).or_default();
+
+		if branch.protected
+		{
+			counts.already_protected += 1;
+			continue;
+		}
+
+		if config.dry_run
+		{
+			log::info!("dry run: would protect branch “{}” in repository “{repository_name}”",
+				branch.name);
+			summary_rows.push(summary_row(&branch.name, "dry_run",
+				"would protect branch".into()));
+			counts.protected += 1;
+			continue;
+		}
+
+		let protect_branch_request = config.branch_protection_for_branch(repository_name,
+			&branch.name, rule == DEFAULT_BRANCH_RULE).protect_branch_request();
+
+		let result = crate::retry_if_rate_limited(
+			|| github_api_client.protect_branch(organization_name, repository_name, &branch.name,
+				&protect_branch_request)).await;
+
+		match result
+		{
+			Ok(()) =>
+			{
+				log::info!("protected branch “{}” in repository “{repository_name}”", branch.name);
+				summary_rows.push(summary_row(&branch.name, "protected", String::new()));
+				counts.protected += 1;
+			},
+			// Missing access affects all branches of the repository alike
+			Err(error) if error.is_client_error_with_status(reqwest::StatusCode::NOT_FOUND)
+				|| error.is_client_error_with_status(reqwest::StatusCode::FORBIDDEN) =>
+				return Err(error),
+			Err(error) =>
+			{
+				log::error!("could not protect branch “{}” in repository “{repository_name}”: \
+					{error}", branch.name);
+				summary_rows.push(summary_row(&branch.name, "failed",
+					error.describe_with_sources()));
+				counts.failed += 1;
+			},
+		}
+	}
+
+	Ok((counts, summary_rows))
+}
+
+/// Check whether a repository wouldn’t be protected when its branches are created, because it’s a
+/// fork and `protect_forks` is disabled, it isn’t tagged with any of the `required_topics`, or it
+/// contains the `opt_out_file`, returning the reason if so. This applies to retrying failed
+/// branches, too (see [crate::retry_failed]).
+///
+/// # Arguments
+/// - `github_api_client`: A handle to the GitHub API client.
+/// - `config`: The configuration, including which repositories to skip.
+/// - `repository`: The repository to check.
+pub async fn repository_skip_reason(github_api_client: &crate::github_api::Client,
+	config: &crate::Config, repository: &crate::Repository)
+	-> Result<Option<String>, crate::Error>
+{
+	let organization_name = github_api_client.organization();
+	let repository_name = &repository.name;
+
+	if repository.fork && !config.protect_forks
+	{
+		return Ok(Some("repository is a fork".into()));
+	}
+
+	if !config.required_topics.is_empty()
+	{
+		let topics = crate::retry_if_rate_limited(
+			|| github_api_client.get_repository_topics(organization_name, repository_name)).await?;
+
+		if !topics.names.iter().any(|topic| config.required_topics.contains(topic))
+		{
+			return Ok(Some("repository isn’t tagged with any of the required topics".into()));
+		}
+	}
+
+	if let Some(opt_out_file) = &config.opt_out_file
+	{
+		if crate::retry_if_rate_limited(|| github_api_client.file_exists(organization_name,
+			repository_name, opt_out_file)).await?
+		{
+			return Ok(Some(format!("repository opted out by containing “{opt_out_file}”")));
+		}
+	}
+
+	Ok(None)
+}
+
+/// The counts per rule of the branches of a repository along with the rows of the job summary
+/// describing them.
+type BranchResults =
+	(std::collections::BTreeMap<String, BackfillCounts>, Vec<crate::StepSummaryRow>);
+
+/// How many branches matching a rule were handled in which way.
+#[derive(Default)]
+struct BackfillCounts
+{
+	protected: usize,
+	already_protected: usize,
+	failed: usize,
+}
+
+/// How many repositories were skipped or failed for which reason.
+#[derive(Default)]
+struct RepositoryCounts
+{
+	not_installed: usize,
+	no_permission: usize,
+	failed: usize,
+}
+
+impl BackfillCounts
+{
+	/// Add the counts of another repository to these counts.
+	fn add(&mut self, other: &Self)
+	{
+		self.protected += other.protected;
+		self.already_protected += other.already_protected;
+		self.failed += other.failed;
+	}
+}
+
+/// The name under which default branches are counted, as they’re protected without a rule.
+const DEFAULT_BRANCH_RULE: &str = "default branch";
@@ -0,0 +1,78 @@
+/// Check whether a branch name matches a pattern, similar to the patterns of GitHub’s branch
+/// protection rules. `*` matches any number of characters except `/`, `**` matches any number of
+/// characters including `/`, and `?` matches a single character except `/`. All other characters
+/// are matched exactly and case-sensitively (example: `release/*` matches `release/1.0` but not
+/// `release/1.0/hotfix`).
+///
+/// # Arguments
+/// - `pattern`: The branch name pattern.
+/// - `branch_name`: The name of the branch.
+pub fn matches_branch_pattern(pattern: &str, branch_name: &str) -> bool
+{
+	let pattern: Vec<_> = pattern.chars().collect();
+	let branch_name: Vec<_> = branch_name.chars().collect();
+
+	matches_from(&pattern, &branch_name)
+}
+
+/// Match the remainder of a pattern against the remainder of a branch name.
+#[doc(hidden)]
+fn matches_from(pattern: &[char], branch_name: &[char]) -> bool
+{
+	match pattern
+	{
+		[] => branch_name.is_empty(),
+		['*', '*', pattern @ ..] => (0..=branch_name.len())
+			.any(|skipped| matches_from(pattern, &branch_name[skipped..])),
+		['*', pattern @ ..] =>
+		{
+			// A single asterisk may only skip characters up to the next slash
+			let segment_length = branch_name.iter().position(|character| *character == '/')
+				.unwrap_or(branch_name.len());
+
+			(0..=segment_length).any(|skipped| matches_from(pattern, &branch_name[skipped..]))
+		},
+		['?', pattern @ ..] => matches!(branch_name, [character, branch_name @ ..]
+			if *character != '/' && matches_from(pattern, branch_name)),
+		[expected, pattern @ ..] => matches!(branch_name, [character, branch_name @ ..]
+			if character == expected && matches_from(pattern, branch_name)),
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	#[test]
+	fn single_asterisk_matches_within_segment()
+	{
+		assert!(super::matches_branch_pattern("release/*", "release/1.0"));
+		assert!(super::matches_branch_pattern("release/*", "release/"));
+		assert!(!super::matches_branch_pattern("release/*", "release/1.0/hotfix"));
+		assert!(!super::matches_branch_pattern("release/*", "Release/1.0"));
+		assert!(super::matches_branch_pattern("*-stable", "2.x-stable"));
+	}
+
+	#[test]
+	fn double_asterisk_matches_across_segments()
+	{
+		assert!(super::matches_branch_pattern("dependabot/**", "dependabot/npm_and_yarn/lodash"));
+		assert!(super::matches_branch_pattern("**/hotfix", "release/1.0/hotfix"));
+		assert!(!super::matches_branch_pattern("dependabot/**", "renovate/lodash"));
+	}
+
+	#[test]
+	fn question_mark_matches_single_character_except_slash()
+	{
+		assert!(super::matches_branch_pattern("v?", "v1"));
+		assert!(!super::matches_branch_pattern("v?", "v10"));
+		assert!(!super::matches_branch_pattern("a?b", "a/b"));
+	}
+
+	#[test]
+	fn other_characters_match_exactly()
+	{
+		assert!(super::matches_branch_pattern("main", "main"));
+		assert!(!super::matches_branch_pattern("main", "main2"));
+		assert!(!super::matches_branch_pattern("main", ""));
+	}
+}
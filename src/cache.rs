@@ -0,0 +1,66 @@
+/// A simple thread-safe cache whose entries expire after a fixed duration.
+///
+/// This is used to avoid making repeated GitHub API requests for data that rarely changes, such as
+/// the topics of a repository. Expired entries are only evicted when they are looked up again or
+/// when new entries are inserted, which is sufficient for the small number of entries we expect.
+///
+/// The cache can safely be shared between threads, as cloning it only clones a handle to the
+/// underlying data.
+#[derive(Clone)]
+pub struct ExpiringCache<K, V>
+{
+	#[doc(hidden)]
+	time_to_live: std::time::Duration,
+	#[doc(hidden)]
+	entries:
+		std::sync::Arc<std::sync::Mutex<std::collections::HashMap<K, (std::time::Instant, V)>>>,
+}
+
+impl<K, V> ExpiringCache<K, V>
+where
+	K: Eq + std::hash::Hash,
+	V: Clone,
+{
+	/// Create a new, empty cache.
+	///
+	/// # Arguments
+	/// - `time_to_live`: The duration after which entries are considered expired.
+	pub fn new(time_to_live: std::time::Duration) -> Self
+	{
+		Self
+		{
+			time_to_live,
+			entries: Default::default(),
+		}
+	}
+
+	/// Retrieve a copy of the value cached for a key if present and not yet expired.
+	pub fn get(&self, key: &K) -> Option<V>
+	{
+		let mut entries = self.entries.lock().expect("cache mutex should never be poisoned");
+
+		match entries.get(key)
+		{
+			Some((inserted_at, value)) if inserted_at.elapsed() < self.time_to_live =>
+				Some(value.clone()),
+			Some(_) =>
+			{
+				entries.remove(key);
+				None
+			},
+			None => None,
+		}
+	}
+
+	/// Store a value for a key, replacing any previously cached value.
+	pub fn insert(&self, key: K, value: V)
+	{
+		let mut entries = self.entries.lock().expect("cache mutex should never be poisoned");
+
+		// Evict all expired entries so the cache doesn’t grow indefinitely
+		let time_to_live = self.time_to_live;
+		entries.retain(|_, (inserted_at, _)| inserted_at.elapsed() < time_to_live);
+
+		entries.insert(key, (std::time::Instant::now(), value));
+	}
+}
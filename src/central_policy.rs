@@ -0,0 +1,174 @@
+/// Location of a file with branch protection settings maintained centrally in a repository of the
+/// organization, which are overlaid on the local configuration.
+#[derive(Clone, Debug, PartialEq, schemars::JsonSchema, serde::Deserialize)]
+pub struct CentralPolicyConfig
+{
+	/// The name of the repository containing the file, which needs to be owned by the same
+	/// organization (example: `github-config`).
+	pub repository: String,
+	/// The path of the YAML or JSON file relative to the root of the repository’s default branch
+	/// (example: `branch-protection.yaml`).
+	pub path: String,
+}
+
+/// Branch protection settings maintained centrally, which replace `branch_protection` and the
+/// policies of the same names in the local configuration.
+#[derive(Clone, Debug, serde::Deserialize)]
+pub struct CentralPolicy
+{
+	/// The branch protection settings applied to default branches unless another policy is
+	/// selected, if they should be replaced.
+	#[serde(default)]
+	pub branch_protection: Option<crate::BranchProtectionSettings>,
+	/// Named branch protection policies, which are added to the local ones.
+	#[serde(default)]
+	pub policies: std::collections::HashMap<String, crate::BranchProtectionSettings>,
+}
+
+/// The centrally maintained branch protection settings that were last fetched and applied
+/// successfully along with their location, which are applied again if fetching them fails later
+/// on, for example, when reloading the configuration during an outage of GitHub.
+///
+/// The cache can safely be shared between threads, as cloning it only clones a handle to the
+/// underlying data.
+#[derive(Clone, Default)]
+pub struct CentralPolicyCache
+{
+	#[doc(hidden)]
+	last_applied: std::sync::Arc<std::sync::Mutex<Option<(CentralPolicyConfig, CentralPolicy)>>>,
+}
+
+impl CentralPolicyCache
+{
+	/// Remember settings that were fetched and applied successfully.
+	///
+	/// # Arguments
+	/// - `central_policy_config`: The location the settings were fetched from.
+	/// - `central_policy`: The settings.
+	fn store(&self, central_policy_config: CentralPolicyConfig, central_policy: CentralPolicy)
+	{
+		*self.last_applied.lock().expect("this call is infallible unless a thread panicked") =
+			Some((central_policy_config, central_policy));
+	}
+
+	/// The settings last fetched from a location, if any, which aren’t used if the location was
+	/// changed since.
+	///
+	/// # Arguments
+	/// - `central_policy_config`: The location the settings should have been fetched from.
+	fn get(&self, central_policy_config: &CentralPolicyConfig) -> Option<CentralPolicy>
+	{
+		match &*self.last_applied.lock().expect("this call is infallible unless a thread panicked")
+		{
+			Some((location, central_policy)) if location == central_policy_config =>
+				Some(central_policy.clone()),
+			_ => None,
+		}
+	}
+}
+
+/// Fetch the centrally maintained branch protection settings, if configured, and overlay them on a
+/// configuration. If they can’t be fetched or are invalid, a warning is logged and the settings
+/// last fetched from the same location are overlaid instead, if any, or else the local
+/// configuration is kept as is, so that a broken central repository doesn’t prevent this service
+/// from starting or reloading its configuration.
+///
+/// # Arguments
+/// - `config`: The validated local configuration, which is updated in place.
+/// - `github_api_client`: The GitHub API client used to fetch the settings.
+/// - `cache`: The settings last fetched successfully, which is updated if fetching succeeds.
+pub async fn apply_central_policy(config: &mut crate::Config,
+	github_api_client: &crate::github_api::Client, cache: &CentralPolicyCache)
+{
+	let central_policy_config = match &config.central_policy
+	{
+		Some(central_policy_config) => central_policy_config.clone(),
+		None => return,
+	};
+
+	let repository_name = &central_policy_config.repository;
+	let path = &central_policy_config.path;
+
+	let result = async
+	{
+		let contents = github_api_client.get_file_contents(github_api_client.organization(),
+			repository_name, path).await?;
+		let central_policy: CentralPolicy =
+			serde_yaml::from_slice(&contents).map_err(crate::Error::ParseCentralPolicy)?;
+
+		config.overlay_central_policy(central_policy.clone())?;
+
+		Ok::<_, crate::Error>(central_policy)
+	}.await;
+
+	let error = match result
+	{
+		Ok(central_policy) =>
+		{
+			log::info!("applied central branch protection settings from “{path}” in repository \
+				“{repository_name}”");
+			cache.store(central_policy_config, central_policy);
+			return;
+		},
+		Err(error) => error,
+	};
+
+	// The settings last fetched may have become invalid with a changed local configuration
+	if let Some(central_policy) = cache.get(&central_policy_config)
+	{
+		if config.overlay_central_policy(central_policy).is_ok()
+		{
+			log::warn!("could not apply central branch protection settings from “{path}” in \
+				repository “{repository_name}”, falling back to the ones fetched last: {error}");
+			return;
+		}
+	}
+
+	log::warn!("could not apply central branch protection settings from “{path}” in repository \
+		“{repository_name}”, falling back to the local configuration: {error}");
+}
+
+#[cfg(test)]
+mod tests
+{
+	/// The endpoint of the file with the central branch protection settings in the tests.
+	const CENTRAL_POLICY_ENDPOINT: &str =
+		"repos/example-organization/github-config/contents/branch-protection.yaml";
+
+	#[tokio::test]
+	async fn central_policy_falls_back_to_settings_fetched_last()
+	{
+		use base64::Engine as _;
+
+		let contents = base64::engine::general_purpose::STANDARD
+			.encode("policies: {central: {required_approving_review_count: 2}}");
+
+		let mock_github = crate::test_support::MockGitHub::new();
+		mock_github
+			.respond("GET", CENTRAL_POLICY_ENDPOINT, 200,
+				serde_json::json!({"encoding": "base64", "content": contents, "sha": "abc"}))
+			.respond("GET", CENTRAL_POLICY_ENDPOINT, 404, serde_json::json!({}));
+		let github_api_client = mock_github.client(serde_json::json!({})).await;
+		let local_config = || crate::test_support::config(serde_json::json!(
+		{
+			"central_policy": {"repository": "github-config", "path": "branch-protection.yaml"},
+		}));
+		let cache = super::CentralPolicyCache::default();
+
+		let mut config = local_config();
+		super::apply_central_policy(&mut config, &github_api_client, &cache).await;
+
+		assert_eq!(config.policies["central"].required_approving_review_count, 2);
+
+		let mut config = local_config();
+		super::apply_central_policy(&mut config, &github_api_client, &cache).await;
+
+		assert_eq!(config.policies["central"].required_approving_review_count, 2);
+		assert_eq!(mock_github.requests("GET", CENTRAL_POLICY_ENDPOINT).len(), 2);
+
+		let mut config = local_config();
+		super::apply_central_policy(&mut config, &github_api_client, &Default::default()).await;
+
+		assert!(!config.policies.contains_key("central"));
+	}
+}
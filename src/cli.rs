@@ -0,0 +1,102 @@
+/// Command-line arguments of this application.
+#[derive(clap::Parser)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
+pub struct Arguments
+{
+	/// The command to run (default: serve webhook events).
+	#[command(subcommand)]
+	pub command: Option<Command>,
+	/// Handle a single webhook event whose payload is read from this file instead of serving
+	/// webhook events, print the outcome, and exit (requires `--event`).
+	#[arg(long, value_name = "PAYLOAD_FILE", requires = "event")]
+	pub once: Option<std::path::PathBuf>,
+	/// The type of the webhook event handled with `--once`.
+	#[arg(long, value_enum, requires = "once")]
+	pub event: Option<EventType>,
+	/// Allow failing GitHub API requests as configured in `debug.fault_injection`, which is only
+	/// meant for staging environments.
+	#[arg(long, hide = true)]
+	pub allow_fault_injection: bool,
+}
+
+/// Commands supported by this application in addition to serving webhook events.
+#[derive(clap::Subcommand)]
+pub enum Command
+{
+	/// Export the current protection settings of the default branches of all repositories in the
+	/// organization.
+	Export
+	{
+		/// The format of the report.
+		#[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+		format: ExportFormat,
+		/// Write the report to this file instead of the standard output.
+		#[arg(long)]
+		output: Option<std::path::PathBuf>,
+	},
+	/// Protect the existing default branches and branches matching the configured protected
+	/// branches and patterns of all repositories in the organization that aren’t protected yet,
+	/// and print how many branches were protected per rule.
+	Backfill
+	{
+		/// How many repositories to process at the same time.
+		#[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u16).range(1..))]
+		concurrency: u16,
+		/// Exit with a nonzero status if any repository couldn’t be processed completely,
+		/// including repositories the GitHub App isn’t installed for or lacks permissions for.
+		#[arg(long)]
+		fail_on_error: bool,
+	},
+	/// Retry protecting the branches of all failed records in a file of event records as written
+	/// to the event sink, such as after a prolonged GitHub outage, remove the records of recovered
+	/// branches from the file, and print how many branches were recovered.
+	RetryFailed
+	{
+		/// The file containing the event records, one JSON object per line.
+		#[arg(long, value_name = "RECORDS_FILE")]
+		records: std::path::PathBuf,
+		/// How many branches to process at the same time (default: `max_concurrent_events` if
+		/// configured, otherwise 4).
+		#[arg(long, value_parser = clap::value_parser!(u16).range(1..))]
+		concurrency: Option<u16>,
+		/// Exit with a nonzero status if any branch still couldn’t be protected.
+		#[arg(long)]
+		fail_on_error: bool,
+	},
+	/// Check whether a payload signature is valid for a webhook secret, as when receiving a
+	/// webhook event, to debug signature mismatches without the running service. This doesn’t
+	/// read the configuration.
+	VerifySignature
+	{
+		/// The file containing the raw payload exactly as delivered.
+		#[arg(long, value_name = "PAYLOAD_FILE")]
+		payload: std::path::PathBuf,
+		/// The webhook secret to verify the signature with.
+		#[arg(long)]
+		secret: String,
+		/// The value of the signature header as delivered (example: `sha256=757107ea…`).
+		#[arg(long)]
+		signature: String,
+	},
+	/// Print a JSON Schema of the configuration file, which editors can use for autocompletion and
+	/// validation. This doesn’t read the configuration.
+	Schema,
+}
+
+/// Formats in which branch protection reports can be exported.
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum ExportFormat
+{
+	Json,
+	Csv,
+}
+
+/// Types of webhook events that can be handled with `--once`.
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum EventType
+{
+	Create,
+	Push,
+	BranchProtectionRule,
+	Repository,
+}
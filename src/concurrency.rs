@@ -0,0 +1,211 @@
+/// Limits how many webhook events are handled in the background at the same time, both in total
+/// and per organization, so that a burst of events in one organization doesn’t delay handling
+/// events in other organizations beyond the total limit.
+///
+/// The limiter can safely be shared between threads, as cloning it only clones handles to the
+/// underlying data.
+#[derive(Clone)]
+pub struct ConcurrencyLimiter
+{
+	#[doc(hidden)]
+	global_semaphore: Option<std::sync::Arc<tokio::sync::Semaphore>>,
+	#[doc(hidden)]
+	max_concurrent_events_per_organization: Option<usize>,
+	#[doc(hidden)]
+	// Semaphores are created on demand for each organization and kept for reuse
+	organization_semaphores: std::sync::Arc<std::sync::Mutex<
+		std::collections::HashMap<String, std::sync::Arc<tokio::sync::Semaphore>>>>,
+}
+
+impl ConcurrencyLimiter
+{
+	/// Create a new concurrency limiter.
+	///
+	/// # Arguments
+	/// - `max_concurrent_events`: How many events to handle at the same time in total, if limited.
+	/// - `max_concurrent_events_per_organization`: How many events to handle at the same time per
+	///   organization, if limited.
+	pub fn new(max_concurrent_events: Option<usize>,
+		max_concurrent_events_per_organization: Option<usize>)
+		-> Self
+	{
+		Self
+		{
+			global_semaphore: max_concurrent_events
+				.map(|limit| std::sync::Arc::new(tokio::sync::Semaphore::new(limit))),
+			max_concurrent_events_per_organization,
+			organization_semaphores: Default::default(),
+		}
+	}
+
+	/// Wait until an event in the given organization may be handled within the limits, returning a
+	/// permit that needs to be held while handling the event.
+	///
+	/// # Arguments
+	/// - `organization_name`: The organization the event is reported for.
+	pub async fn acquire(&self, organization_name: &str) -> ConcurrencyPermit
+	{
+		let organization_semaphore = self.max_concurrent_events_per_organization
+			.map(|limit|
+			{
+				// A poisoned lock only means that another task panicked while looking up a
+				// semaphore, which leaves the map intact
+				let mut organization_semaphores = self.organization_semaphores.lock()
+					.unwrap_or_else(std::sync::PoisonError::into_inner);
+
+				organization_semaphores.entry(organization_name.to_owned())
+					.or_insert_with(|| std::sync::Arc::new(tokio::sync::Semaphore::new(limit)))
+					.clone()
+			});
+
+		// Wait for the organization’s limit first so that events queued in a busy organization
+		// don’t take up permits of the total limit, which would delay other organizations
+		let organization_permit = match organization_semaphore
+		{
+			Some(semaphore) => Some(semaphore.acquire_owned().await
+				.expect("this call is infallible because semaphores are never closed")),
+			None => None,
+		};
+
+		let global_permit = match &self.global_semaphore
+		{
+			Some(semaphore) => Some(semaphore.clone().acquire_owned().await
+				.expect("this call is infallible because semaphores are never closed")),
+			None => None,
+		};
+
+		ConcurrencyPermit
+		{
+			_organization_permit: organization_permit,
+			_global_permit: global_permit,
+		}
+	}
+}
+
+/// Permit to handle an event within the concurrency limits, which are released when dropped.
+pub struct ConcurrencyPermit
+{
+	#[doc(hidden)]
+	_organization_permit: Option<tokio::sync::OwnedSemaphorePermit>,
+	#[doc(hidden)]
+	_global_permit: Option<tokio::sync::OwnedSemaphorePermit>,
+}
+
+/// Keeps track of the webhook events being handled in the background, so that shutting down can
+/// wait until all of them have been handled instead of abandoning them halfway.
+///
+/// The tracker can safely be shared between threads, as cloning it only clones a handle to the
+/// underlying data.
+#[derive(Clone)]
+pub struct PendingEvents
+{
+	#[doc(hidden)]
+	// Holds the number of events being handled, which waiting for pending events watches
+	count: std::sync::Arc<tokio::sync::watch::Sender<usize>>,
+}
+
+impl Default for PendingEvents
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			count: std::sync::Arc::new(tokio::sync::watch::channel(0).0),
+		}
+	}
+}
+
+impl PendingEvents
+{
+	/// Handle a webhook event in a separate task, keeping track of it until it’s handled.
+	///
+	/// # Arguments
+	/// - `future`: The future handling the webhook event.
+	pub fn spawn<F>(&self, future: F)
+	where
+		F: std::future::Future<Output = ()> + Send + 'static,
+	{
+		self.count.send_modify(|count| *count += 1);
+
+		// The event is also no longer pending if handling it panics, which drops the guard
+		let guard = PendingEvent{count: self.count.clone()};
+
+		tokio::spawn(
+			async move
+			{
+				let _guard = guard;
+				future.await;
+			});
+	}
+
+	/// The number of webhook events currently being handled in the background.
+	pub fn count(&self) -> usize
+	{
+		*self.count.borrow()
+	}
+
+	/// Wait until all webhook events handled in the background have been handled.
+	pub async fn wait(&self)
+	{
+		let mut receiver = self.count.subscribe();
+
+		// The channel can’t be closed while this tracker holds the sender
+		let _ = receiver.wait_for(|count| *count == 0).await;
+	}
+}
+
+/// Guard marking a webhook event as no longer pending when dropped.
+#[doc(hidden)]
+struct PendingEvent
+{
+	#[doc(hidden)]
+	count: std::sync::Arc<tokio::sync::watch::Sender<usize>>,
+}
+
+impl Drop for PendingEvent
+{
+	fn drop(&mut self)
+	{
+		self.count.send_modify(|count| *count -= 1);
+	}
+}
+
+/// Serializes operations per repository that can’t safely run at the same time, such as updating a
+/// repository’s status issue, which would otherwise lose lines or create duplicate issues.
+///
+/// The locks can safely be shared between threads, as cloning them only clones handles to the
+/// underlying data.
+#[derive(Clone, Default)]
+pub struct RepositoryLocks
+{
+	#[doc(hidden)]
+	// Locks are created on demand for each repository and kept for reuse
+	locks: std::sync::Arc<std::sync::Mutex<
+		std::collections::HashMap<String, std::sync::Arc<tokio::sync::Mutex<()>>>>>,
+}
+
+impl RepositoryLocks
+{
+	/// Wait until no other task holds the lock of a repository, returning a guard that releases
+	/// the lock when dropped.
+	///
+	/// # Arguments
+	/// - `organization_name`: The organization owning the repository.
+	/// - `repository_name`: The name of the repository.
+	pub async fn lock(&self, organization_name: &str, repository_name: &str)
+		-> tokio::sync::OwnedMutexGuard<()>
+	{
+		let lock =
+		{
+			// A poisoned lock only means that another task panicked while looking up a lock,
+			// which leaves the map intact
+			let mut locks = self.locks.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+
+			locks.entry(format!("{organization_name}/{repository_name}").to_lowercase())
+				.or_default()
+				.clone()
+		};
+
+		lock.lock_owned().await
+	}
+}
@@ -1,4 +1,4 @@
-#[derive(serde::Deserialize)]
+#[derive(schemars::JsonSchema, serde::Deserialize)]
 /// Top-level configuration of this application.
 ///
 /// Currently, only GitHub-API-specific configuration options are available, but this can be
@@ -7,6 +7,251 @@ pub struct Config
 {
 	/// Configuration options specific to the GitHub API and authentication.
 	pub github_api: crate::github_api::Config,
+	/// The branch protection settings applied to default branches unless another policy is
+	/// selected (optional, default: require one approving review and enforce this for
+	/// administrators).
+	#[serde(default)]
+	pub branch_protection: BranchProtectionSettings,
+	/// Named branch protection policies that can be selected for all or individual repositories
+	/// (optional, default: none).
+	#[serde(default)]
+	pub policies: std::collections::HashMap<String, BranchProtectionSettings>,
+	/// The name of the policy applied to repositories without a policy of their own instead of
+	/// `branch_protection` (optional, default: apply `branch_protection`).
+	pub default_policy: Option<String>,
+	/// Names of the policies applied to individual repositories, by repository name (optional,
+	/// default: apply the default policy to all repositories).
+	#[serde(default)]
+	pub repository_policies: std::collections::HashMap<String, String>,
+	/// Only protect the default branches of repositories tagged with at least one of these topics
+	/// (optional, default: protect the default branches of all repositories).
+	#[serde(default)]
+	pub required_topics: Vec<String>,
+	/// Skip repositories containing a file at this path in their default branch, so that teams
+	/// can opt repositories out of being protected by committing a marker file (optional, default:
+	/// no opt-out, example: `.github/no-auto-protect`).
+	pub opt_out_file: Option<String>,
+	/// Names of branches to protect when they’re created in addition to the default branch, such
+	/// as `release`, matched exactly and case-sensitively (optional, default: only protect the
+	/// default branch).
+	#[serde(default)]
+	pub protected_branches: Vec<String>,
+	/// Patterns of branch names to protect in addition to the default branch, where `*` matches
+	/// any characters except `/`, `**` matches any characters, and `?` matches a single character
+	/// except `/`, such as `release/*` (optional, default: none).
+	#[serde(default)]
+	pub protected_branch_patterns: Vec<String>,
+	/// Patterns of branch names never to protect, with the same syntax as
+	/// `protected_branch_patterns`, such as `dependabot/**`. These take precedence over all other
+	/// settings, including for default branches (optional, default: none).
+	#[serde(default)]
+	pub branch_deny_patterns: Vec<String>,
+	/// Which predicates decide whether to protect branches other than the default branch when
+	/// they’re created, of which at least one needs to hold (optional, default: only protect
+	/// branches matching `protected_branches` or `protected_branch_patterns`).
+	#[serde(default)]
+	pub branch_predicates: BranchPredicatesConfig,
+	/// Names of the policies applied to branches matching individual entries of
+	/// `protected_branches` or `protected_branch_patterns`, by entry, which take precedence over
+	/// the policy of the repository (optional, default: apply the policy of the repository to all
+	/// branches).
+	#[serde(default)]
+	pub branch_policies: std::collections::HashMap<String, String>,
+	/// How to acknowledge webhook events that are handled in the background (optional).
+	#[serde(default)]
+	pub acknowledgment: AcknowledgmentConfig,
+	/// The HTTP status code to respond with to webhook events this service doesn’t act on, which
+	/// needs to be a success status code (optional, default: 200).
+	#[serde(default = "default_ignored_event_status")]
+	pub ignored_event_status: u16,
+	/// Acknowledge webhook events of types this service doesn’t handle, such as `pull_request`,
+	/// with 200 OK regardless of `ignored_event_status`, once their source IP address and payload
+	/// signature were verified, so that deliveries from other sources are still rejected. This is
+	/// meant for webhooks shared with other integrations (optional, default: `false`).
+	#[serde(default)]
+	pub acknowledge_unhandled_events: bool,
+	/// Reject webhook payloads larger than this number of bytes, which needs to be between 1 kB
+	/// and 25 MB, the largest payload GitHub sends. This applies to all event types, including
+	/// push events, unless overridden in `max_payload_size_bytes_by_event` (optional, default:
+	/// 256 kB, except for push events, whose payloads may list many commits, and events this
+	/// service doesn’t handle: 25 MB).
+	pub max_payload_size_bytes: Option<u64>,
+	/// Limits on the size of webhook payloads in bytes by event type (example: `push`), which take
+	/// precedence over `max_payload_size_bytes` and need to be in the same range. Only the event
+	/// types this service handles can be listed (optional, default: none).
+	#[serde(default)]
+	pub max_payload_size_bytes_by_event: std::collections::HashMap<String, u64>,
+	/// Add the security headers `X-Content-Type-Options: nosniff`, `X-Frame-Options: DENY`,
+	/// `Referrer-Policy: no-referrer`, and `Content-Security-Policy: default-src 'none'` to all
+	/// replies (optional, default: `true`).
+	#[serde(default = "default_security_headers")]
+	pub security_headers: bool,
+	/// Additional headers to add to all replies by name, such as headers expected by gateways,
+	/// which take precedence over the security headers (optional, default: none).
+	#[serde(default)]
+	pub response_headers: std::collections::HashMap<String, String>,
+	/// Also protect default branches that weren’t protected when they’re pushed for the first time,
+	/// which requires subscribing the GitHub App to push events (optional, default: `false`).
+	#[serde(default)]
+	pub protect_on_first_push: bool,
+	/// Only protect branches that have at least one commit, leaving branches without commits to be
+	/// protected when they’re pushed to, which requires `protect_on_first_push` (optional,
+	/// default: `false`).
+	#[serde(default)]
+	pub require_commits_before_protecting: bool,
+	/// Also protect branches of forked repositories, which usually track another repository and
+	/// don’t need protection of their own (optional, default: `false`).
+	#[serde(default)]
+	pub protect_forks: bool,
+	/// Also act on webhook events caused by this GitHub App itself, such as branch protection rule
+	/// events caused by restoring a deleted rule. By default, these are ignored to avoid loops
+	/// (optional, default: `false`).
+	#[serde(default)]
+	pub act_on_self: bool,
+	/// Wait this number of seconds after receiving a webhook event before protecting a branch,
+	/// as repositories created from templates may still change for a few seconds after their
+	/// creation. This doesn’t delay acknowledging webhook events (optional, default: 0).
+	#[serde(default)]
+	pub protection_delay_seconds: u64,
+	/// Additionally wait a random number of milliseconds up to this number before protecting a
+	/// branch, so that the GitHub API requests for many repositories created at once, such as by a
+	/// script, are spread out instead of being sent in a burst. This doesn’t delay acknowledging
+	/// webhook events (optional, default: 0).
+	#[serde(default)]
+	pub protection_jitter_milliseconds: u64,
+	/// Ignore ref creation and push events if the repository was last pushed to more than this
+	/// number of seconds before the event is received, as GitHub may redeliver events days later,
+	/// when acting on them could be surprising. Branch protection rule events don’t include a
+	/// suitable timestamp and are always acted on (optional, default: no age limit).
+	pub max_event_age_seconds: Option<u64>,
+	/// Report a degraded service, for example, when access tokens repeatedly can’t be obtained,
+	/// with 503 Service Unavailable at `/health` instead of 200 OK, so that orchestrators can react
+	/// to it (optional, default: `false`).
+	#[serde(default)]
+	pub strict_health_check: bool,
+	/// How many webhook events to handle in the background at the same time in total, while
+	/// further events wait (optional, default: no limit).
+	pub max_concurrent_events: Option<usize>,
+	/// How many webhook events to handle in the background at the same time per organization, so
+	/// that a burst of events in one organization doesn’t delay other organizations beyond the
+	/// total limit (optional, default: `max_concurrent_events`).
+	pub max_concurrent_events_per_organization: Option<usize>,
+	/// Don’t change anything but log the request that would protect each branch along with the
+	/// changes it would make to the branch’s current protection settings, for auditing before
+	/// enforcing protection (optional, default: `false`).
+	#[serde(default)]
+	pub dry_run: bool,
+	/// Temporarily stop acting on webhook events during incidents, which are still acknowledged
+	/// with the acknowledgment code `maintenance` so that GitHub doesn’t consider the webhook
+	/// failing, and only log what would have been done. Unlike `dry_run`, this doesn’t make any
+	/// GitHub API requests for acting on events, including ones still waiting to be handled, and
+	/// is meant to be toggled by reloading the configuration (optional, default: `false`).
+	#[serde(default)]
+	pub maintenance_mode: bool,
+	/// How to handle branches that are found to be protected already when they’re pushed for the
+	/// first time but whose settings differ from the configured ones (optional, default: `skip`).
+	#[serde(default)]
+	pub existing_protection_policy: ExistingProtectionPolicy,
+	/// How to react to branch protection rules of protected branches being deleted manually,
+	/// which requires subscribing the GitHub App to branch protection rule events (optional,
+	/// default: only log this).
+	#[serde(default)]
+	pub deleted_protection: DeletedProtectionConfig,
+	/// Record a “Branch protection applied” check run with the applied settings on the commit a
+	/// branch points to after protecting it, which requires the `checks:write` permission.
+	/// Failures to create check runs are logged but don’t affect the outcome (optional, default:
+	/// `false`).
+	#[serde(default)]
+	pub report_check_run: bool,
+	/// How users and teams are notified about actions taken on their branches (optional).
+	#[serde(default)]
+	pub notification: NotificationConfig,
+	/// The IANA name of the timezone timestamps in notifications are shown in, such as the date in
+	/// status issues and the `{timestamp}` placeholder of `notification.issue_body_template`,
+	/// while logs and event records keep using UTC (optional, default: `UTC`, example:
+	/// `Europe/Berlin`).
+	pub display_timezone: Option<String>,
+	/// Post an alert to a webhook, such as a Slack incoming webhook, whenever handling a webhook
+	/// event fails permanently, that is, after all retries (optional, default: only log failures).
+	pub failure_notification: Option<crate::FailureNotificationConfig>,
+	/// Run an external command for each webhook event this service would act on, which decides
+	/// whether to act on the event and may select a policy, for rules that are awkward to express
+	/// in the configuration (optional, default: act on all events as configured).
+	pub decision_hook: Option<crate::DecisionHookConfig>,
+	/// Overlay the branch protection settings and policies in a YAML or JSON file maintained in a
+	/// repository of the organization on the local ones, which is fetched at startup and whenever
+	/// the configuration is reloaded. If the file can’t be fetched or is invalid, the local
+	/// settings apply (optional, default: only use the local settings).
+	pub central_policy: Option<crate::CentralPolicyConfig>,
+	/// Which validation errors to retry when protecting branches, as GitHub occasionally fails to
+	/// find freshly created branches (optional, default: don’t retry validation errors).
+	#[serde(default)]
+	pub protect_branch_retry: ProtectBranchRetryConfig,
+	/// If GitHub rejects branch protection settings with a validation error (422 Unprocessable
+	/// Entity) naming optional settings, such as settings unavailable on GitHub Enterprise Server
+	/// or for restricted repositories, retry without them one at a time, so that the branch is
+	/// protected as well as possible, and list the settings that couldn’t be applied in the
+	/// notification about the protected branch, unless it’s posted before protecting the branch
+	/// (see `notification.order`), in which case they’re only logged. Only strict status checks,
+	/// status checks, code owner reviews, and dismissing stale reviews are optional (optional,
+	/// default: `false`, that is, fail if any setting is rejected).
+	#[serde(default)]
+	pub best_effort_protection: bool,
+	/// Instead of applying branch protection via the API, propose it as a ruleset file in a pull
+	/// request against the default branch, so that it’s reviewed and version-controlled. The pull
+	/// request replaces the notification and requires the `contents:write` and
+	/// `pull_requests:write` permissions. Merged ruleset files only protect the branch once a
+	/// separate process imports them. This only applies to branches protected when handling
+	/// webhook events, while backfilling and restoring deleted branch protection rules still use
+	/// the API (optional, default: apply branch protection via the API).
+	pub ruleset_pull_request: Option<crate::RulesetPullRequestConfig>,
+	/// Where to write machine-readable records of handled webhook events to (optional, default:
+	/// `stdout`).
+	#[serde(default)]
+	pub event_sink: crate::EventSinkConfig,
+	/// Only accept webhook events from the IP addresses on this allow list (optional, default:
+	/// accept webhook events from all IP addresses).
+	pub source_ip_allow_list: Option<crate::SourceIpAllowListConfig>,
+	/// Write the raw payload of each webhook event whose signature was verified to a timestamped
+	/// file in this directory for forensics. Captured payloads may contain sensitive information
+	/// and are only readable by the user running this service. This requires payloads to be
+	/// verified, usually with a webhook secret (optional, default: don’t capture payloads).
+	pub payload_capture_dir: Option<std::path::PathBuf>,
+	/// How many captured payloads to keep before deleting the oldest ones (optional,
+	/// default: 1000).
+	#[serde(default = "default_payload_capture_max_files")]
+	pub payload_capture_max_files: usize,
+	/// Export a trace per webhook event with spans for each GitHub API request to the
+	/// OpenTelemetry collector listening at this OTLP/gRPC endpoint (optional, default: don’t
+	/// export traces).
+	pub otel_endpoint: Option<url::Url>,
+	/// Additionally send all metrics served at `/metrics` as StatsD metrics via UDP to this address
+	/// (example: `localhost:8125`, optional, default: don’t send StatsD metrics).
+	pub statsd_address: Option<String>,
+	/// Options for testing this service in staging environments, which are deliberately left out
+	/// of the documentation and the schema (optional).
+	#[doc(hidden)]
+	#[serde(default)]
+	#[schemars(skip)]
+	pub debug: crate::DebugConfig,
+}
+
+#[doc(hidden)]
+fn default_security_headers() -> bool
+{
+	true
+}
+
+#[doc(hidden)]
+fn default_ignored_event_status() -> u16
+{
+	200
+}
+
+#[doc(hidden)]
+fn default_payload_capture_max_files() -> usize
+{
+	1000
 }
 
 impl Config
@@ -20,6 +265,1368 @@ impl Config
 		P: AsRef<std::path::Path>
 	{
 		let file = std::fs::File::open(&path).map_err(crate::Error::ReadConfigFile)?;
-		serde_yaml::from_reader(&file).map_err(crate::Error::ParseConfigFile)
+		let mut config: serde_yaml::Value =
+			serde_yaml::from_reader(&file).map_err(crate::Error::ParseConfigFile)?;
+
+		// Inject secrets and other values from the environment before interpreting the options
+		expand_environment_variables(&mut config)?;
+
+		let mut config: Self =
+			serde_yaml::from_value(config).map_err(crate::Error::ParseConfigFile)?;
+
+		config.github_api.read_secret_files()?;
+		config.github_api.validate()?;
+		config.validate()?;
+
+		Ok(config)
+	}
+
+	/// A JSON Schema of the configuration file derived from the configuration types, including
+	/// their documentation as descriptions. Constraints that are only checked after parsing, such
+	/// as valid team slugs, aren’t part of the schema.
+	pub fn json_schema() -> String
+	{
+		serde_json::to_string_pretty(&schemars::schema_for!(Self))
+			.expect("this call is infallible because schemas are plain data")
+	}
+
+	/// The entry of `protected_branches` or `protected_branch_patterns` matching a branch name, if
+	/// any, with exact names taking precedence over patterns. This doesn’t cover default branches,
+	/// which are always protected.
+	///
+	/// # Arguments
+	/// - `branch_name`: The name of the branch.
+	pub fn protected_branch_rule(&self, branch_name: &str) -> Option<&str>
+	{
+		self.protected_branches.iter()
+			.find(|protected_branch| *protected_branch == branch_name)
+			.or_else(|| self.protected_branch_patterns.iter()
+				.find(|pattern| crate::matches_branch_pattern(pattern, branch_name)))
+			.map(String::as_str)
+	}
+
+	/// The first entry of `branch_deny_patterns` matching a branch name, if any, in which case the
+	/// branch must never be protected.
+	///
+	/// # Arguments
+	/// - `branch_name`: The name of the branch.
+	pub fn branch_deny_pattern(&self, branch_name: &str) -> Option<&str>
+	{
+		self.branch_deny_patterns.iter()
+			.find(|pattern| crate::matches_branch_pattern(pattern, branch_name))
+			.map(String::as_str)
+	}
+
+	/// The timezone timestamps in notifications are shown in, as configured by `display_timezone`.
+	pub fn display_timezone(&self) -> chrono_tz::Tz
+	{
+		match &self.display_timezone
+		{
+			Some(display_timezone) => display_timezone.parse()
+				.expect("this call is infallible because the timezone was validated"),
+			None => chrono_tz::UTC,
+		}
+	}
+
+	/// Keep the options of a running configuration that are only applied at startup, such as the
+	/// GitHub API client’s options, and return the names of those that differ in this
+	/// configuration, so that reloading the configuration can report them as ignored.
+	///
+	/// # Arguments
+	/// - `running`: The configuration this service is currently running with.
+	pub fn keep_restart_only_options(&mut self, running: &Self) -> Vec<&'static str>
+	{
+		let mut changed_options = vec![];
+
+		if self.github_api != running.github_api
+		{
+			changed_options.push("github_api");
+			self.github_api = running.github_api.clone();
+		}
+
+		if self.debug != running.debug
+		{
+			changed_options.push("debug");
+			self.debug = running.debug.clone();
+		}
+
+		if self.event_sink != running.event_sink
+		{
+			changed_options.push("event_sink");
+			self.event_sink = running.event_sink.clone();
+		}
+
+		if self.source_ip_allow_list != running.source_ip_allow_list
+		{
+			changed_options.push("source_ip_allow_list");
+			self.source_ip_allow_list = running.source_ip_allow_list.clone();
+		}
+
+		if self.payload_capture_dir != running.payload_capture_dir
+		{
+			changed_options.push("payload_capture_dir");
+			self.payload_capture_dir = running.payload_capture_dir.clone();
+		}
+
+		if self.payload_capture_max_files != running.payload_capture_max_files
+		{
+			changed_options.push("payload_capture_max_files");
+			self.payload_capture_max_files = running.payload_capture_max_files;
+		}
+
+		if self.max_concurrent_events != running.max_concurrent_events
+		{
+			changed_options.push("max_concurrent_events");
+			self.max_concurrent_events = running.max_concurrent_events;
+		}
+
+		if self.max_concurrent_events_per_organization
+			!= running.max_concurrent_events_per_organization
+		{
+			changed_options.push("max_concurrent_events_per_organization");
+			self.max_concurrent_events_per_organization =
+				running.max_concurrent_events_per_organization;
+		}
+
+		if self.otel_endpoint != running.otel_endpoint
+		{
+			changed_options.push("otel_endpoint");
+			self.otel_endpoint = running.otel_endpoint.clone();
+		}
+
+		if self.max_payload_size_bytes != running.max_payload_size_bytes
+		{
+			changed_options.push("max_payload_size_bytes");
+			self.max_payload_size_bytes = running.max_payload_size_bytes;
+		}
+
+		if self.max_payload_size_bytes_by_event != running.max_payload_size_bytes_by_event
+		{
+			changed_options.push("max_payload_size_bytes_by_event");
+			self.max_payload_size_bytes_by_event = running.max_payload_size_bytes_by_event.clone();
+		}
+
+		if self.statsd_address != running.statsd_address
+		{
+			changed_options.push("statsd_address");
+			self.statsd_address = running.statsd_address.clone();
+		}
+
+		if self.security_headers != running.security_headers
+		{
+			changed_options.push("security_headers");
+			self.security_headers = running.security_headers;
+		}
+
+		if self.response_headers != running.response_headers
+		{
+			changed_options.push("response_headers");
+			self.response_headers = running.response_headers.clone();
+		}
+
+		changed_options
+	}
+
+	/// Check the configuration for invalid values that can’t be detected while parsing.
+	fn validate(&self) -> Result<(), crate::Error>
+	{
+		self.branch_protection.validate("branch_protection")?;
+
+		if let Some(fault_injection) = &self.debug.fault_injection
+		{
+			fault_injection.validate()?;
+		}
+
+		for (policy_name, policy) in &self.policies
+		{
+			policy.validate(&format!("policies.{policy_name}"))?;
+		}
+
+		// Make sure that all referenced policies exist so that typos are detected at startup
+		if let Some(policy_name) = &self.default_policy
+		{
+			if !self.policies.contains_key(policy_name)
+			{
+				return Err(crate::Error::InvalidConfig(
+					format!("default_policy references unknown policy “{policy_name}”")));
+			}
+		}
+
+		for (repository_name, policy_name) in &self.repository_policies
+		{
+			if !self.policies.contains_key(policy_name)
+			{
+				return Err(crate::Error::InvalidConfig(format!("the policy of repository \
+					“{repository_name}” references unknown policy “{policy_name}”")));
+			}
+		}
+
+		for (rule, policy_name) in &self.branch_policies
+		{
+			if !self.protected_branches.contains(rule)
+				&& !self.protected_branch_patterns.contains(rule)
+			{
+				return Err(crate::Error::InvalidConfig(format!("branch_policies references \
+					“{rule}”, which is neither in protected_branches nor in \
+					protected_branch_patterns")));
+			}
+
+			if !self.policies.contains_key(policy_name)
+			{
+				return Err(crate::Error::InvalidConfig(format!("the policy of branches matching \
+					“{rule}” references unknown policy “{policy_name}”")));
+			}
+		}
+
+		self.notification.validate()?;
+
+		if let Some(ruleset_pull_request) = &self.ruleset_pull_request
+		{
+			ruleset_pull_request.validate()?;
+		}
+
+		if self.protected_branch_patterns.iter().any(String::is_empty)
+		{
+			return Err(crate::Error::InvalidConfig(
+				"protected_branch_patterns must not contain empty patterns".to_string()));
+		}
+
+		if self.branch_deny_patterns.iter().any(String::is_empty)
+		{
+			return Err(crate::Error::InvalidConfig(
+				"branch_deny_patterns must not contain empty patterns".to_string()));
+		}
+
+		if let Some(display_timezone) = &self.display_timezone
+		{
+			if display_timezone.parse::<chrono_tz::Tz>().is_err()
+			{
+				return Err(crate::Error::InvalidConfig(format!("display_timezone \
+					“{display_timezone}” is not a valid IANA timezone name (example: \
+					Europe/Berlin)")));
+			}
+		}
+
+		match warp::http::StatusCode::from_u16(self.ignored_event_status)
+		{
+			Ok(status_code) if status_code.is_success() => (),
+			_ => return Err(crate::Error::InvalidConfig(format!("ignored_event_status must be a \
+				success status code (2xx), but is {}", self.ignored_event_status))),
+		}
+
+		self.parse_response_headers()?;
+
+		if let Some(max_payload_size_bytes) = self.max_payload_size_bytes
+		{
+			if !(MINIMUM_PAYLOAD_SIZE_LIMIT..=MAXIMUM_PAYLOAD_SIZE_LIMIT)
+				.contains(&max_payload_size_bytes)
+			{
+				return Err(crate::Error::InvalidConfig(format!("max_payload_size_bytes must be \
+					between {MINIMUM_PAYLOAD_SIZE_LIMIT} and {MAXIMUM_PAYLOAD_SIZE_LIMIT}, but is \
+					{max_payload_size_bytes}")));
+			}
+		}
+
+		for (event_type, max_payload_size_bytes) in &self.max_payload_size_bytes_by_event
+		{
+			if !crate::HANDLED_EVENT_TYPES.contains(&event_type.as_str())
+			{
+				return Err(crate::Error::InvalidConfig(format!("max_payload_size_bytes_by_event \
+					lists “{event_type}”, which isn’t one of the handled event types {}",
+					crate::HANDLED_EVENT_TYPES.join(", "))));
+			}
+
+			if !(MINIMUM_PAYLOAD_SIZE_LIMIT..=MAXIMUM_PAYLOAD_SIZE_LIMIT)
+				.contains(max_payload_size_bytes)
+			{
+				return Err(crate::Error::InvalidConfig(format!("max_payload_size_bytes_by_event \
+					for “{event_type}” must be between {MINIMUM_PAYLOAD_SIZE_LIMIT} and \
+					{MAXIMUM_PAYLOAD_SIZE_LIMIT}, but is {max_payload_size_bytes}")));
+			}
+		}
+
+		// Otherwise, anybody could make this service write arbitrary payloads to disk
+		if self.payload_capture_dir.is_some() && !self.github_api.verifies_payloads()
+		{
+			return Err(crate::Error::InvalidConfig("payload_capture_dir requires a webhook secret, \
+				as payloads can’t be verified otherwise".to_string()));
+		}
+
+		if self.payload_capture_max_files == 0
+		{
+			return Err(crate::Error::InvalidConfig(
+				"payload_capture_max_files must be at least 1".to_string()));
+		}
+
+		if self.max_concurrent_events == Some(0)
+			|| self.max_concurrent_events_per_organization == Some(0)
+		{
+			return Err(crate::Error::InvalidConfig("max_concurrent_events and \
+				max_concurrent_events_per_organization must be at least 1".to_string()));
+		}
+
+		// Otherwise, branches without commits would never be protected
+		if self.require_commits_before_protecting && !self.protect_on_first_push
+		{
+			return Err(crate::Error::InvalidConfig("require_commits_before_protecting requires \
+				protect_on_first_push".to_string()));
+		}
+
+		Ok(())
+	}
+
+	/// Overlay centrally maintained branch protection settings on this configuration, replacing
+	/// `branch_protection` and the policies of the same names. If the result is invalid, for
+	/// example, because a policy is invalid, this configuration is left unchanged.
+	///
+	/// # Arguments
+	/// - `central_policy`: The centrally maintained settings.
+	pub fn overlay_central_policy(&mut self, central_policy: crate::CentralPolicy)
+		-> Result<(), crate::Error>
+	{
+		let local_branch_protection = self.branch_protection.clone();
+		let local_policies = self.policies.clone();
+
+		if let Some(branch_protection) = central_policy.branch_protection
+		{
+			self.branch_protection = branch_protection;
+		}
+
+		self.policies.extend(central_policy.policies);
+
+		if let Err(error) = self.validate()
+		{
+			self.branch_protection = local_branch_protection;
+			self.policies = local_policies;
+			return Err(error);
+		}
+
+		Ok(())
+	}
+
+	/// The branch protection settings applied to a repository, which are either the ones of the
+	/// policy selected for the repository, the default policy, or `branch_protection` in that
+	/// order of precedence.
+	///
+	/// # Arguments
+	/// - `repository_name`: The name of the repository.
+	pub fn branch_protection_for(&self, repository_name: &str) -> &BranchProtectionSettings
+	{
+		let policy_name = self.repository_policies.get(repository_name)
+			.or(self.default_policy.as_ref());
+
+		match policy_name
+		{
+			Some(policy_name) => self.policies.get(policy_name)
+				.expect("this call is infallible because policy references were validated"),
+			None => &self.branch_protection,
+		}
+	}
+
+	/// The branch protection settings applied to a branch, which are the ones of the policy
+	/// selected for the entry of `protected_branches` or `protected_branch_patterns` matching the
+	/// branch, if any, or else the ones applied to the repository (see
+	/// [Self::branch_protection_for]). Default branches always get the settings applied to the
+	/// repository.
+	///
+	/// # Arguments
+	/// - `repository_name`: The name of the repository.
+	/// - `branch_name`: The name of the branch.
+	/// - `is_default_branch`: Whether the branch is the default branch of the repository.
+	pub fn branch_protection_for_branch(&self, repository_name: &str, branch_name: &str,
+		is_default_branch: bool)
+		-> &BranchProtectionSettings
+	{
+		let policy_name = match is_default_branch
+		{
+			true => None,
+			false => self.protected_branch_rule(branch_name)
+				.and_then(|rule| self.branch_policies.get(rule)),
+		};
+
+		match policy_name
+		{
+			Some(policy_name) => self.policies.get(policy_name)
+				.expect("this call is infallible because policy references were validated"),
+			None => self.branch_protection_for(repository_name),
+		}
+	}
+
+	/// How long to wait after receiving a webhook event before protecting a branch, including a
+	/// random jitter that differs with every call.
+	pub fn protection_delay(&self) -> std::time::Duration
+	{
+		use std::hash::{BuildHasher as _, Hasher as _};
+
+		let delay = std::time::Duration::from_secs(self.protection_delay_seconds);
+
+		if self.protection_jitter_milliseconds == 0
+		{
+			return delay;
+		}
+
+		// Randomly seeded hashers are random enough for spreading out requests
+		let random = std::collections::hash_map::RandomState::new().build_hasher().finish();
+		let jitter = random % (self.protection_jitter_milliseconds + 1);
+
+		delay + std::time::Duration::from_millis(jitter)
+	}
+
+	/// The headers to add to all replies, consisting of the security headers unless disabled and
+	/// the configured additional headers.
+	pub fn response_headers(&self) -> warp::http::HeaderMap
+	{
+		self.parse_response_headers()
+			.expect("this call is infallible because the response headers were validated")
+	}
+
+	/// Parse the headers to add to all replies (see [Config::response_headers]).
+	#[doc(hidden)]
+	fn parse_response_headers(&self) -> Result<warp::http::HeaderMap, crate::Error>
+	{
+		use warp::http::header::{HeaderName, HeaderValue};
+
+		let mut headers = warp::http::HeaderMap::new();
+
+		if self.security_headers
+		{
+			for (name, value) in SECURITY_HEADERS
+			{
+				headers.insert(name.clone(), HeaderValue::from_static(value));
+			}
+		}
+
+		for (name, value) in &self.response_headers
+		{
+			let name = HeaderName::from_bytes(name.as_bytes()).map_err(|_|
+				crate::Error::InvalidConfig(format!("response_headers contains the invalid header \
+					name “{name}”")))?;
+			let value = HeaderValue::from_str(value).map_err(|_|
+				crate::Error::InvalidConfig(format!("response_headers contains an invalid value \
+					for header “{name}”")))?;
+
+			headers.insert(name, value);
+		}
+
+		Ok(headers)
+	}
+
+	/// The largest webhook payload to accept in bytes, which is the limit configured for the event
+	/// type, if any, or else the limit configured for all event types, if any, or else the given
+	/// default limit of the event type.
+	///
+	/// # Arguments
+	/// - `event_type`: The type of the webhook event (example: `push`), if it’s handled.
+	/// - `default_limit`: The limit of the event type if none is configured.
+	pub fn payload_size_limit(&self, event_type: Option<&str>, default_limit: u64) -> u64
+	{
+		event_type
+			.and_then(|event_type| self.max_payload_size_bytes_by_event.get(event_type).copied())
+			.or(self.max_payload_size_bytes)
+			.unwrap_or(default_limit)
+	}
+
+	/// The maximum age of webhook events to act on, if limited.
+	pub fn max_event_age(&self) -> Option<std::time::Duration>
+	{
+		self.max_event_age_seconds.map(std::time::Duration::from_secs)
+	}
+
+	/// The HTTP status code to respond with to webhook events this service doesn’t act on.
+	pub fn ignored_event_status_code(&self) -> warp::http::StatusCode
+	{
+		warp::http::StatusCode::from_u16(self.ignored_event_status)
+			.expect("this call is infallible because the status code was validated")
+	}
+}
+
+/// Replace references of the form `${ENV:NAME}` in all string values of the configuration with the
+/// value of the environment variable `NAME`, so that secrets can be injected uniformly. Mapping
+/// keys are left as is.
+///
+/// # Arguments
+/// - `value`: The configuration as parsed from YAML.
+fn expand_environment_variables(value: &mut serde_yaml::Value) -> Result<(), crate::Error>
+{
+	match value
+	{
+		serde_yaml::Value::String(string) => *string = expand_environment_variables_in(string)?,
+		serde_yaml::Value::Sequence(values) =>
+		{
+			for value in values
+			{
+				expand_environment_variables(value)?;
+			}
+		},
+		serde_yaml::Value::Mapping(mapping) =>
+		{
+			for (_, value) in mapping.iter_mut()
+			{
+				expand_environment_variables(value)?;
+			}
+		},
+		_ => (),
+	}
+
+	Ok(())
+}
+
+/// Replace references of the form `${ENV:NAME}` in a string with the values of the environment
+/// variables, failing if any of them is unset.
+///
+/// # Arguments
+/// - `string`: The string possibly containing references.
+fn expand_environment_variables_in(string: &str) -> Result<String, crate::Error>
+{
+	let mut expanded = String::with_capacity(string.len());
+	let mut remainder = string;
+
+	while let Some(start) = remainder.find(ENVIRONMENT_VARIABLE_PREFIX)
+	{
+		expanded.push_str(&remainder[..start]);
+		remainder = &remainder[start + ENVIRONMENT_VARIABLE_PREFIX.len()..];
+
+		let end = remainder.find('}').ok_or_else(|| crate::Error::InvalidConfig(
+			format!("unterminated environment variable reference in “{string}”")))?;
+		let name = &remainder[..end];
+
+		let value = std::env::var(name).map_err(|error|
+			crate::Error::ExpandEnvironmentVariable{name: name.to_owned(), source: error})?;
+
+		expanded.push_str(&value);
+		remainder = &remainder[end + 1..];
+	}
+
+	expanded.push_str(remainder);
+
+	Ok(expanded)
+}
+
+/// The beginning of references to environment variables in configuration values.
+const ENVIRONMENT_VARIABLE_PREFIX: &str = "${ENV:";
+
+/// The smallest accepted value of `max_payload_size_bytes` and `max_payload_size_bytes_by_event`,
+/// below which no valid webhook payload fits.
+const MINIMUM_PAYLOAD_SIZE_LIMIT: u64 = 1024;
+
+/// The largest accepted value of `max_payload_size_bytes` and `max_payload_size_bytes_by_event`,
+/// as GitHub doesn’t send payloads larger than 25 MB.
+const MAXIMUM_PAYLOAD_SIZE_LIMIT: u64 = 25 * 1024 * 1024;
+
+/// The smallest accepted value of `notification.max_issue_body_length`, which leaves room for the
+/// beginning of the body along with team mentions and the attribution of this service.
+const MINIMUM_ISSUE_BODY_LENGTH_LIMIT: usize = 1024;
+
+/// The largest accepted value of `notification.max_issue_body_length`, as GitHub rejects issue
+/// bodies longer than 65536 characters.
+const MAXIMUM_ISSUE_BODY_LENGTH_LIMIT: usize = 65536;
+
+/// The ID of the built-in repository role of administrators, as used by rulesets.
+const REPOSITORY_ADMIN_ROLE_ID: u64 = 5;
+
+/// The security headers added to all replies unless `security_headers` is disabled.
+const SECURITY_HEADERS: &[(warp::http::header::HeaderName, &str)] = &[
+	(warp::http::header::X_CONTENT_TYPE_OPTIONS, "nosniff"),
+	(warp::http::header::X_FRAME_OPTIONS, "DENY"),
+	(warp::http::header::REFERRER_POLICY, "no-referrer"),
+	(warp::http::header::CONTENT_SECURITY_POLICY, "default-src 'none'"),
+];
+
+/// Configuration of how webhook events that are handled in the background are acknowledged.
+/// Acknowledgments also include the repository and branch acted on.
+#[derive(Debug, schemars::JsonSchema, serde::Deserialize)]
+pub struct AcknowledgmentConfig
+{
+	/// The info message to acknowledge webhook events with (optional, default: a message
+	/// describing how the event is handled).
+	pub message: Option<String>,
+	/// The stable, machine-readable code to acknowledge webhook events with (optional, default:
+	/// `accepted`).
+	#[serde(default = "default_acknowledgment_code")]
+	pub code: String,
+}
+
+impl Default for AcknowledgmentConfig
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			message: None,
+			code: default_acknowledgment_code(),
+		}
+	}
+}
+
+#[doc(hidden)]
+fn default_acknowledgment_code() -> String
+{
+	"accepted".into()
+}
+
+/// Configuration of the predicates deciding whether to protect a newly created branch other than
+/// the default branch. These only apply to ref creation events, while push events and backfilling
+/// only consider `protected_branches` and `protected_branch_patterns`.
+#[derive(Debug, schemars::JsonSchema, serde::Deserialize)]
+pub struct BranchPredicatesConfig
+{
+	/// Protect branches matching `protected_branches` or `protected_branch_patterns`, which
+	/// doesn’t cost any GitHub API requests (optional, default: `true`).
+	#[serde(default = "default_protect_if_matches_pattern")]
+	pub protect_if_matches_pattern: bool,
+	/// Protect branches that are the head of an open pull request, which requires the
+	/// `pull_requests:read` permission and costs one GitHub API request per created branch unless
+	/// another predicate holds already. As branches are usually
+	/// created before opening pull requests, this mostly applies to branches restored after being
+	/// deleted (optional, default: `false`).
+	#[serde(default)]
+	pub protect_if_has_open_pr: bool,
+}
+
+impl Default for BranchPredicatesConfig
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			protect_if_matches_pattern: default_protect_if_matches_pattern(),
+			protect_if_has_open_pr: false,
+		}
+	}
+}
+
+#[doc(hidden)]
+fn default_protect_if_matches_pattern() -> bool
+{
+	true
+}
+
+/// Configuration of how to react to branch protection rules of protected branches being deleted
+/// manually.
+#[derive(Debug, Default, schemars::JsonSchema, serde::Deserialize)]
+pub struct DeletedProtectionConfig
+{
+	/// Protect the branch again with the configured settings (optional, default: `false`).
+	#[serde(default)]
+	pub reapply: bool,
+	/// Open an issue mentioning the user who deleted the branch protection rule (optional,
+	/// default: `false`).
+	#[serde(default)]
+	pub open_issue: bool,
+}
+
+/// Configuration of which validation errors (422 Unprocessable Entity) to retry when protecting
+/// branches, as they may be transient for freshly created branches.
+#[derive(Debug, schemars::JsonSchema, serde::Deserialize)]
+pub struct ProtectBranchRetryConfig
+{
+	/// Retry validation errors whose message contains any of these substrings, such as
+	/// `Branch not found` (optional, default: none).
+	#[serde(default)]
+	pub messages: Vec<String>,
+	/// How often to attempt protecting a branch in total (optional, default: 3).
+	#[serde(default = "default_protect_branch_max_attempts")]
+	pub max_attempts: u32,
+	/// How many seconds to wait before retrying (optional, default: 2).
+	#[serde(default = "default_protect_branch_retry_delay_seconds")]
+	pub delay_seconds: u64,
+}
+
+impl Default for ProtectBranchRetryConfig
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			messages: vec![],
+			max_attempts: default_protect_branch_max_attempts(),
+			delay_seconds: default_protect_branch_retry_delay_seconds(),
+		}
+	}
+}
+
+#[doc(hidden)]
+fn default_protect_branch_max_attempts() -> u32
+{
+	3
+}
+
+#[doc(hidden)]
+fn default_protect_branch_retry_delay_seconds() -> u64
+{
+	2
+}
+
+impl ProtectBranchRetryConfig
+{
+	/// Check whether an error that occurred while protecting a branch should be retried.
+	///
+	/// # Arguments
+	/// - `error`: The error that occurred.
+	/// - `attempt`: The number of the attempt that failed, starting at 1.
+	pub fn should_retry(&self, error: &crate::Error, attempt: u32) -> bool
+	{
+		match error
+		{
+			crate::Error::ReceivedGitHubApiClientError{status_code, response_body, ..}
+				if *status_code == reqwest::StatusCode::UNPROCESSABLE_ENTITY =>
+				attempt < self.max_attempts
+					&& self.messages.iter().any(|message| response_body.contains(message)),
+			_ => false,
+		}
+	}
+
+	/// How long to wait before retrying.
+	pub fn delay(&self) -> std::time::Duration
+	{
+		std::time::Duration::from_secs(self.delay_seconds)
+	}
+}
+
+/// Configuration of how users and teams are notified about actions taken on their branches.
+#[derive(Debug, schemars::JsonSchema, serde::Deserialize)]
+pub struct NotificationConfig
+{
+	/// Slugs of teams in the organization to mention in all issues created by this service, so
+	/// that they’re notified, too. Mentions only notify teams with access to the repository
+	/// (optional, default: none).
+	#[serde(default)]
+	pub notify_teams: Vec<String>,
+	/// Where to post notifications, either `issue`, `discussion`, or `commit_comment` (optional,
+	/// default: `issue`).
+	#[serde(default)]
+	pub target: NotificationTarget,
+	/// The name of the discussion category to post notifications in, which is required if
+	/// notifications are posted as discussions.
+	pub discussion_category: Option<String>,
+	/// Template of the body of the issue informing about newly protected branches in Markdown
+	/// format, such as a task list. The placeholders `{creator}`, `{organization}`,
+	/// `{repository}`, `{branch}`, `{rules_summary}` (a summary of the applied branch protection
+	/// rules), and `{timestamp}` (when the branch was protected, in `display_timezone`) are
+	/// substituted, while everything else, including leading whitespace and line breaks, is kept as
+	/// is (optional, default: a message explaining the branch protection rules).
+	pub issue_body_template: Option<String>,
+	/// The maximum number of characters of the bodies of notifications, beyond which the body is
+	/// truncated and marked as such, keeping team mentions and the attribution of this service
+	/// intact. Must be between 1024 and GitHub’s limit of 65536 (optional, default: 65536).
+	#[serde(default = "default_max_issue_body_length")]
+	pub max_issue_body_length: usize,
+	/// The maximum number of notifications to post per repository within an hour, beyond which
+	/// further notifications are skipped with a warning while branches are still protected. This
+	/// is a safety valve against flooding repositories with issues if something goes wrong, such as
+	/// a loop of branches being deleted and created again, rather than a way of reducing
+	/// notifications (optional, default: no limit).
+	pub max_per_hour: Option<u32>,
+	/// Pin created issues to the top of the repository’s issue list for visibility. As
+	/// repositories can only have three pinned issues, failures to pin issues are logged but don’t
+	/// affect the outcome (optional, default: `false`).
+	#[serde(default)]
+	pub pin_issue: bool,
+	/// Instead of creating an issue per protected branch, maintain a single “Branch protection
+	/// status” issue per repository that lists all protected branches, adding a line whenever
+	/// another branch is protected. Once the list would exceed `max_issue_body_length`, it’s
+	/// continued in a new status issue, and the full one is closed. This requires notifications to
+	/// be posted as issues (optional, default: `false`).
+	#[serde(default)]
+	pub status_issue: bool,
+	/// Whether to protect branches before notifying about this (`protect_then_notify`), to notify
+	/// first so that the notification is posted even if protecting the branch fails
+	/// (`notify_then_protect`), or to do both at the same time (`concurrent`). Unless protecting
+	/// the branch first, the branch is protected even if notifying fails (optional, default:
+	/// `protect_then_notify`).
+	#[serde(default)]
+	pub order: NotificationOrder,
+}
+
+/// In which order branches are protected and notifications about this are posted.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, schemars::JsonSchema,
+	serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationOrder
+{
+	/// Protect the branch first and only notify about it if this succeeded.
+	#[default]
+	ProtectThenNotify,
+	/// Notify first, then protect the branch regardless of whether notifying succeeded.
+	NotifyThenProtect,
+	/// Protect the branch and notify at the same time.
+	Concurrent,
+}
+
+/// Where notifications about actions taken on branches are posted.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, schemars::JsonSchema,
+	serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationTarget
+{
+	/// Create an issue in the repository.
+	#[default]
+	Issue,
+	/// Create a discussion in the repository, which requires the `discussions` permission.
+	Discussion,
+	/// Comment on the commit the branch points to, which requires the `contents:write`
+	/// permission. Branches without commits are skipped.
+	CommitComment,
+}
+
+impl NotificationConfig
+{
+	/// Check the configuration for invalid values that can’t be detected while parsing.
+	fn validate(&self) -> Result<(), crate::Error>
+	{
+		// Team slugs consist of lowercase letters, digits, hyphens, and underscores, which GitHub
+		// derives from the team name
+		for team_slug in &self.notify_teams
+		{
+			let is_valid = !team_slug.is_empty() && team_slug.chars()
+				.all(|character| matches!(character, 'a'..='z' | '0'..='9' | '-' | '_'));
+
+			if !is_valid
+			{
+				return Err(crate::Error::InvalidConfig(format!("notification.notify_teams must \
+					only contain team slugs without the organization, such as “security-team”, but \
+					contains “{team_slug}”")));
+			}
+		}
+
+		if self.target == NotificationTarget::Discussion && self.discussion_category.is_none()
+		{
+			return Err(crate::Error::InvalidConfig("notification.discussion_category is required \
+				if notification.target is “discussion”".to_string()));
+		}
+
+		if self.target != NotificationTarget::Issue && self.status_issue
+		{
+			return Err(crate::Error::InvalidConfig("notification.status_issue can only be \
+				enabled if notification.target is “issue”".to_string()));
+		}
+
+		if self.max_per_hour == Some(0)
+		{
+			return Err(crate::Error::InvalidConfig(
+				"notification.max_per_hour must be at least 1".to_string()));
+		}
+
+		if !(MINIMUM_ISSUE_BODY_LENGTH_LIMIT..=MAXIMUM_ISSUE_BODY_LENGTH_LIMIT)
+			.contains(&self.max_issue_body_length)
+		{
+			return Err(crate::Error::InvalidConfig(format!("notification.max_issue_body_length \
+				must be between {MINIMUM_ISSUE_BODY_LENGTH_LIMIT} and \
+				{MAXIMUM_ISSUE_BODY_LENGTH_LIMIT}, but is {}", self.max_issue_body_length)));
+		}
+
+		Ok(())
+	}
+}
+
+impl Default for NotificationConfig
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			notify_teams: vec![],
+			target: Default::default(),
+			discussion_category: None,
+			issue_body_template: None,
+			max_issue_body_length: default_max_issue_body_length(),
+			max_per_hour: None,
+			pin_issue: false,
+			status_issue: false,
+			order: Default::default(),
+		}
+	}
+}
+
+#[doc(hidden)]
+fn default_max_issue_body_length() -> usize
+{
+	MAXIMUM_ISSUE_BODY_LENGTH_LIMIT
+}
+
+/// How to handle branches that are protected already but whose settings differ from the configured
+/// ones.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, schemars::JsonSchema,
+	serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExistingProtectionPolicy
+{
+	/// Leave the existing settings unchanged.
+	#[default]
+	Skip,
+	/// Replace the existing settings with the configured ones.
+	Overwrite,
+	/// Leave the existing settings unchanged but open an issue listing the differences.
+	ReportDrift,
+}
+
+/// Branch protection settings applied to protected branches.
+#[derive(Clone, Debug, schemars::JsonSchema, serde::Deserialize)]
+pub struct BranchProtectionSettings
+{
+	/// The number of approving reviews required before merging pull requests (between 0 and 6,
+	/// optional, default: 1).
+	#[serde(default = "default_required_approving_review_count")]
+	pub required_approving_review_count: u8,
+	/// Dismiss approving reviews when new commits are pushed (optional, default: `false`).
+	#[serde(default)]
+	pub dismiss_stale_reviews: bool,
+	/// Require an approving review by a code owner (optional, default: `false`).
+	#[serde(default)]
+	pub require_code_owner_reviews: bool,
+	/// Enforce these settings for administrators, too (optional, default: `true`).
+	#[serde(default = "default_enforce_admins")]
+	pub enforce_admins: bool,
+	/// The names of the status checks that need to pass before merging (optional, default: none).
+	#[serde(default)]
+	pub required_status_checks: Vec<String>,
+	/// Require branches to be up to date with the base branch before merging, which only applies
+	/// if any status checks are required. Note that this can cause contention with many open pull
+	/// requests or merge queues, as each merge requires updating all other pull requests
+	/// (optional, default: `false`, as used by GitHub). This is a separate setting rather than
+	/// nested in `required_status_checks`, which would break existing configurations listing the
+	/// status checks directly.
+	#[serde(default)]
+	pub strict_status_checks: bool,
+}
+
+impl Default for BranchProtectionSettings
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			required_approving_review_count: default_required_approving_review_count(),
+			dismiss_stale_reviews: false,
+			require_code_owner_reviews: false,
+			enforce_admins: default_enforce_admins(),
+			required_status_checks: vec![],
+			strict_status_checks: false,
+		}
+	}
+}
+
+impl BranchProtectionSettings
+{
+	/// Check the settings for values GitHub wouldn’t accept.
+	///
+	/// # Arguments
+	/// - `path`: Where these settings are located in the configuration, for error messages.
+	fn validate(&self, path: &str) -> Result<(), crate::Error>
+	{
+		if self.required_approving_review_count > 6
+		{
+			return Err(crate::Error::InvalidConfig(format!("{path}.required_approving_review_count \
+				must be between 0 and 6, but is {}", self.required_approving_review_count)));
+		}
+
+		Ok(())
+	}
+
+	/// Compare these settings with the existing protection settings of a branch, returning
+	/// human-readable descriptions of all differences (empty if the settings match).
+	pub fn differences(&self, existing: &crate::BranchProtectionResponse) -> Vec<String>
+	{
+		let mut differences = vec![];
+
+		match &existing.required_pull_request_reviews
+		{
+			None => differences.push("pull request reviews aren’t required".to_owned()),
+			Some(reviews) =>
+			{
+				let required_approving_review_count =
+					reviews.required_approving_review_count.unwrap_or_default();
+				let configured_approving_review_count =
+					u32::from(self.required_approving_review_count);
+
+				if required_approving_review_count != configured_approving_review_count
+				{
+					differences.push(format!("{required_approving_review_count} approving reviews \
+						are required instead of {configured_approving_review_count}"));
+				}
+
+				if reviews.dismiss_stale_reviews != self.dismiss_stale_reviews
+				{
+					differences.push(format!("dismissing stale reviews is {} instead of {}",
+						enabled_or_disabled(reviews.dismiss_stale_reviews),
+						enabled_or_disabled(self.dismiss_stale_reviews)));
+				}
+
+				if reviews.require_code_owner_reviews != self.require_code_owner_reviews
+				{
+					differences.push(format!("requiring code owner reviews is {} instead of {}",
+						enabled_or_disabled(reviews.require_code_owner_reviews),
+						enabled_or_disabled(self.require_code_owner_reviews)));
+				}
+			},
+		}
+
+		let enforce_admins =
+			existing.enforce_admins.as_ref().is_some_and(|setting| setting.enabled);
+
+		if enforce_admins != self.enforce_admins
+		{
+			differences.push(format!("enforcing the rules for administrators is {} instead of {}",
+				enabled_or_disabled(enforce_admins), enabled_or_disabled(self.enforce_admins)));
+		}
+
+		// The order of status checks doesn’t matter
+		let mut required_status_checks = existing.required_status_checks.as_ref()
+			.map(|status_checks| status_checks.contexts.clone())
+			.unwrap_or_default();
+		required_status_checks.sort();
+
+		let mut configured_status_checks = self.required_status_checks.clone();
+		configured_status_checks.sort();
+
+		if required_status_checks != configured_status_checks
+		{
+			differences.push(format!("the required status checks are [{}] instead of [{}]",
+				required_status_checks.join(", "), configured_status_checks.join(", ")));
+		}
+
+		// Strict mode only matters if any status checks are required
+		let strict_status_checks = existing.required_status_checks.as_ref()
+			.is_some_and(|status_checks| status_checks.strict);
+
+		if !self.required_status_checks.is_empty()
+			&& strict_status_checks != self.strict_status_checks
+		{
+			differences.push(format!("requiring branches to be up to date is {} instead of {}",
+				enabled_or_disabled(strict_status_checks),
+				enabled_or_disabled(self.strict_status_checks)));
+		}
+
+		differences
+	}
+
+	/// Build a ruleset enforcing these settings for a branch, which prevents deleting and force
+	/// pushing to the branch like classic branch protection. Unless enforced for administrators,
+	/// repository administrators may bypass the ruleset.
+	///
+	/// # Arguments
+	/// - `branch_name`: The name of the branch.
+	pub fn ruleset(&self, branch_name: &str) -> crate::Ruleset
+	{
+		let mut rules = vec![crate::RulesetRule::Deletion, crate::RulesetRule::NonFastForward,
+			crate::RulesetRule::PullRequest(crate::RulesetPullRequestParameters
+			{
+				required_approving_review_count: self.required_approving_review_count,
+				dismiss_stale_reviews_on_push: self.dismiss_stale_reviews,
+				require_code_owner_review: self.require_code_owner_reviews,
+				require_last_push_approval: false,
+				required_review_thread_resolution: false,
+			})];
+
+		if !self.required_status_checks.is_empty()
+		{
+			rules.push(crate::RulesetRule::RequiredStatusChecks(
+				crate::RulesetRequiredStatusChecksParameters
+				{
+					strict_required_status_checks_policy: self.strict_status_checks,
+					required_status_checks: self.required_status_checks.iter()
+						.map(|context| crate::RulesetStatusCheck{context: context.clone()})
+						.collect(),
+				}));
+		}
+
+		let bypass_actors = match self.enforce_admins
+		{
+			true => vec![],
+			false => vec![crate::RulesetBypassActor
+			{
+				actor_id: REPOSITORY_ADMIN_ROLE_ID,
+				actor_type: "RepositoryRole",
+				bypass_mode: "always",
+			}],
+		};
+
+		crate::Ruleset
+		{
+			name: format!("Protect {branch_name}"),
+			target: "branch",
+			enforcement: "active",
+			conditions: crate::RulesetConditions
+			{
+				ref_name: crate::RulesetRefNameCondition
+				{
+					include: vec![format!("refs/heads/{branch_name}")],
+					exclude: vec![],
+				},
+			},
+			rules,
+			bypass_actors,
+		}
+	}
+
+	/// Build the GitHub API request applying these settings to a branch.
+	pub fn protect_branch_request(&self) -> crate::ProtectBranchRequest
+	{
+		let required_status_checks = match self.required_status_checks.is_empty()
+		{
+			true => None,
+			false => Some(crate::RequiredStatusChecks
+			{
+				strict: self.strict_status_checks,
+				contexts: self.required_status_checks.clone(),
+			}),
+		};
+
+		crate::ProtectBranchRequest
+		{
+			required_status_checks,
+			enforce_admins: self.enforce_admins.then_some(true),
+			required_pull_request_reviews: Some(crate::RequiredPullRequestReviews
+			{
+				required_approving_review_count: self.required_approving_review_count,
+				dismiss_stale_reviews: self.dismiss_stale_reviews,
+				require_code_owner_reviews: self.require_code_owner_reviews,
+			}),
+			restrictions: None,
+		}
+	}
+}
+
+// Summarize the settings in a single line of key–value pairs for logging
+impl std::fmt::Display for BranchProtectionSettings
+{
+	fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result
+	{
+		write!(formatter, "required_approving_review_count={} dismiss_stale_reviews={} \
+			require_code_owner_reviews={} enforce_admins={} required_status_checks=[{}] \
+			strict_status_checks={}",
+			self.required_approving_review_count, self.dismiss_stale_reviews,
+			self.require_code_owner_reviews, self.enforce_admins,
+			self.required_status_checks.join(","), self.strict_status_checks)
+	}
+}
+
+#[doc(hidden)]
+fn enabled_or_disabled(enabled: bool) -> &'static str
+{
+	match enabled
+	{
+		true => "enabled",
+		false => "disabled",
+	}
+}
+
+#[doc(hidden)]
+fn default_required_approving_review_count() -> u8
+{
+	1
+}
+
+#[doc(hidden)]
+fn default_enforce_admins() -> bool
+{
+	true
+}
+
+#[cfg(test)]
+mod tests
+{
+	#[test]
+	fn protected_branch_rule_matches_exact_names_case_sensitively()
+	{
+		let config = crate::test_support::config(serde_json::json!(
+		{
+			"protected_branches": ["main", "release", "hotfix"],
+		}));
+
+		assert_eq!(config.protected_branch_rule("release"), Some("release"));
+		assert_eq!(config.protected_branch_rule("hotfix"), Some("hotfix"));
+		assert_eq!(config.protected_branch_rule("Release"), None);
+		assert_eq!(config.protected_branch_rule("RELEASE"), None);
+		assert_eq!(config.protected_branch_rule("release/1.0"), None);
+		assert_eq!(config.protected_branch_rule("feature"), None);
+	}
+
+	#[test]
+	fn payload_size_limit_prefers_limit_of_event_type()
+	{
+		let config = crate::test_support::config(serde_json::json!({}));
+
+		assert_eq!(config.payload_size_limit(Some("push"), 25 * 1024 * 1024), 25 * 1024 * 1024);
+
+		let config = crate::test_support::config(serde_json::json!(
+		{
+			"max_payload_size_bytes": 65536,
+			"max_payload_size_bytes_by_event": {"push": 1048576},
+		}));
+
+		assert_eq!(config.payload_size_limit(Some("push"), 25 * 1024 * 1024), 1048576);
+		assert_eq!(config.payload_size_limit(Some("create"), 256 * 1024), 65536);
+		assert_eq!(config.payload_size_limit(None, 25 * 1024 * 1024), 65536);
+
+		let config = crate::test_support::config(serde_json::json!(
+		{
+			"max_payload_size_bytes_by_event": {"pull_request": 1048576},
+		}));
+
+		assert!(matches!(config.validate(), Err(crate::Error::InvalidConfig(_))));
+	}
+
+	#[test]
+	fn protected_branch_rule_prefers_exact_names_over_patterns()
+	{
+		let config = crate::test_support::config(serde_json::json!(
+		{
+			"protected_branches": ["release"],
+			"protected_branch_patterns": ["rel*"],
+		}));
+
+		assert_eq!(config.protected_branch_rule("release"), Some("release"));
+		assert_eq!(config.protected_branch_rule("relay"), Some("rel*"));
+		assert_eq!(config.protected_branch_rule("Release"), None);
+	}
+
+	#[test]
+	fn branch_protection_for_branch_applies_policy_of_matching_pattern()
+	{
+		let config = crate::test_support::config(serde_json::json!(
+		{
+			"protected_branch_patterns": ["release/*", "hotfix/*", "feature/*"],
+			"policies":
+			{
+				"strict": {"required_approving_review_count": 2, "enforce_admins": true},
+				"relaxed": {"required_approving_review_count": 0, "enforce_admins": false},
+			},
+			"branch_policies": {"release/*": "relaxed", "hotfix/*": "strict"},
+			"branch_protection": {"enforce_admins": true, "dismiss_stale_reviews": true},
+		}));
+
+		let settings = config.branch_protection_for_branch("example-repository", "release/1.0",
+			false);
+		assert!(!settings.enforce_admins);
+		assert_eq!(settings.required_approving_review_count, 0);
+		assert_eq!(settings.protect_branch_request().enforce_admins, None);
+
+		let settings = config.branch_protection_for_branch("example-repository", "hotfix/1",
+			false);
+		assert!(settings.enforce_admins);
+		assert_eq!(settings.required_approving_review_count, 2);
+		assert!(!settings.dismiss_stale_reviews);
+		assert_eq!(settings.protect_branch_request().enforce_admins, Some(true));
+
+		// Branches matching patterns without a policy of their own and default branches get the
+		// settings applied to the repository
+		for (branch_name, is_default_branch) in [("feature/1", false), ("release/1.0", true)]
+		{
+			let settings = config.branch_protection_for_branch("example-repository", branch_name,
+				is_default_branch);
+			assert!(settings.enforce_admins, "{branch_name}");
+			assert!(settings.dismiss_stale_reviews, "{branch_name}");
+		}
+	}
+
+	#[test]
+	fn payload_capture_requires_webhook_secret()
+	{
+		let config = crate::test_support::config(serde_json::json!(
+		{
+			"payload_capture_dir": "/var/lib/branch-autoprotector/payloads",
+		}));
+		assert!(matches!(config.validate(), Err(crate::Error::InvalidConfig(_))));
+
+		let config = crate::test_support::config(serde_json::json!(
+		{
+			"github_api": {"webhook_secret": "It's a Secret to Everybody"},
+			"payload_capture_dir": "/var/lib/branch-autoprotector/payloads",
+		}));
+		assert!(config.validate().is_ok());
+	}
+
+	#[test]
+	fn requiring_commits_before_protecting_requires_protecting_on_first_push()
+	{
+		let config = crate::test_support::config(serde_json::json!(
+		{
+			"require_commits_before_protecting": true,
+		}));
+		assert!(matches!(config.validate(), Err(crate::Error::InvalidConfig(_))));
+
+		let config = crate::test_support::config(serde_json::json!(
+		{
+			"protect_on_first_push": true,
+			"require_commits_before_protecting": true,
+		}));
+		assert!(config.validate().is_ok());
+	}
+
+	/// Decode the protection settings of a branch as returned by the GitHub API.
+	fn branch_protection_response(response: serde_json::Value) -> crate::BranchProtectionResponse
+	{
+		serde_json::from_value(response).unwrap()
+	}
+
+	#[test]
+	fn differences_are_empty_for_matching_settings()
+	{
+		let settings = super::BranchProtectionSettings
+		{
+			required_status_checks: vec!["ci/test".into(), "ci/build".into()],
+			..Default::default()
+		};
+
+		let existing = branch_protection_response(serde_json::json!(
+		{
+			"required_pull_request_reviews":
+			{
+				"required_approving_review_count": 1,
+				"dismiss_stale_reviews": false,
+				"require_code_owner_reviews": false,
+			},
+			"enforce_admins": {"enabled": true},
+			"required_status_checks": {"strict": false, "contexts": ["ci/build", "ci/test"]},
+		}));
+
+		assert_eq!(settings.differences(&existing), Vec::<String>::new());
+	}
+
+	#[test]
+	fn differences_describe_each_differing_setting()
+	{
+		let settings = super::BranchProtectionSettings
+		{
+			required_approving_review_count: 2,
+			dismiss_stale_reviews: true,
+			required_status_checks: vec!["ci/build".into()],
+			strict_status_checks: true,
+			..Default::default()
+		};
+
+		let existing = branch_protection_response(serde_json::json!(
+		{
+			"required_pull_request_reviews":
+			{
+				"required_approving_review_count": 1,
+				"require_code_owner_reviews": true,
+			},
+			"enforce_admins": {"enabled": false},
+		}));
+
+		assert_eq!(settings.differences(&existing), vec![
+			"1 approving reviews are required instead of 2",
+			"dismissing stale reviews is disabled instead of enabled",
+			"requiring code owner reviews is enabled instead of disabled",
+			"enforcing the rules for administrators is disabled instead of enabled",
+			"the required status checks are [] instead of [ci/build]",
+			"requiring branches to be up to date is disabled instead of enabled",
+		]);
+	}
+
+	#[test]
+	fn differences_report_missing_pull_request_reviews()
+	{
+		let existing = branch_protection_response(serde_json::json!(
+		{
+			"enforce_admins": {"enabled": true},
+			"required_status_checks": {"strict": true, "contexts": []},
+		}));
+
+		// Strict status checks don’t matter if no status checks are required
+		assert_eq!(super::BranchProtectionSettings::default().differences(&existing),
+			vec!["pull request reviews aren’t required"]);
 	}
 }
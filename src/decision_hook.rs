@@ -0,0 +1,132 @@
+/// Configuration of an external command deciding whether to act on webhook events, as an escape
+/// hatch for policies that are awkward to express in the configuration.
+#[derive(Clone, Debug, PartialEq, schemars::JsonSchema, serde::Deserialize)]
+pub struct DecisionHookConfig
+{
+	/// Path to the executable to run for each webhook event this service would act on.
+	pub command: std::path::PathBuf,
+	/// Arguments to pass to the executable (optional, default: none).
+	#[serde(default)]
+	pub args: Vec<String>,
+	/// Kill the executable and fail handling the event if it doesn’t exit within this number of
+	/// seconds (optional, default: 10).
+	#[serde(default = "default_decision_hook_timeout_seconds")]
+	pub timeout_seconds: u64,
+}
+
+#[doc(hidden)]
+fn default_decision_hook_timeout_seconds() -> u64
+{
+	10
+}
+
+/// The decision of a decision hook about a webhook event.
+#[derive(Debug, serde::Deserialize)]
+pub struct Decision
+{
+	/// Whether to act on the event.
+	pub protect: bool,
+	/// The name of the policy to protect the branch with instead of the one configured for the
+	/// repository, if any.
+	#[serde(default)]
+	pub policy: Option<String>,
+	/// Why the hook decided this way, which is logged.
+	#[serde(default)]
+	pub reason: Option<String>,
+}
+
+/// The event passed to a decision hook on its standard input (serialized to JSON).
+#[derive(serde::Serialize)]
+struct DecisionHookInput<'a>
+{
+	/// The type of the webhook event (example: `create`).
+	event: &'a str,
+	/// The organization owning the repository.
+	organization: &'a str,
+	/// The name of the repository.
+	repository: &'a str,
+	/// The name of the branch.
+	branch: &'a str,
+	/// Whether the branch is the default branch of the repository.
+	is_default_branch: bool,
+	/// The handle of the user who caused the event.
+	sender: &'a str,
+}
+
+/// Run a decision hook for a webhook event that this service would act on and return its
+/// decision.
+///
+/// The hook receives the event as a JSON object with the fields `event`, `organization`,
+/// `repository`, `branch`, `is_default_branch`, and `sender` on its standard input. If it exits
+/// with code 0, the event is acted on unless it prints a JSON object such as
+/// `{"protect": true, "policy": "strict", "reason": "…"}` on its standard output, which decides
+/// whether to act on the event and optionally selects one of the configured policies. If it exits
+/// with code 1, the event is ignored. Any other exit code, exceeding the timeout, or invalid
+/// output is an error.
+///
+/// # Arguments
+/// - `config`: Which executable to run and how long to wait for it.
+/// - `event_type`: The type of the webhook event (example: `create`).
+/// - `target`: The branch the webhook event asks us to protect.
+pub async fn run_decision_hook(config: &DecisionHookConfig, event_type: &str,
+	target: &crate::ProtectionTarget)
+	-> Result<Decision, crate::Error>
+{
+	use tokio::io::AsyncWriteExt as _;
+
+	let input = serde_json::to_vec(&DecisionHookInput
+	{
+		event: event_type,
+		organization: &target.organization_name,
+		repository: &target.repository_name,
+		branch: &target.branch_name,
+		is_default_branch: target.is_default_branch,
+		sender: &target.creator_name,
+	})
+	.expect("this call is infallible because the input consists of plain strings");
+
+	let mut child = tokio::process::Command::new(&config.command)
+		.args(&config.args)
+		.stdin(std::process::Stdio::piped())
+		.stdout(std::process::Stdio::piped())
+		.stderr(std::process::Stdio::piped())
+		// Don’t leave hooks running after giving up on them
+		.kill_on_drop(true)
+		.spawn()
+		.map_err(crate::Error::RunDecisionHook)?;
+
+	let mut stdin = child.stdin.take()
+		.expect("this call is infallible because the standard input was piped");
+
+	let timeout = std::time::Duration::from_secs(config.timeout_seconds);
+
+	let output = tokio::time::timeout(timeout, async move
+	{
+		// Hooks may exit without reading their input, which isn’t an error in itself
+		if let Err(error) = stdin.write_all(&input).await
+		{
+			log::debug!("could not write event to decision hook: {error}");
+		}
+
+		drop(stdin);
+
+		child.wait_with_output().await
+	})
+	.await
+	.map_err(|_| crate::Error::DecisionHookTimedOut{timeout})?
+	.map_err(crate::Error::RunDecisionHook)?;
+
+	match output.status.code()
+	{
+		Some(0) if output.stdout.iter().all(u8::is_ascii_whitespace) =>
+			Ok(Decision{protect: true, policy: None, reason: None}),
+		Some(0) => serde_json::from_slice(&output.stdout)
+			.map_err(crate::Error::DecodeDecisionHookOutput),
+		Some(1) => Ok(Decision{protect: false, policy: None, reason: None}),
+		_ => Err(crate::Error::DecisionHookFailed
+		{
+			status: output.status.to_string(),
+			stderr: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+		}),
+	}
+}
@@ -0,0 +1,95 @@
+/// [warp] route serving diagnostic information for humans debugging this service as JSON at
+/// `/diagnostics`, including the version, the organization, and the current GitHub API rate limit
+/// status. Unlike `/health`, this requires GitHub to be reachable to report the rate limit status,
+/// but failures to look it up are reported in the response instead of failing the request.
+///
+/// # Arguments
+/// - `state`: The shared state, including the GitHub API client and the configuration.
+pub fn diagnostics_route(state: crate::AppState)
+	-> impl warp::Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone
+{
+	use warp::Filter as _;
+
+	warp::path!("diagnostics")
+		.and(warp::get())
+		.and(warp::any().map(move || state.clone()))
+		.then(|state: crate::AppState| async move
+		{
+			let rate_limit = state.github_api_client.get_rate_limit().await;
+
+			let (rate_limit, rate_limit_error) = match rate_limit
+			{
+				Ok(rate_limit) => (Some(rate_limit), None),
+				Err(error) =>
+				{
+					log::warn!("could not look up GitHub API rate limit status: {error}");
+					(None, Some(error.to_string()))
+				},
+			};
+
+			warp::reply::json(&Diagnostics
+			{
+				version: env!("CARGO_PKG_VERSION"),
+				organization: state.github_api_client.organization(),
+				dry_run: state.config().dry_run,
+				maintenance_mode: state.config().maintenance_mode,
+				rate_limit,
+				rate_limit_error,
+			})
+		})
+}
+
+/// [warp] route describing this service as JSON on GET requests to the root path, so that
+/// operators opening the webhook URL in a browser see what it’s for instead of 405 Method Not
+/// Allowed. Webhook events are still only accepted as POST requests, and other methods are still
+/// rejected.
+pub fn index_route()
+	-> impl warp::Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone
+{
+	use warp::Filter as _;
+
+	warp::path::end()
+		.and(warp::get())
+		.map(|| warp::reply::json(&Index
+		{
+			name: env!("CARGO_PKG_NAME"),
+			version: env!("CARGO_PKG_VERSION"),
+			description: "This is the webhook endpoint of a GitHub App protecting branches. \
+				Webhook events are expected as POST requests.",
+			handled_events: crate::HANDLED_EVENT_TYPES,
+		}))
+}
+
+/// Description of this service for humans opening the webhook URL (serialized to JSON).
+#[derive(serde::Serialize)]
+struct Index
+{
+	/// The name of this service.
+	name: &'static str,
+	/// The version of this service.
+	version: &'static str,
+	/// What this endpoint is for and how to use it.
+	description: &'static str,
+	/// The types of webhook events this service acts on.
+	handled_events: &'static [&'static str],
+}
+
+/// Diagnostic information about this service (serialized to JSON).
+#[derive(serde::Serialize)]
+struct Diagnostics<'a>
+{
+	/// The version of this service.
+	version: &'static str,
+	/// The organization this service watches.
+	organization: &'a str,
+	/// Whether this service only logs the changes it would make.
+	dry_run: bool,
+	/// Whether this service only acknowledges webhook events without acting on them.
+	maintenance_mode: bool,
+	/// The current GitHub API rate limit status, if it could be looked up.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	rate_limit: Option<crate::RateLimit>,
+	/// Why the rate limit status couldn’t be looked up, if applicable.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	rate_limit_error: Option<String>,
+}
@@ -0,0 +1,161 @@
+/// A change that protecting a branch would make to a field of its current protection settings.
+#[derive(Debug, serde::Serialize)]
+pub struct ProtectionChange
+{
+	/// The path of the field in the branch protection request (example:
+	/// `required_pull_request_reviews.required_approving_review_count`).
+	pub field: String,
+	/// Whether the field would be added, removed, or changed.
+	pub kind: ProtectionChangeKind,
+	/// The current value of the field, if set.
+	pub current: Option<serde_json::Value>,
+	/// The desired value of the field, if set.
+	pub desired: Option<serde_json::Value>,
+}
+
+/// Kinds of changes to fields of branch protection settings.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtectionChangeKind
+{
+	/// The field isn’t set currently but would be.
+	Added,
+	/// The field is set currently but wouldn’t be anymore.
+	Removed,
+	/// The field is set to a different value currently.
+	Changed,
+}
+
+impl std::fmt::Display for ProtectionChange
+{
+	fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result
+	{
+		let format_value = |value: &Option<serde_json::Value>| value.as_ref()
+			.map(serde_json::Value::to_string)
+			.unwrap_or_else(|| "unset".into());
+
+		match self.kind
+		{
+			ProtectionChangeKind::Added =>
+				write!(formatter, "+{} = {}", self.field, format_value(&self.desired)),
+			ProtectionChangeKind::Removed =>
+				write!(formatter, "-{} (was {})", self.field, format_value(&self.current)),
+			ProtectionChangeKind::Changed => write!(formatter, "~{}: {} → {}", self.field,
+				format_value(&self.current), format_value(&self.desired)),
+		}
+	}
+}
+
+/// Compare the current protection settings of a branch with the desired branch protection request
+/// field by field, returning the changes applying the request would make in the order of the
+/// fields’ paths. Fields that are `null` are treated as unset.
+///
+/// # Arguments
+/// - `current`: The current protection settings of the branch, if it’s protected.
+/// - `desired`: The branch protection request that would be applied.
+pub fn protection_changes(current: Option<&crate::BranchProtectionResponse>,
+	desired: &crate::ProtectBranchRequest)
+	-> Vec<ProtectionChange>
+{
+	let mut current_fields = std::collections::BTreeMap::new();
+
+	if let Some(current) = current
+	{
+		flatten_fields("", to_json_value(&current_protect_branch_request(current)),
+			&mut current_fields);
+	}
+
+	let mut desired_fields = std::collections::BTreeMap::new();
+	flatten_fields("", to_json_value(desired), &mut desired_fields);
+
+	let mut fields: Vec<_> = current_fields.keys().chain(desired_fields.keys()).cloned().collect();
+	fields.sort();
+	fields.dedup();
+
+	fields.into_iter()
+		.filter_map(|field|
+		{
+			let current = current_fields.remove(&field);
+			let desired = desired_fields.remove(&field);
+
+			let kind = match (&current, &desired)
+			{
+				(None, Some(_)) => ProtectionChangeKind::Added,
+				(Some(_), None) => ProtectionChangeKind::Removed,
+				(Some(current), Some(desired)) if current != desired =>
+					ProtectionChangeKind::Changed,
+				_ => return None,
+			};
+
+			Some(ProtectionChange{field, kind, current, desired})
+		})
+		.collect()
+}
+
+/// Express the current protection settings of a branch as the branch protection request that
+/// would reproduce them, so that they can be compared with the desired request. Push restrictions
+/// aren’t supported by requests and are left out.
+#[doc(hidden)]
+fn current_protect_branch_request(current: &crate::BranchProtectionResponse)
+	-> crate::ProtectBranchRequest
+{
+	crate::ProtectBranchRequest
+	{
+		required_status_checks: current.required_status_checks.as_ref()
+			.map(|status_checks| crate::RequiredStatusChecks
+			{
+				strict: status_checks.strict,
+				contexts: status_checks.contexts.clone(),
+			}),
+		enforce_admins: current.enforce_admins.as_ref()
+			.and_then(|enforce_admins| enforce_admins.enabled.then_some(true)),
+		required_pull_request_reviews: current.required_pull_request_reviews.as_ref()
+			.map(|reviews| crate::RequiredPullRequestReviews
+			{
+				required_approving_review_count: reviews.required_approving_review_count
+					.unwrap_or_default()
+					.try_into()
+					.unwrap_or(u8::MAX),
+				dismiss_stale_reviews: reviews.dismiss_stale_reviews,
+				require_code_owner_reviews: reviews.require_code_owner_reviews,
+			}),
+		restrictions: None,
+	}
+}
+
+/// Encode a branch protection request as JSON for comparing it field by field.
+#[doc(hidden)]
+fn to_json_value(request: &crate::ProtectBranchRequest) -> serde_json::Value
+{
+	serde_json::to_value(request)
+		.expect("this call is infallible because branch protection requests are plain data")
+}
+
+/// Collect the leaf fields of a JSON value by their dotted paths, leaving out `null` values.
+/// Arrays are treated as leaves, as their elements don’t have stable paths.
+#[doc(hidden)]
+fn flatten_fields(path: &str, value: serde_json::Value,
+	fields: &mut std::collections::BTreeMap<String, serde_json::Value>)
+{
+	match value
+	{
+		serde_json::Value::Null => (),
+		serde_json::Value::Object(object) =>
+		{
+			for (key, value) in object
+			{
+				let path = match path.is_empty()
+				{
+					true => key,
+					false => format!("{path}.{key}"),
+				};
+
+				flatten_fields(&path, value, fields);
+			}
+		},
+		value =>
+		{
+			fields.insert(path.to_owned(), value);
+		},
+	}
+}
@@ -6,6 +6,24 @@ pub enum Error
 	ReadConfigFile(#[source] std::io::Error),
 	#[error("could not parse config file")]
 	ParseConfigFile(#[source] serde_yaml::Error),
+	#[error("invalid configuration: {0}")]
+	InvalidConfig(String),
+	#[error("could not parse central branch protection settings")]
+	ParseCentralPolicy(#[source] serde_yaml::Error),
+	#[error("could not expand environment variable “{name}” referenced in config file")]
+	ExpandEnvironmentVariable
+	{
+		name: String,
+		#[source]
+		source: std::env::VarError,
+	},
+
+	#[error("could not initialize tracing")]
+	InitializeTracing(#[source] opentelemetry::trace::TraceError),
+	#[error("could not set up StatsD client")]
+	SetUpStatsd(#[source] std::io::Error),
+	#[error("could not resolve StatsD address")]
+	ResolveStatsdAddress(#[source] cadence::MetricError),
 
 	#[error("could not create HTTP client")]
 	CreateHttpClient(#[source] reqwest::Error),
@@ -14,15 +32,43 @@ pub enum Error
 	ReadPrivateGitHubAppKeyFile(#[source] std::io::Error),
 	#[error("could not parse private GitHub App key file")]
 	ParsePrivateGitHubAppKeyFile(#[source] jsonwebtoken::errors::Error),
+	#[error("could not read webhook secret file")]
+	ReadWebhookSecretFile(#[source] std::io::Error),
 	#[error("could not create JWT")]
 	CreateJwt(#[source] jsonwebtoken::errors::Error),
 	#[error("could not obtain GitHub App installation access token")]
 	ObtainGitHubAppInstallationToken(#[source] Box<crate::Error>),
+	#[error("could not look up the metadata of this GitHub App")]
+	ObtainGitHubAppMetadata(#[source] Box<crate::Error>),
+	#[error("could not look up the public keys GitHub signs payloads with")]
+	ObtainPublicKeys(#[source] Box<crate::Error>),
+
+	#[error("GitHub rejected the JWT of this GitHub App, possibly due to clock skew: \
+		{response_body}")]
+	JwtRejected
+	{
+		response_body: String,
+	},
+
+	#[error("the GitHub App installation for the organization “{organization}” is suspended, \
+		which an organization owner needs to undo in the organization’s GitHub App settings")]
+	InstallationSuspended
+	{
+		organization: String,
+	},
 
 	#[error("could not parse URL")]
 	ParseUrl(#[source] url::ParseError),
 	#[error("could not make GitHub API request")]
 	MakeGitHubApiRequest(#[source] reqwest_middleware::Error),
+	#[error("could not resolve the host name of the GitHub API server")]
+	DnsFailed(#[source] reqwest_middleware::Error),
+	#[error("could not connect to the GitHub API server")]
+	ConnectFailed(#[source] reqwest_middleware::Error),
+	#[error("could not establish a TLS connection to the GitHub API server")]
+	TlsError(#[source] reqwest_middleware::Error),
+	#[error("GitHub API request timed out")]
+	TimedOut(#[source] reqwest_middleware::Error),
 	#[error("received GitHub API client error (status code {status_code}): {response_body}")]
 	ReceivedGitHubApiClientError
 	{
@@ -30,15 +76,198 @@ pub enum Error
 		url: url::Url,
 		response_body: String,
 	},
+	#[error("received GitHub API server error (status code {status_code}): {response_body}")]
+	ReceivedGitHubApiServerError
+	{
+		status_code: reqwest::StatusCode,
+		url: url::Url,
+		response_body: String,
+	},
+	#[error("triggered GitHub’s abuse detection mechanism (retry after {} seconds)",
+		retry_after.as_secs())]
+	AbuseDetected
+	{
+		url: url::Url,
+		retry_after: std::time::Duration,
+	},
+	#[error("GitHub API request exceeded the deadline of {} seconds", deadline.as_secs())]
+	DeadlineExceeded
+	{
+		deadline: std::time::Duration,
+	},
 	#[error("could not decode GitHub API response body")]
 	DecodeGitHubApiResponseBody(#[source] serde_json::Error),
+	#[error("could not decode file contents")]
+	DecodeFileContents(#[source] base64::DecodeError),
+	#[error("file is too large or has the unsupported encoding “{encoding}”")]
+	UnsupportedFileEncoding
+	{
+		encoding: String,
+	},
+	#[error("GitHub GraphQL API request failed: {0}")]
+	GraphQlRequestFailed(String),
+	#[error("discussions are disabled for repository “{repository}”")]
+	DiscussionsDisabled
+	{
+		repository: String,
+	},
+	#[error("discussion category “{category}” doesn’t exist in repository “{repository}”")]
+	DiscussionCategoryNotFound
+	{
+		repository: String,
+		category: String,
+	},
+
+	#[error("could not run decision hook")]
+	RunDecisionHook(#[source] std::io::Error),
+	#[error("decision hook didn’t exit within {} seconds", timeout.as_secs())]
+	DecisionHookTimedOut
+	{
+		timeout: std::time::Duration,
+	},
+	#[error("decision hook failed ({status}): {stderr}")]
+	DecisionHookFailed
+	{
+		status: String,
+		stderr: String,
+	},
+	#[error("could not decode decision hook output")]
+	DecodeDecisionHookOutput(#[source] serde_json::Error),
+	#[error("decision hook selected policy “{policy}”, which doesn’t exist")]
+	UnknownDecisionHookPolicy
+	{
+		policy: String,
+	},
 
+	#[error("could not read payload file")]
+	ReadPayloadFile(#[source] std::io::Error),
 	#[error("could not decode payload body")]
 	DecodePayloadBody(#[source] serde_json::Error),
 	#[error("missing payload signature")]
 	MissingPayloadSignature,
 	#[error("invalid payload signature")]
 	InvalidPayloadSignature,
+	#[error("source IP address {0:?} not on allow list")]
+	SourceIpAddressNotAllowed(Option<std::net::IpAddr>),
+
+	#[error("could not write report")]
+	WriteReport(#[source] std::io::Error),
+	#[error("could not encode report as JSON")]
+	EncodeJsonReport(#[source] serde_json::Error),
+	#[error("could not encode report as CSV")]
+	EncodeCsvReport(#[source] csv::Error),
+	#[error("backfilling failed for {repository_count} repositories")]
+	BackfillFailed
+	{
+		repository_count: usize,
+	},
+	#[error("could not read event records")]
+	ReadEventRecords(#[source] std::io::Error),
+	#[error("could not write event records")]
+	WriteEventRecords(#[source] std::io::Error),
+	#[error("retrying failed for {branch_count} branches")]
+	RetryingFailedBranchesFailed
+	{
+		branch_count: usize,
+	},
+}
+
+impl Error
+{
+	/// Check whether this error was caused by a client error with a given HTTP status code.
+	pub fn is_client_error_with_status(&self, expected_status_code: reqwest::StatusCode) -> bool
+	{
+		matches!(self, Self::ReceivedGitHubApiClientError{status_code, ..}
+			if *status_code == expected_status_code)
+	}
+
+	/// Classify an error that occurred while making an HTTP request to the GitHub API as
+	/// precisely as possible, so that failures to reach GitHub can be told apart from local
+	/// configuration issues such as broken TLS settings. Errors that can’t be classified are
+	/// returned as [Error::MakeGitHubApiRequest].
+	pub fn from_request_error(error: reqwest_middleware::Error) -> Self
+	{
+		let reqwest_error = match &error
+		{
+			reqwest_middleware::Error::Reqwest(reqwest_error) => reqwest_error,
+			reqwest_middleware::Error::Middleware(_) => return Self::MakeGitHubApiRequest(error),
+		};
+
+		if reqwest_error.is_timeout()
+		{
+			return Self::TimedOut(error);
+		}
+
+		if !reqwest_error.is_connect()
+		{
+			return Self::MakeGitHubApiRequest(error);
+		}
+
+		// The underlying connector only reports the failing step in its messages, as the TLS
+		// implementation’s error type isn’t exposed
+		let messages: Vec<_> = std::iter::successors(
+			Some(reqwest_error as &dyn std::error::Error), |error| error.source())
+			.map(|error| error.to_string().to_lowercase())
+			.collect();
+		let mentions = |keywords: &[&str]| messages.iter()
+			.any(|message| keywords.iter().any(|keyword| message.contains(keyword)));
+
+		if mentions(&["dns error", "failed to lookup address"])
+		{
+			Self::DnsFailed(error)
+		}
+		else if mentions(&["tls", "ssl", "certificate", "handshake"])
+		{
+			Self::TlsError(error)
+		}
+		else
+		{
+			Self::ConnectFailed(error)
+		}
+	}
+
+	/// The kind of network error this is (`dns`, `connect`, `tls`, `timeout`, or `other`), or
+	/// `None` if this isn’t a network error but, for example, an error response from GitHub.
+	pub fn network_error_kind(&self) -> Option<&'static str>
+	{
+		match self
+		{
+			Self::DnsFailed(_) => Some("dns"),
+			Self::ConnectFailed(_) => Some("connect"),
+			Self::TlsError(_) => Some("tls"),
+			Self::TimedOut(_) => Some("timeout"),
+			Self::MakeGitHubApiRequest(_) => Some("other"),
+			_ => None,
+		}
+	}
+
+	/// Describe this error along with the whole chain of errors causing it in a single line.
+	pub fn describe_with_sources(&self) -> String
+	{
+		let mut description = self.to_string();
+		let mut source = std::error::Error::source(self);
+
+		while let Some(error) = source
+		{
+			description.push_str(&format!(": {error}"));
+			source = error.source();
+		}
+
+		description
+	}
+
+	/// Check whether this error was caused by exceeding the GitHub API rate limit.
+	pub fn is_rate_limit_exceeded(&self) -> bool
+	{
+		match self
+		{
+			Self::ReceivedGitHubApiClientError{status_code, response_body, ..} =>
+				*status_code == reqwest::StatusCode::TOO_MANY_REQUESTS
+					|| (*status_code == reqwest::StatusCode::FORBIDDEN
+						&& response_body.to_lowercase().contains("rate limit")),
+			_ => false,
+		}
+	}
 }
 
 // Allow this crate’s error type to be used for failed HTTP responses
@@ -0,0 +1,223 @@
+/// Configuration of where machine-readable records of handled webhook events are written to.
+#[derive(Clone, Debug, Default, Eq, PartialEq, schemars::JsonSchema, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventSinkConfig
+{
+	/// Write records to the standard output, one JSON object per line.
+	#[default]
+	Stdout,
+	/// Send records to the local syslog daemon via `/dev/log`.
+	Syslog,
+	/// Send records to a collector listening on a Unix domain socket, one JSON object per line.
+	UnixSocket
+	{
+		/// Path to the Unix domain socket.
+		path: std::path::PathBuf,
+	},
+}
+
+/// Writes machine-readable records of handled webhook events to the configured sink.
+///
+/// Failures to write to the sink are logged but otherwise ignored, so that they don’t affect
+/// handling webhook events. As writing to the sink blocks, records are written on a thread
+/// dedicated to blocking operations instead of the async runtime’s worker threads.
+#[derive(Clone)]
+pub struct EventSink
+{
+	#[doc(hidden)]
+	config: std::sync::Arc<EventSinkConfig>,
+}
+
+impl EventSink
+{
+	/// Create a new event sink from its configuration.
+	pub fn new(config: EventSinkConfig) -> Self
+	{
+		Self
+		{
+			config: std::sync::Arc::new(config),
+		}
+	}
+
+	/// Write a record of the outcome of handling a webhook event for a given branch.
+	///
+	/// # Arguments
+	/// - `target`: The branch the webhook event asked us to protect.
+	/// - `outcome`: The outcome of handling the webhook event.
+	pub async fn record(&self, target: &crate::ProtectionTarget, outcome: &crate::HandledOutcome)
+	{
+		let record = EventRecord
+		{
+			timestamp: chrono::Utc::now(),
+			organization: &target.organization_name,
+			repository: &target.repository_name,
+			branch: &target.branch_name,
+			event: target.event_type,
+			sender: &target.creator_name,
+			outcome: outcome.name(),
+			detail: outcome.detail(),
+		};
+
+		self.record_encoded(&record).await;
+	}
+
+	/// Write a record of a lifecycle event of this service, including a summary of the
+	/// configuration, so that restarts can be correlated with changes in behavior.
+	///
+	/// # Arguments
+	/// - `event`: The lifecycle event.
+	/// - `organization`: The organization this service watches.
+	/// - `config`: The configuration of this service.
+	pub async fn record_lifecycle(&self, event: LifecycleEvent, organization: &str,
+		config: &crate::Config)
+	{
+		let mut policies: Vec<_> = config.policies.keys().map(String::as_str).collect();
+		policies.sort_unstable();
+
+		let record = LifecycleRecord
+		{
+			timestamp: chrono::Utc::now(),
+			event: event.name(),
+			version: env!("CARGO_PKG_VERSION"),
+			organization,
+			default_policy: config.default_policy.as_deref(),
+			policies,
+		};
+
+		self.record_encoded(&record).await;
+	}
+
+	/// Encode a record as JSON and write it to the configured sink.
+	#[doc(hidden)]
+	async fn record_encoded<R>(&self, record: &R)
+	where
+		R: serde::Serialize,
+	{
+		let record = match serde_json::to_string(record)
+		{
+			Ok(record) => record,
+			Err(error) =>
+			{
+				log::warn!("could not encode event record: {error}");
+				return;
+			},
+		};
+
+		let sink = self.clone();
+		let result = tokio::task::spawn_blocking(move || sink.write(&record)).await
+			.unwrap_or_else(|error| Err(std::io::Error::other(error)));
+
+		if let Err(error) = result
+		{
+			log::warn!("could not write event record to {:?}: {error}", self.config);
+		}
+	}
+
+	/// Write an encoded record to the configured sink.
+	#[doc(hidden)]
+	fn write(&self, record: &str) -> std::io::Result<()>
+	{
+		use std::io::Write as _;
+
+		match &*self.config
+		{
+			EventSinkConfig::Stdout => writeln!(std::io::stdout(), "{record}"),
+			EventSinkConfig::Syslog =>
+			{
+				let socket = std::os::unix::net::UnixDatagram::unbound()?;
+
+				let message =
+					format!("<{SYSLOG_PRIORITY}>{}: {record}", env!("CARGO_PKG_NAME"));
+
+				socket.send_to(message.as_bytes(), SYSLOG_SOCKET_PATH)?;
+
+				Ok(())
+			},
+			EventSinkConfig::UnixSocket{path} =>
+			{
+				let mut stream = std::os::unix::net::UnixStream::connect(path)?;
+
+				// Don’t let an unresponsive collector block handling webhook events for long
+				stream.set_write_timeout(Some(SOCKET_WRITE_TIMEOUT))?;
+
+				writeln!(stream, "{record}")
+			},
+		}
+	}
+}
+
+/// Machine-readable record of the outcome of handling a webhook event (serialized to JSON).
+#[derive(serde::Serialize)]
+struct EventRecord<'a>
+{
+	/// When the webhook event was handled.
+	timestamp: chrono::DateTime<chrono::Utc>,
+	/// The organization owning the repository.
+	organization: &'a str,
+	/// The name of the repository.
+	repository: &'a str,
+	/// The name of the branch.
+	branch: &'a str,
+	/// The type of the webhook event (example: `create`).
+	event: &'static str,
+	/// The handle of the user who caused the webhook event.
+	sender: &'a str,
+	/// The kind of outcome (see [crate::HandledOutcome::name]).
+	outcome: &'static str,
+	/// Details about the outcome, such as the URL of a created issue or an error message.
+	detail: String,
+}
+
+/// Lifecycle events of this service.
+#[derive(Clone, Copy, Debug)]
+pub enum LifecycleEvent
+{
+	/// The service started listening for webhook events.
+	Started,
+	/// The service was asked to shut down and stops accepting webhook events.
+	Stopping,
+	/// The configuration was reloaded after receiving SIGHUP.
+	ConfigReloaded,
+}
+
+impl LifecycleEvent
+{
+	/// A short, machine-readable name of this lifecycle event.
+	fn name(self) -> &'static str
+	{
+		match self
+		{
+			Self::Started => "service_started",
+			Self::Stopping => "service_stopping",
+			Self::ConfigReloaded => "config_reloaded",
+		}
+	}
+}
+
+/// Machine-readable record of a lifecycle event of this service (serialized to JSON).
+#[derive(serde::Serialize)]
+struct LifecycleRecord<'a>
+{
+	/// When the lifecycle event occurred.
+	timestamp: chrono::DateTime<chrono::Utc>,
+	/// The kind of lifecycle event (see [LifecycleEvent::name]).
+	event: &'static str,
+	/// The version of this service.
+	version: &'static str,
+	/// The organization this service watches.
+	organization: &'a str,
+	/// The name of the policy applied by default, if not `branch_protection`.
+	default_policy: Option<&'a str>,
+	/// The names of all configured policies in alphabetical order.
+	policies: Vec<&'a str>,
+}
+
+/// The syslog priority of event records, consisting of the facility “user” (1) and the severity
+/// “informational” (6) as defined in RFC 5424 (1 × 8 + 6).
+const SYSLOG_PRIORITY: u8 = 14;
+
+/// Path to the socket of the local syslog daemon.
+const SYSLOG_SOCKET_PATH: &str = "/dev/log";
+
+/// How long to wait for writing a record to a Unix domain socket before giving up.
+const SOCKET_WRITE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
@@ -0,0 +1,243 @@
+/// Export the current protection settings of the default branches of all repositories in the
+/// organization the GitHub API client is authenticated for.
+///
+/// This only reads from the GitHub API and doesn’t change any settings. Requests that exceed the
+/// GitHub API rate limit are retried after waiting for the rate limit to recover.
+///
+/// # Arguments
+/// - `github_api_client`: A handle to the GitHub API client.
+/// - `format`: The format of the report.
+/// - `output`: Path to the file the report is written to (standard output if `None`).
+pub async fn export(
+	github_api_client: &crate::github_api::Client,
+	format: crate::cli::ExportFormat,
+	output: Option<&std::path::Path>)
+	-> Result<(), crate::Error>
+{
+	let organization_name = github_api_client.organization();
+
+	let repositories: Vec<crate::Repository> = retry_if_rate_limited(
+		|| github_api_client.get_paginated(format!("orgs/{}/repos",
+			crate::github_api::encode_path_segment(organization_name)))).await?;
+
+	log::info!("exporting branch protection settings of {} repositories", repositories.len());
+
+	let mut records = Vec::with_capacity(repositories.len());
+
+	for repository in repositories
+	{
+		let repository_name = repository.name;
+
+		let branch_name = match repository.default_branch
+		{
+			Some(branch_name) => branch_name,
+			None =>
+			{
+				log::warn!("repository “{repository_name}” has no default branch, skipping it");
+				continue;
+			},
+		};
+
+		// Branches that aren’t protected (or don’t exist yet in empty repositories) are reported
+		// as unprotected
+		let branch_protection = retry_if_rate_limited(
+			|| github_api_client.get_branch_protection(organization_name, &repository_name,
+				&branch_name)).await?;
+
+		records.push(BranchProtectionRecord::new(repository_name, branch_name, branch_protection));
+	}
+
+	let mut writer: Box<dyn std::io::Write> = match output
+	{
+		Some(path) => Box::new(std::fs::File::create(path).map_err(crate::Error::WriteReport)?),
+		None => Box::new(std::io::stdout()),
+	};
+
+	use std::io::Write as _;
+
+	match format
+	{
+		crate::cli::ExportFormat::Json =>
+		{
+			serde_json::to_writer_pretty(&mut writer, &records)
+				.map_err(crate::Error::EncodeJsonReport)?;
+			writeln!(writer).map_err(crate::Error::WriteReport)?;
+		},
+		crate::cli::ExportFormat::Csv =>
+		{
+			let mut csv_writer = csv::Writer::from_writer(writer);
+
+			csv_writer.write_record(BranchProtectionRecord::CSV_HEADER)
+				.map_err(crate::Error::EncodeCsvReport)?;
+
+			for record in &records
+			{
+				csv_writer.write_record(record.to_csv_record())
+					.map_err(crate::Error::EncodeCsvReport)?;
+			}
+
+			csv_writer.flush().map_err(crate::Error::WriteReport)?;
+		},
+	}
+
+	log::info!("exported branch protection settings of {} default branches", records.len());
+
+	Ok(())
+}
+
+/// Run a GitHub API operation, waiting and retrying if it failed because the rate limit was
+/// exceeded.
+///
+/// # Arguments
+/// - `operation`: A closure making the GitHub API request.
+pub async fn retry_if_rate_limited<F, Fut, T>(mut operation: F) -> Result<T, crate::Error>
+where
+	F: FnMut() -> Fut,
+	Fut: std::future::Future<Output = Result<T, crate::Error>>,
+{
+	let mut attempt = 1;
+
+	loop
+	{
+		match operation().await
+		{
+			Err(error)
+				if error.is_rate_limit_exceeded() && attempt < MAXIMUM_RATE_LIMITED_ATTEMPTS =>
+			{
+				log::warn!("GitHub API rate limit exceeded, waiting {} seconds before retrying",
+					RATE_LIMIT_WAIT_DURATION.as_secs());
+
+				tokio::time::sleep(RATE_LIMIT_WAIT_DURATION).await;
+				attempt += 1;
+			},
+			result => return result,
+		}
+	}
+}
+
+/// How long to wait before retrying a request that exceeded the GitHub API rate limit.
+const RATE_LIMIT_WAIT_DURATION: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// How often to attempt a request that keeps exceeding the rate limit before giving up (GitHub
+/// resets rate limits every hour).
+const MAXIMUM_RATE_LIMITED_ATTEMPTS: u32 = 61;
+
+/// The branch protection settings of a single default branch as included in the report.
+#[derive(serde::Serialize)]
+struct BranchProtectionRecord
+{
+	repository: String,
+	branch: String,
+	protected: bool,
+	enforce_admins: Option<bool>,
+	required_approving_review_count: Option<u32>,
+	dismiss_stale_reviews: Option<bool>,
+	require_code_owner_reviews: Option<bool>,
+	strict_status_checks: Option<bool>,
+	required_status_checks: Vec<String>,
+	restricted_push_users: Vec<String>,
+	restricted_push_teams: Vec<String>,
+	allow_force_pushes: Option<bool>,
+	allow_deletions: Option<bool>,
+}
+
+impl BranchProtectionRecord
+{
+	/// The column names of the report in CSV format.
+	const CSV_HEADER: [&'static str; 13] =
+	[
+		"repository",
+		"branch",
+		"protected",
+		"enforce_admins",
+		"required_approving_review_count",
+		"dismiss_stale_reviews",
+		"require_code_owner_reviews",
+		"strict_status_checks",
+		"required_status_checks",
+		"restricted_push_users",
+		"restricted_push_teams",
+		"allow_force_pushes",
+		"allow_deletions",
+	];
+
+	fn new(repository: String, branch: String,
+		branch_protection: Option<crate::BranchProtectionResponse>)
+		-> Self
+	{
+		let branch_protection = match branch_protection
+		{
+			Some(branch_protection) => branch_protection,
+			None => return Self
+			{
+				repository,
+				branch,
+				protected: false,
+				enforce_admins: None,
+				required_approving_review_count: None,
+				dismiss_stale_reviews: None,
+				require_code_owner_reviews: None,
+				strict_status_checks: None,
+				required_status_checks: vec![],
+				restricted_push_users: vec![],
+				restricted_push_teams: vec![],
+				allow_force_pushes: None,
+				allow_deletions: None,
+			},
+		};
+
+		let reviews = branch_protection.required_pull_request_reviews.as_ref();
+		let status_checks = branch_protection.required_status_checks;
+		let restrictions = branch_protection.restrictions;
+
+		Self
+		{
+			repository,
+			branch,
+			protected: true,
+			enforce_admins: branch_protection.enforce_admins.map(|setting| setting.enabled),
+			required_approving_review_count:
+				reviews.and_then(|reviews| reviews.required_approving_review_count),
+			dismiss_stale_reviews: reviews.map(|reviews| reviews.dismiss_stale_reviews),
+			require_code_owner_reviews: reviews.map(|reviews| reviews.require_code_owner_reviews),
+			strict_status_checks: status_checks.as_ref().map(|status_checks| status_checks.strict),
+			required_status_checks: status_checks.map(|status_checks| status_checks.contexts)
+				.unwrap_or_default(),
+			restricted_push_users: restrictions.as_ref()
+				.map(|restrictions|
+					restrictions.users.iter().map(|user| user.login.clone()).collect())
+				.unwrap_or_default(),
+			restricted_push_teams: restrictions
+				.map(|restrictions| restrictions.teams.into_iter().map(|team| team.slug).collect())
+				.unwrap_or_default(),
+			allow_force_pushes: branch_protection.allow_force_pushes.map(|setting| setting.enabled),
+			allow_deletions: branch_protection.allow_deletions.map(|setting| setting.enabled),
+		}
+	}
+
+	/// Format this record as a CSV row, joining lists with semicolons and leaving unset fields
+	/// empty.
+	fn to_csv_record(&self) -> [String; 13]
+	{
+		fn optional<T: ToString>(value: Option<T>) -> String
+		{
+			value.map(|value| value.to_string()).unwrap_or_default()
+		}
+
+		[
+			self.repository.clone(),
+			self.branch.clone(),
+			self.protected.to_string(),
+			optional(self.enforce_admins),
+			optional(self.required_approving_review_count),
+			optional(self.dismiss_stale_reviews),
+			optional(self.require_code_owner_reviews),
+			optional(self.strict_status_checks),
+			self.required_status_checks.join(";"),
+			self.restricted_push_users.join(";"),
+			self.restricted_push_teams.join(";"),
+			optional(self.allow_force_pushes),
+			optional(self.allow_deletions),
+		]
+	}
+}
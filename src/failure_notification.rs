@@ -0,0 +1,87 @@
+/// Configuration of the alert sent to operators when handling a webhook event fails permanently.
+#[derive(Clone, Debug, PartialEq, schemars::JsonSchema, serde::Deserialize)]
+pub struct FailureNotificationConfig
+{
+	/// The URL the alert is posted to, such as the URL of a Slack incoming webhook. Keep this
+	/// secret, as anyone knowing it can post messages.
+	pub webhook_url: url::Url,
+	/// The format of the alert, either `slack` for a message with a `text` field understood by
+	/// Slack and compatible chat services or `json` for an object with the individual fields
+	/// (optional, default: `slack`).
+	#[serde(default)]
+	pub format: FailureNotificationFormat,
+}
+
+/// Formats in which alerts about failures can be posted.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, schemars::JsonSchema,
+	serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureNotificationFormat
+{
+	/// `{"text": "…"}`, as expected by Slack incoming webhooks.
+	#[default]
+	Slack,
+	/// `{"organization": "…", "repository": "…", "branch": "…", "error": "…"}`
+	Json,
+}
+
+/// Posts alerts about webhook events whose handling failed permanently, so that branches left
+/// unprotected because of persistent failures don’t go unnoticed.
+///
+/// Alerts are posted in the background, and failures to post them are logged but otherwise ignored.
+/// The notifier can safely be shared between threads, as cloning it only clones handles to the
+/// underlying data.
+#[derive(Clone, Default)]
+pub struct FailureNotifier
+{
+	#[doc(hidden)]
+	reqwest_client: reqwest::Client,
+}
+
+impl FailureNotifier
+{
+	/// Post an alert about a failure to handle a webhook event for a given branch in the
+	/// background.
+	///
+	/// # Arguments
+	/// - `config`: Where and in which format to post the alert.
+	/// - `target`: The branch the webhook event asked us to protect.
+	/// - `error`: The final error, including the chain of causes.
+	pub fn notify(&self, config: &FailureNotificationConfig, target: &crate::ProtectionTarget,
+		error: String)
+	{
+		let body = match config.format
+		{
+			FailureNotificationFormat::Slack => serde_json::json!(
+			{
+				"text": format!("Could not protect branch “{}” in repository “{}/{}”: {error}",
+					target.branch_name, target.organization_name, target.repository_name),
+			}),
+			FailureNotificationFormat::Json => serde_json::json!(
+			{
+				"organization": target.organization_name,
+				"repository": target.repository_name,
+				"branch": target.branch_name,
+				"error": error,
+			}),
+		};
+
+		let request = self.reqwest_client.post(config.webhook_url.clone())
+			// Don’t let an unresponsive receiver pile up pending alerts
+			.timeout(FAILURE_NOTIFICATION_TIMEOUT)
+			.json(&body);
+
+		tokio::spawn(async move
+		{
+			let result = request.send().await.and_then(reqwest::Response::error_for_status);
+
+			if let Err(error) = result
+			{
+				log::warn!("could not post failure notification: {error}");
+			}
+		});
+	}
+}
+
+/// How long to wait for the receiver of a failure notification to respond.
+const FAILURE_NOTIFICATION_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
@@ -0,0 +1,136 @@
+/// Options for testing this service in staging environments, which must never be used in
+/// production.
+#[doc(hidden)]
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
+pub struct DebugConfig
+{
+	/// Make a share of GitHub API requests fail, which additionally requires passing
+	/// `--allow-fault-injection` (optional, default: disabled).
+	pub fault_injection: Option<FaultInjectionConfig>,
+	/// Include how long it took to reply to webhook deliveries in the `processing_ms` field of
+	/// replies, which shows up in the delivery log of the GitHub App (optional, default: `false`).
+	#[serde(default)]
+	pub include_timing: bool,
+}
+
+/// Which GitHub API requests to fail and how, for testing retries and failure alerts.
+#[doc(hidden)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+pub struct FaultInjectionConfig
+{
+	/// The percentage of GitHub API requests to fail (between 0 and 100).
+	pub percentage: f64,
+	/// The HTTP status code failed requests respond with, which needs to be a client or server
+	/// error (optional, default: 503).
+	#[serde(default = "default_fault_injection_status_code")]
+	pub status_code: u16,
+}
+
+#[doc(hidden)]
+fn default_fault_injection_status_code() -> u16
+{
+	503
+}
+
+impl FaultInjectionConfig
+{
+	/// Check the configuration for invalid values that can’t be detected while parsing.
+	pub fn validate(&self) -> Result<(), crate::Error>
+	{
+		if !(0.0..=100.0).contains(&self.percentage)
+		{
+			return Err(crate::Error::InvalidConfig(format!("debug.fault_injection.percentage must \
+				be between 0 and 100, but is {}", self.percentage)));
+		}
+
+		match reqwest::StatusCode::from_u16(self.status_code)
+		{
+			Ok(status_code) if status_code.is_client_error() || status_code.is_server_error() =>
+				Ok(()),
+			_ => Err(crate::Error::InvalidConfig(format!("debug.fault_injection.status_code must \
+				be a client or server error status code (4xx or 5xx), but is {}",
+				self.status_code))),
+		}
+	}
+}
+
+/// Fails the configured share of GitHub API requests in place of sending them. Requests are
+/// failed evenly spaced rather than randomly, so that test runs are reproducible.
+///
+/// The fault injector can safely be shared between threads.
+#[doc(hidden)]
+pub struct FaultInjector
+{
+	#[doc(hidden)]
+	config: FaultInjectionConfig,
+	#[doc(hidden)]
+	request_count: std::sync::atomic::AtomicU64,
+}
+
+impl FaultInjector
+{
+	/// Create a fault injector, which should only be done if explicitly allowed.
+	///
+	/// # Arguments
+	/// - `config`: Which requests to fail and how.
+	pub fn new(config: FaultInjectionConfig) -> Self
+	{
+		log::warn!("FAULT INJECTION IS ENABLED: {}% of GitHub API requests will fail with status \
+			code {}. Never use this in production", config.percentage, config.status_code);
+
+		Self
+		{
+			config,
+			request_count: Default::default(),
+		}
+	}
+
+	/// Decide whether to fail the next GitHub API request and return the failure in place of its
+	/// result if so. Client errors are returned as [crate::Error::ReceivedGitHubApiClientError],
+	/// while server errors are returned as responses, as if GitHub had responded this way.
+	///
+	/// # Arguments
+	/// - `url`: The URL of the request.
+	pub fn inject(&self, url: &url::Url) -> Option<Result<reqwest::Response, crate::Error>>
+	{
+		use std::sync::atomic::Ordering;
+
+		let request_count = self.request_count.fetch_add(1, Ordering::Relaxed) + 1;
+
+		// Fail a request whenever the share of failed requests would drop below the percentage
+		let share = self.config.percentage / 100.0;
+		let should_fail = (request_count as f64 * share).floor()
+			> ((request_count - 1) as f64 * share).floor();
+
+		if !should_fail
+		{
+			return None;
+		}
+
+		let status_code = reqwest::StatusCode::from_u16(self.config.status_code)
+			.expect("this call is infallible because the status code was validated");
+
+		log::warn!("injecting fault: failing GitHub API request to “{url}” with status code \
+			{status_code}");
+
+		if status_code.is_client_error()
+		{
+			return Some(Err(crate::Error::ReceivedGitHubApiClientError
+			{
+				status_code,
+				url: url.clone(),
+				response_body: INJECTED_FAULT_MESSAGE.into(),
+			}));
+		}
+
+		let response = warp::http::Response::builder()
+			.status(status_code)
+			.body(INJECTED_FAULT_MESSAGE)
+			.expect("this call is infallible because the status code was validated");
+
+		Some(Ok(response.into()))
+	}
+}
+
+/// The response body of requests failed by fault injection.
+const INJECTED_FAULT_MESSAGE: &str = "injected fault";
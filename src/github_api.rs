@@ -1,5 +1,5 @@
 /// Configuration of the GitHub API client.
-#[derive(serde::Deserialize)]
+#[derive(Clone, PartialEq, schemars::JsonSchema, serde::Deserialize)]
 pub struct Config
 {
 	/// The base URL of the GitHub API server with a trailing slash (optional, default:
@@ -16,8 +16,346 @@ pub struct Config
 	/// The numeric App ID of this GitHub App as shown at the top of its *About* page.
 	app_id: u64,
 	/// To verify that incoming webhook payloads actually come from GitHub.com, provide the GitHub
-	/// App’s webhook secret (optional, but recommended for production use).
+	/// App’s webhook secret (optional, but recommended for production use). Alternatively, see
+	/// `webhook_secret_path`.
 	webhook_secret: Option<String>,
+	/// Path to a file containing the GitHub App’s webhook secret, as an alternative to providing it
+	/// inline via `webhook_secret`, for example, when using secret managers that mount secrets as
+	/// files. Trailing whitespace and newlines are ignored (optional).
+	webhook_secret_path: Option<std::path::PathBuf>,
+	/// Webhook secrets by organization slug, for organizations whose webhooks are configured with a
+	/// secret of their own. Payloads of other organizations are verified with `webhook_secret`,
+	/// and rejected if that isn’t set (optional, default: none).
+	#[serde(default)]
+	webhook_secrets: std::collections::HashMap<String, String>,
+	/// The algorithms accepted for payload signatures in order of preference. A payload is verified
+	/// with the first of these algorithms whose signature header is present. SHA-1 should only be
+	/// accepted if required by older GitHub servers (optional, default: `[sha256]`).
+	#[serde(default = "default_signature_algorithms")]
+	signature_algorithms: Vec<SignatureAlgorithm>,
+	/// How payload signatures are verified, either `hmac` with the webhook secret or `public_key`
+	/// with the public keys GitHub publishes for event types it signs asymmetrically (optional,
+	/// default: `hmac`).
+	#[serde(default)]
+	signature_verification: SignatureVerification,
+	/// The API endpoint listing the public keys payloads are signed with if
+	/// `signature_verification` is `public_key` (optional, default:
+	/// `meta/public_keys/secret_scanning`).
+	#[serde(default = "default_public_keys_endpoint")]
+	public_keys_endpoint: String,
+	/// Abort GitHub API requests that take longer than this number of seconds in total, including
+	/// all retries (optional, default: no deadline other than the retry policy of five minutes).
+	request_deadline_seconds: Option<u64>,
+	/// Retry non-idempotent requests such as creating issues after failures, too. If a response is
+	/// lost after GitHub processed such a request, retrying it may create duplicates, for example,
+	/// duplicate issues (optional, default: `true`).
+	#[serde(default = "default_retry_non_idempotent_requests")]
+	retry_non_idempotent_requests: bool,
+	/// Talk to the GitHub API server via HTTP/2 right away instead of negotiating the protocol,
+	/// which saves a round trip per connection. Only enable this for servers known to support
+	/// HTTP/2, as requests fail otherwise (optional, default: `false`).
+	#[serde(default)]
+	http2_prior_knowledge: bool,
+	/// Send TCP keep-alive probes on idle connections to the GitHub API server after this number of
+	/// seconds, which keeps connections through proxies and firewalls alive for reuse at the cost
+	/// of some background traffic (optional, default: no keep-alive probes).
+	tcp_keepalive_seconds: Option<u64>,
+	/// The minimum TLS version accepted for connections to the GitHub API server, either `"1.2"` or
+	/// `"1.3"` (optional, default: `"1.2"`).
+	#[serde(default)]
+	min_tls_version: TlsVersion,
+	/// Don’t request an access token at startup but only with the first GitHub API request, so
+	/// that this service can start even if GitHub is unreachable. Configuration errors such as a
+	/// wrong App ID are then only detected with the first request (optional, default: `false`).
+	#[serde(default)]
+	lazy_token: bool,
+	/// The scheme of the `Authorization` header sent with GitHub API requests, either `bearer` or
+	/// `token` (GitHub’s older scheme). This only needs to be changed for unusual gateways in
+	/// front of GitHub Enterprise Server that expect the older scheme (optional, default:
+	/// `bearer`).
+	#[serde(default)]
+	auth_header_scheme: AuthHeaderScheme,
+	/// Backdate the time JWTs for authenticating as the GitHub App were issued at by this number
+	/// of seconds, so that GitHub accepts them even if this host’s clock is ahead of GitHub’s. This
+	/// doesn’t affect when JWTs expire (optional, default: 60).
+	#[serde(default = "default_jwt_issued_at_backdate_seconds")]
+	jwt_issued_at_backdate_seconds: u64,
+	/// How often to retry failed GitHub API requests by the class of error (optional, default: see
+	/// [RetryLimits]).
+	#[serde(default)]
+	retry_limits: RetryLimits,
+}
+
+/// How often to retry failed GitHub API requests by the class of error. The defaults match the
+/// behavior without any limits configured.
+#[derive(Clone, Debug, Eq, PartialEq, schemars::JsonSchema, serde::Deserialize)]
+pub struct RetryLimits
+{
+	/// How often to retry requests that failed because of network issues such as timeouts,
+	/// including 408 Request Timeout responses, or refused connections (optional, default: as often
+	/// as possible within five minutes with exponential backoff).
+	network_errors: Option<u32>,
+	/// How often to retry requests that GitHub responded to with a server error (5xx status code)
+	/// (optional, default: as often as possible within five minutes with exponential backoff).
+	server_errors: Option<u32>,
+	/// How often to retry requests that triggered GitHub’s abuse detection mechanism or exceeded
+	/// GitHub’s primary or secondary rate limits (429 Too Many Requests or 403 Forbidden) after
+	/// waiting for the time GitHub indicates, unless that’s longer than five minutes (optional,
+	/// default: 2).
+	#[serde(default = "default_rate_limit_error_retries")]
+	rate_limit_errors: u32,
+	/// How often to retry requests that GitHub rejected with 401 Unauthorized after renewing the
+	/// access token, which usually means that the token expired (optional, default: 1).
+	#[serde(default = "default_authentication_error_retries")]
+	authentication_errors: u32,
+}
+
+impl Default for RetryLimits
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			network_errors: None,
+			server_errors: None,
+			rate_limit_errors: default_rate_limit_error_retries(),
+			authentication_errors: default_authentication_error_retries(),
+		}
+	}
+}
+
+impl Config
+{
+	/// Read secrets that are provided as paths to files, making sure that each secret is provided
+	/// either inline or as a file but not both.
+	pub(crate) fn read_secret_files(&mut self) -> Result<(), crate::Error>
+	{
+		if let Some(webhook_secret_path) = &self.webhook_secret_path
+		{
+			if self.webhook_secret.is_some()
+			{
+				return Err(crate::Error::InvalidConfig("only one of webhook_secret and \
+					webhook_secret_path may be set".into()));
+			}
+
+			let webhook_secret = std::fs::read_to_string(webhook_secret_path)
+				.map_err(crate::Error::ReadWebhookSecretFile)?;
+
+			// Files often end with a newline, which isn’t part of the secret
+			self.webhook_secret = Some(webhook_secret.trim_end().to_owned());
+		}
+
+		Ok(())
+	}
+
+	/// Check the configuration for invalid values that can’t be detected while parsing.
+	pub(crate) fn validate(&self) -> Result<(), crate::Error>
+	{
+		self.jwt_issued_at_backdate()?;
+
+		if self.signature_algorithms.is_empty()
+		{
+			return Err(crate::Error::InvalidConfig(
+				"github_api.signature_algorithms must list at least one algorithm".into()));
+		}
+
+		Ok(())
+	}
+
+	/// Whether the payloads of webhook events are verified, which requires a webhook secret unless
+	/// they’re verified with GitHub’s public keys.
+	pub fn verifies_payloads(&self) -> bool
+	{
+		match self.signature_verification
+		{
+			SignatureVerification::Hmac =>
+				self.webhook_secret.is_some() || !self.webhook_secrets.is_empty(),
+			SignatureVerification::PublicKey => true,
+		}
+	}
+
+	/// How long to backdate the time JWTs were issued at.
+	fn jwt_issued_at_backdate(&self) -> Result<chrono::Duration, crate::Error>
+	{
+		chrono::Duration::from_std(std::time::Duration::from_secs(
+			self.jwt_issued_at_backdate_seconds))
+			.map_err(|_| crate::Error::InvalidConfig(
+				"github_api.jwt_issued_at_backdate_seconds is too large".into()))
+	}
+}
+
+/// Algorithms GitHub signs webhook payloads with, each sent in a header of its own.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, schemars::JsonSchema, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureAlgorithm
+{
+	/// HMAC-SHA256, sent in the `X-Hub-Signature-256` header.
+	Sha256,
+	/// HMAC-SHA1, sent in the `X-Hub-Signature` header, which GitHub only keeps for compatibility.
+	Sha1,
+}
+
+impl SignatureAlgorithm
+{
+	#[doc(hidden)]
+	const ALL: [Self; 2] = [Self::Sha256, Self::Sha1];
+
+	/// The name of the header containing signatures created with this algorithm.
+	fn header_name(self) -> &'static str
+	{
+		match self
+		{
+			Self::Sha256 => "x-hub-signature-256",
+			Self::Sha1 => "x-hub-signature",
+		}
+	}
+
+	/// The prefix of signatures created with this algorithm.
+	fn prefix(self) -> &'static str
+	{
+		match self
+		{
+			Self::Sha256 => "sha256=",
+			Self::Sha1 => "sha1=",
+		}
+	}
+
+	/// Compute the hex-encoded signature of a payload without prefix.
+	///
+	/// # Arguments
+	/// - `payload`: The raw payload bytes.
+	/// - `secret`: The webhook secret used as the HMAC key.
+	fn sign(self, payload: &[u8], secret: &str) -> String
+	{
+		use hmac::Mac as _;
+
+		match self
+		{
+			Self::Sha256 =>
+			{
+				let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(secret.as_bytes())
+					.expect("this call is infallible because HMAC supports keys of arbitrary size");
+				mac.update(payload);
+
+				hex::encode(mac.finalize().into_bytes())
+			},
+			Self::Sha1 =>
+			{
+				let mut mac = hmac::Hmac::<sha1::Sha1>::new_from_slice(secret.as_bytes())
+					.expect("this call is infallible because HMAC supports keys of arbitrary size");
+				mac.update(payload);
+
+				hex::encode(mac.finalize().into_bytes())
+			},
+		}
+	}
+}
+
+/// Ways of verifying the signatures of webhook payloads.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, schemars::JsonSchema,
+	serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureVerification
+{
+	/// Verify the HMAC digest in the `X-Hub-Signature-256` header with the webhook secret.
+	#[default]
+	Hmac,
+	/// Verify the ECDSA or Ed25519 signature in the `Github-Public-Key-Signature` header with the
+	/// public key named in the `Github-Public-Key-Identifier` header, as published by GitHub.
+	PublicKey,
+}
+
+/// Schemes of the `Authorization` header sent with GitHub API requests.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, schemars::JsonSchema,
+	serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthHeaderScheme
+{
+	/// `Authorization: Bearer <token>`
+	#[default]
+	Bearer,
+	/// `Authorization: token <token>`
+	Token,
+}
+
+impl AuthHeaderScheme
+{
+	/// The value of the `Authorization` header for a given credential, which is marked as
+	/// sensitive so that it isn’t logged.
+	fn header_value(self, credential: &impl Credential) -> reqwest::header::HeaderValue
+	{
+		let header_value = match self
+		{
+			Self::Bearer => format!("Bearer {}", credential.secret()),
+			Self::Token => format!("token {}", credential.secret()),
+		};
+
+		let mut header_value = reqwest::header::HeaderValue::from_str(&header_value)
+			.expect("this call is infallible because tokens consist of visible ASCII characters");
+		header_value.set_sensitive(true);
+
+		header_value
+	}
+}
+
+/// TLS versions that can be required as the minimum for connections to the GitHub API server.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, schemars::JsonSchema,
+	serde::Deserialize)]
+pub enum TlsVersion
+{
+	#[default]
+	#[serde(rename = "1.2")]
+	Tls1_2,
+	#[serde(rename = "1.3")]
+	Tls1_3,
+}
+
+impl From<TlsVersion> for reqwest::tls::Version
+{
+	fn from(version: TlsVersion) -> Self
+	{
+		match version
+		{
+			TlsVersion::Tls1_2 => Self::TLS_1_2,
+			TlsVersion::Tls1_3 => Self::TLS_1_3,
+		}
+	}
+}
+
+#[doc(hidden)]
+fn default_signature_algorithms() -> Vec<SignatureAlgorithm>
+{
+	vec![SignatureAlgorithm::Sha256]
+}
+
+#[doc(hidden)]
+fn default_public_keys_endpoint() -> String
+{
+	"meta/public_keys/secret_scanning".to_string()
+}
+
+#[doc(hidden)]
+fn default_retry_non_idempotent_requests() -> bool
+{
+	true
+}
+
+#[doc(hidden)]
+fn default_jwt_issued_at_backdate_seconds() -> u64
+{
+	60
+}
+
+#[doc(hidden)]
+fn default_rate_limit_error_retries() -> u32
+{
+	2
+}
+
+#[doc(hidden)]
+fn default_authentication_error_retries() -> u32
+{
+	1
 }
 
 #[doc(hidden)]
@@ -34,7 +372,8 @@ fn github_com_api_base_url() -> url::Url
 /// GitHub App has the proper permissions configured. This is achieved by automatically obtaining an
 /// installation access token. The client automatically renews the token once it expires. Also, the
 /// client retries API requests that failed for reasons such as network issues multiple times for a
-/// total of up to five minutes.
+/// total of up to five minutes unless configured otherwise (non-idempotent requests only if
+/// configured).
 ///
 /// Currently, the GitHub API client supports only a single organization.
 ///
@@ -49,6 +388,9 @@ pub struct Client
 	#[doc(hidden)]
 	reqwest_client: reqwest_middleware::ClientWithMiddleware,
 	#[doc(hidden)]
+	// Used instead of the client above for non-idempotent requests if these must not be retried
+	non_retrying_reqwest_client: reqwest_middleware::ClientWithMiddleware,
+	#[doc(hidden)]
 	private_key: jsonwebtoken::EncodingKey,
 	#[doc(hidden)]
 	// The access token is protected by a read–write lock. In this way, tasks can read the token
@@ -56,134 +398,1111 @@ pub struct Client
 	// writing in order to refresh it. Thanks to tokio’s implementation of read–write locks, writers
 	// take precedence over readers. This avoids starvations issues where many tasks recognize the
 	// access token as expired but none of them succeed in acquiring the write lock that would be
-	// necessary to refresh the access token because there are still more readers waiting
-	access_token: std::sync::Arc<tokio::sync::RwLock<AccessToken>>,
+	// necessary to refresh the access token because there are still more readers waiting. If
+	// configured, the access token is only requested with the first request
+	access_token: std::sync::Arc<tokio::sync::RwLock<Option<InstallationToken>>>,
+	#[doc(hidden)]
+	// Looked up once together with the first access token, as the GitHub App’s metadata doesn’t
+	// change while this service is running
+	app_metadata: std::sync::Arc<std::sync::OnceLock<crate::AppMetadata>>,
+	#[doc(hidden)]
+	// Set when access tokens repeatedly can’t be obtained
+	degradation: crate::Degradation,
+	#[doc(hidden)]
+	// Only set in staging environments for testing how failures are handled
+	fault_injector: Option<std::sync::Arc<crate::FaultInjector>>,
+	#[doc(hidden)]
+	// The public keys payloads are signed with by key identifier, which GitHub rotates rarely
+	public_keys_cache: crate::ExpiringCache<String, WebhookPublicKey>,
+	#[doc(hidden)]
+	// Key identifiers GitHub didn’t publish a supported key for when last looked up, so that
+	// payloads naming them don’t cause the keys to be looked up again and again
+	unknown_key_identifiers: crate::ExpiringCache<String, ()>,
+	#[doc(hidden)]
+	// When the public keys were last looked up, which is locked while looking them up, so that
+	// concurrent requests wait for a single lookup
+	public_keys_requested_at: std::sync::Arc<tokio::sync::Mutex<Option<std::time::Instant>>>,
+	#[doc(hidden)]
+	// Only set once the shared state is created, so that requests made before aren’t measured
+	metrics: Option<crate::Metrics>,
 }
 
-impl Client
-{
-	/// Initialize a new GitHub API client with a given configuration.
-	pub async fn from_config(config: Config) -> Result<Self, crate::Error>
+impl Client
+{
+	/// Initialize a new GitHub API client with a given configuration.
+	pub async fn from_config(config: Config) -> Result<Self, crate::Error>
+	{
+		let config = std::sync::Arc::new(config);
+
+		// Read and parse the GitHub App’s private key from the .pem file
+		let private_key = std::fs::read(&config.private_key_path)
+			.map_err(crate::Error::ReadPrivateGitHubAppKeyFile)?;
+		let private_key = jsonwebtoken::EncodingKey::from_rsa_pem(&private_key)
+			.map_err(crate::Error::ParsePrivateGitHubAppKeyFile)?;
+
+		// Initialize a new HTTP client
+		let mut reqwest_client_builder = reqwest::ClientBuilder::new()
+			// Set a recognizable user agent to get meaningful debugging information from GitHub
+			.user_agent(concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")))
+			.tcp_keepalive(config.tcp_keepalive_seconds.map(std::time::Duration::from_secs))
+			// Refuse connections with outdated TLS versions
+			.min_tls_version(config.min_tls_version.into());
+
+		if config.http2_prior_knowledge
+		{
+			reqwest_client_builder = reqwest_client_builder.http2_prior_knowledge();
+		}
+
+		let reqwest_client =
+			reqwest_client_builder.build().map_err(crate::Error::CreateHttpClient)?;
+
+		// Wrap the HTTP client in middleware that retries requests for up to 5 minutes in case of
+		// network failures
+		let retry_policy = reqwest_retry::policies::ExponentialBackoff::builder()
+			.backoff_exponent(2)
+			.retry_bounds(MINIMUM_RETRY_BACKOFF_DURATION, MAXIMUM_RETRY_BACKOFF_DURATION)
+			.build_with_total_retry_duration(TOTAL_RETRY_DURATION);
+		let retry_transient_middleware =
+			reqwest_retry::RetryTransientMiddleware::new_with_policy(retry_policy);
+
+		let non_retrying_reqwest_client =
+			reqwest_middleware::ClientBuilder::new(reqwest_client.clone()).build();
+		let reqwest_client = reqwest_middleware::ClientBuilder::new(reqwest_client)
+			.with(retry_transient_middleware)
+			.build();
+
+		// Request an initial access token from GitHub for this GitHub App and the organization it’s
+		// installed to unless this should be deferred to the first request
+		let access_token = match config.lazy_token
+		{
+			true =>
+			{
+				log::info!("deferring GitHub App installation access token request until the \
+					first GitHub API request");
+				None
+			},
+			false =>
+			{
+				log::info!("requesting GitHub App installation access token");
+				Some(InstallationToken::new(&config, &private_key, &reqwest_client).await?)
+			},
+		};
+		let access_token = std::sync::Arc::new(tokio::sync::RwLock::new(access_token));
+
+		let app_metadata = std::sync::Arc::new(std::sync::OnceLock::new());
+
+		if !config.lazy_token
+		{
+			let metadata = request_app_metadata(&config, &private_key, &reqwest_client).await?;
+			app_metadata.get_or_init(|| metadata);
+		}
+
+		Ok(Self
+		{
+			config,
+			reqwest_client,
+			non_retrying_reqwest_client,
+			private_key,
+			access_token,
+			app_metadata,
+			degradation: Default::default(),
+			fault_injector: None,
+			public_keys_cache: crate::ExpiringCache::new(PUBLIC_KEYS_CACHE_TIME_TO_LIVE),
+			unknown_key_identifiers: crate::ExpiringCache::new(PUBLIC_KEYS_REFRESH_INTERVAL),
+			public_keys_requested_at: Default::default(),
+			metrics: None,
+		})
+	}
+
+	/// Make a share of the requests of this client fail as configured, for testing retries and
+	/// failure alerts in staging environments. This must only be called if fault injection was
+	/// explicitly allowed.
+	///
+	/// # Arguments
+	/// - `config`: Which requests to fail and how.
+	pub fn with_fault_injection(mut self, config: crate::FaultInjectionConfig) -> Self
+	{
+		self.fault_injector = Some(std::sync::Arc::new(crate::FaultInjector::new(config)));
+		self
+	}
+
+	/// Record how long GitHub API requests take in the given metrics.
+	///
+	/// # Arguments
+	/// - `metrics`: A handle to the metrics.
+	pub fn with_metrics(mut self, metrics: crate::Metrics) -> Self
+	{
+		self.metrics = Some(metrics);
+		self
+	}
+
+	/// The slug of the organization this client is authenticated for.
+	pub fn organization(&self) -> &str
+	{
+		&self.config.organization
+	}
+
+	/// A handle to the flag indicating whether this service is degraded because access tokens
+	/// repeatedly can’t be obtained.
+	pub fn degradation(&self) -> &crate::Degradation
+	{
+		&self.degradation
+	}
+
+	/// The metadata of this GitHub App, including its slug, permissions, and subscribed events,
+	/// which is looked up once and then kept for the lifetime of this client.
+	pub async fn app_metadata(&self) -> Result<&crate::AppMetadata, crate::Error>
+	{
+		if let Some(app_metadata) = self.app_metadata.get()
+		{
+			return Ok(app_metadata);
+		}
+
+		let app_metadata =
+			request_app_metadata(&self.config, &self.private_key, &self.reqwest_client).await?;
+
+		// Another task may have looked up the metadata in the meantime, which is just as good
+		Ok(self.app_metadata.get_or_init(|| app_metadata))
+	}
+
+	/// The login of the bot user this GitHub App acts as (example: `branch-autoprotector[bot]`),
+	/// which appears as the sender of webhook events caused by this service. If the access token
+	/// is deferred until the first request, this is unknown until then.
+	pub fn app_bot_login(&self) -> Option<String>
+	{
+		self.app_metadata.get().map(crate::AppMetadata::bot_login)
+	}
+
+	/// Make an HTTP request to the GitHub API and decode the response from JSON. Empty responses
+	/// are decoded as if they were empty dictionaries.
+	///
+	/// # Arguments
+	/// - `method`: The HTTP method to use (example: [reqwest::Method::POST]).
+	/// - `endpoint`: The API endpoint (without host and leading slash, example:
+	///   `repos/example_organization`).
+	/// - `body`: A serializable type containing the request body.
+	pub async fn request<S, B, R>(&self, method: reqwest::Method, endpoint: S, body: Option<&B>)
+		-> Result<R, crate::Error>
+	where
+		S: AsRef<str>,
+		B: serde::Serialize,
+		R: serde::de::DeserializeOwned,
+	{
+		let endpoint = endpoint.as_ref();
+
+		self.with_deadline(endpoint, async
+		{
+			let response = self.request_raw_without_deadline(method, endpoint, body).await?;
+
+			decode_response(response).await
+		}).await
+	}
+
+	/// Make an HTTP request to the GitHub API and return the response as is, so that its status
+	/// code, headers, and body can be inspected by the caller (for arguments, see
+	/// [Client::request]). Retries and access token renewals are handled as for [Client::request].
+	/// Client errors are still returned as [crate::Error::ReceivedGitHubApiClientError], while
+	/// server errors are left to the caller.
+	#[allow(dead_code)]
+	pub async fn request_raw<S, B>(&self, method: reqwest::Method, endpoint: S, body: Option<&B>)
+		-> Result<reqwest::Response, crate::Error>
+	where
+		S: AsRef<str>,
+		B: serde::Serialize,
+	{
+		let endpoint = endpoint.as_ref();
+
+		self.with_deadline(endpoint,
+			self.request_raw_without_deadline(method, endpoint, body)).await
+	}
+
+	/// Enforce the configured request deadline on a request, if any.
+	///
+	/// # Arguments
+	/// - `endpoint`: The API endpoint the request is made to, for logging purposes.
+	/// - `request`: The future making the request.
+	#[doc(hidden)]
+	async fn with_deadline<F, T>(&self, endpoint: &str, request: F) -> Result<T, crate::Error>
+	where
+		F: std::future::Future<Output = Result<T, crate::Error>>,
+	{
+		let request_deadline = match self.config.request_deadline_seconds
+		{
+			Some(request_deadline_seconds) =>
+				std::time::Duration::from_secs(request_deadline_seconds),
+			None => return request.await,
+		};
+
+		// Bound the total duration of the request including all retries and token renewals
+		match tokio::time::timeout(request_deadline, request).await
+		{
+			Ok(result) => result,
+			Err(_) =>
+			{
+				log::warn!("GitHub API request to “{endpoint}” exceeded the deadline of {} seconds",
+					request_deadline.as_secs());
+				Err(crate::Error::DeadlineExceeded{deadline: request_deadline})
+			},
+		}
+	}
+
+	/// Make an HTTP request to the GitHub API without enforcing the configured request deadline
+	/// (for arguments, see [Client::request_raw]). The request is traced as a span including the
+	/// response’s status code, with retries and access token renewals counted toward its latency.
+	#[doc(hidden)]
+	async fn request_raw_without_deadline<B>(&self, method: reqwest::Method, endpoint: &str,
+		body: Option<&B>)
+		-> Result<reqwest::Response, crate::Error>
+	where
+		B: serde::Serialize,
+	{
+		use tracing::Instrument as _;
+
+		let span = tracing::info_span!("github_api_request",
+			http.method = method.as_str(),
+			endpoint,
+			http.status_code = tracing::field::Empty,
+			otel.status_code = tracing::field::Empty);
+
+		let started_at = std::time::Instant::now();
+
+		let result = self.request_raw_with_access_token(method, endpoint, body)
+			.instrument(span.clone())
+			.await;
+
+		if let Some(metrics) = &self.metrics
+		{
+			metrics.record_github_api_request_duration(started_at.elapsed());
+		}
+
+		match &result
+		{
+			Ok(response) =>
+			{
+				span.record("http.status_code", response.status().as_u16());
+			},
+			Err(error) =>
+			{
+				if let crate::Error::ReceivedGitHubApiClientError{status_code, ..} = error
+				{
+					span.record("http.status_code", status_code.as_u16());
+				}
+
+				span.record("otel.status_code", "ERROR");
+			},
+		}
+
+		result
+	}
+
+	/// Make an HTTP request to the GitHub API with the current access token, renewing it if it
+	/// expired (for arguments, see [Client::request_raw]).
+	#[doc(hidden)]
+	async fn request_raw_with_access_token<B>(&self, method: reqwest::Method, endpoint: &str,
+		body: Option<&B>)
+		-> Result<reqwest::Response, crate::Error>
+	where
+		B: serde::Serialize,
+	{
+		// Copy the access token by value, as we might need to check whether its value changed if we
+		// need to make another attempt because of an expired access token
+		let access_token = (*self.access_token.read().await).clone();
+		let mut access_token = match access_token
+		{
+			Some(access_token) => access_token,
+			None => self.request_initial_access_token().await?,
+		};
+
+		// If the access token was deferred, the metadata is looked up along with it, which is
+		// retried with later requests until it succeeds
+		if self.config.lazy_token && self.app_metadata.get().is_none()
+		{
+			// Unlike at startup, failing to look up the metadata shouldn’t fail the request at hand
+			if let Err(error) = self.app_metadata().await
+			{
+				log::warn!("could not look up the metadata of this GitHub App, so events caused by \
+					this GitHub App itself can’t be recognized yet: {error}");
+			}
+		}
+
+		let mut authentication_error_retries = 0;
+
+		loop
+		{
+			// Try making the GitHub API request with the provided access token
+			match self.request_raw_with_retries(method.clone(), endpoint, body, &access_token).await
+			{
+				// If the request failed with a 401 Unauthorized status code, check if the access
+				// token has expired and retry with a fresh one
+				Err(crate::Error::ReceivedGitHubApiClientError{status_code, ..})
+					if status_code == reqwest::StatusCode::UNAUTHORIZED
+						&& authentication_error_retries
+							< self.config.retry_limits.authentication_errors =>
+				{
+					let mut access_token_locked = self.access_token.write().await;
+
+					// The access token might already have been refreshed in another task since the
+					// previous attempt for this request was made in this task. Only refresh it if
+					// it wasn’t done yet
+					if access_token_locked.as_ref() == Some(&access_token)
+					{
+						log::info!("GitHub App installation access token has possibly expired, \
+							requesting a fresh one");
+
+						let fresh_access_token = self.request_access_token().await?;
+						*access_token_locked = Some(fresh_access_token.clone());
+						access_token = fresh_access_token;
+					}
+
+					// Drop the lock on the access token so other tasks can make requests again,
+					// then retry the request with the refreshed access token
+					authentication_error_retries += 1;
+				},
+				// If the request succeeded or failed with for a different reason than a possibly
+				// expired access token, return the result as is
+				result => return result,
+			}
+		}
+	}
+
+	/// Make an HTTP request to the GitHub API with a given access token, retrying it after network
+	/// issues and server errors as configured (for the other arguments, see
+	/// [Client::request_raw]).
+	///
+	/// Unless limits are configured for either class of errors, retries are left to the middleware
+	/// of the HTTP client. Otherwise, the requests are retried here with the same backoff, and a
+	/// class of errors without a configured limit is retried for up to five minutes in total.
+	///
+	/// # Arguments
+	/// - `access_token`: The access token to authenticate the request with.
+	#[doc(hidden)]
+	async fn request_raw_with_retries<B>(&self, method: reqwest::Method, endpoint: &str,
+		body: Option<&B>, access_token: &InstallationToken)
+		-> Result<reqwest::Response, crate::Error>
+	where
+		B: serde::Serialize,
+	{
+		let retry_limits = &self.config.retry_limits;
+
+		// Non-idempotent requests are only retried if configured, as retrying a request whose
+		// response got lost after GitHub processed it would have unintended side effects
+		let is_idempotent = !matches!(method, reqwest::Method::POST | reqwest::Method::PATCH);
+
+		if !is_idempotent && !self.config.retry_non_idempotent_requests
+		{
+			return self.request_raw_or_inject_fault(&self.non_retrying_reqwest_client, method,
+				endpoint, body, access_token).await;
+		}
+
+		// Injected faults bypass the middleware, so they need to be retried here
+		if retry_limits.network_errors.is_none() && retry_limits.server_errors.is_none()
+			&& self.fault_injector.is_none()
+		{
+			return request_raw(&self.config, &self.reqwest_client, method, endpoint, body,
+				access_token).await;
+		}
+
+		let started_at = std::time::Instant::now();
+		let mut backoff = MINIMUM_RETRY_BACKOFF_DURATION;
+		let mut network_error_retries = 0;
+		let mut server_error_retries = 0;
+
+		loop
+		{
+			let result = self.request_raw_or_inject_fault(&self.non_retrying_reqwest_client,
+				method.clone(), endpoint, body, access_token).await;
+
+			let (error_class, retries, retry_limit) = match &result
+			{
+				// Requests GitHub timed out are retried like those that timed out locally
+				Err(error) if error.network_error_kind().is_some()
+					|| error.is_client_error_with_status(reqwest::StatusCode::REQUEST_TIMEOUT) =>
+					("network error", &mut network_error_retries, retry_limits.network_errors),
+				Ok(response) if response.status().is_server_error() =>
+					("server error", &mut server_error_retries, retry_limits.server_errors),
+				_ => return result,
+			};
+
+			let may_retry = match retry_limit
+			{
+				Some(retry_limit) => *retries < retry_limit,
+				None => started_at.elapsed() + backoff <= TOTAL_RETRY_DURATION,
+			};
+
+			if !may_retry
+			{
+				return result;
+			}
+
+			log::warn!("GitHub API request to “{endpoint}” failed with a {error_class}, waiting {} \
+				seconds before retrying", backoff.as_secs());
+
+			tokio::time::sleep(backoff).await;
+			*retries += 1;
+			backoff = std::cmp::min(backoff * 2, MAXIMUM_RETRY_BACKOFF_DURATION);
+		}
+	}
+
+	/// Make an HTTP request to the GitHub API with a given HTTP client and access token unless
+	/// fault injection is enabled and decides to fail it (for the other arguments, see
+	/// [Client::request_raw_with_retries]).
+	///
+	/// # Arguments
+	/// - `reqwest_client`: The HTTP client to make the request with.
+	#[doc(hidden)]
+	async fn request_raw_or_inject_fault<B>(&self,
+		reqwest_client: &reqwest_middleware::ClientWithMiddleware, method: reqwest::Method,
+		endpoint: &str, body: Option<&B>, access_token: &InstallationToken)
+		-> Result<reqwest::Response, crate::Error>
+	where
+		B: serde::Serialize,
+	{
+		if let Some(fault_injector) = &self.fault_injector
+		{
+			let url = self.config.base_url.join(endpoint).map_err(crate::Error::ParseUrl)?;
+
+			if let Some(result) = fault_injector.inject(&url)
+			{
+				return result;
+			}
+		}
+
+		request_raw(&self.config, reqwest_client, method, endpoint, body, access_token).await
+	}
+
+	/// Request the first access token if it was deferred until the first request, unless another
+	/// task did so already in the meantime.
+	#[doc(hidden)]
+	async fn request_initial_access_token(&self) -> Result<InstallationToken, crate::Error>
+	{
+		let mut access_token_locked = self.access_token.write().await;
+
+		if let Some(access_token) = &*access_token_locked
+		{
+			return Ok(access_token.clone());
+		}
+
+		log::info!("requesting deferred GitHub App installation access token");
+
+		let access_token = self.request_access_token().await?;
+		*access_token_locked = Some(access_token.clone());
+
+		Ok(access_token)
+	}
+
+	/// Request a fresh access token, recording whether this succeeded for the degradation flag.
+	#[doc(hidden)]
+	async fn request_access_token(&self) -> Result<InstallationToken, crate::Error>
+	{
+		let result =
+			InstallationToken::new(&self.config, &self.private_key, &self.reqwest_client).await;
+
+		match &result
+		{
+			Ok(_) => self.degradation.record_token_success(),
+			Err(error) => self.degradation.record_token_failure(error),
+		}
+
+		result
+	}
+
+	/// Make an HTTP DELETE request to the GitHub API (for arguments, see [Client::request]).
+	#[allow(dead_code)]
+	pub async fn delete<S, R>(&self, endpoint: S) -> Result<R, crate::Error>
+	where
+		S: AsRef<str>,
+		R: serde::de::DeserializeOwned,
+	{
+		self.request(reqwest::Method::DELETE, endpoint, NO_BODY).await
+	}
+
+	/// Make an HTTP GET request to the GitHub API (for arguments, see [Client::request]).
+	#[allow(dead_code)]
+	pub async fn get<S, R>(&self, endpoint: S) -> Result<R, crate::Error>
+	where
+		S: AsRef<str>,
+		R: serde::de::DeserializeOwned,
+	{
+		self.request(reqwest::Method::GET, endpoint, NO_BODY).await
+	}
+
+	/// Make HTTP GET requests to a paginated GitHub API endpoint and collect the items of all pages
+	/// (for arguments, see [Client::request]). The endpoint may already contain query parameters.
+	pub async fn get_paginated<S, R>(&self, endpoint: S) -> Result<Vec<R>, crate::Error>
+	where
+		S: AsRef<str>,
+		R: serde::de::DeserializeOwned,
+	{
+		let endpoint = endpoint.as_ref();
+		let separator = if endpoint.contains('?') {'&'} else {'?'};
+
+		let mut items = vec![];
+
+		for page in 1..
+		{
+			let page_items: Vec<R> =
+				self.get(format!("{endpoint}{separator}per_page={PAGE_SIZE}&page={page}")).await?;
+
+			// A page that isn’t full is the last one
+			let is_last_page = page_items.len() < PAGE_SIZE;
+
+			items.extend(page_items);
+
+			if is_last_page
+			{
+				break;
+			}
+		}
+
+		Ok(items)
+	}
+
+	/// Retrieve the branch protection settings of a branch, returning `None` if the branch isn’t
+	/// protected.
+	///
+	/// # Arguments
+	/// - `organization_name`: The organization owning the repository.
+	/// - `repository_name`: The name of the repository.
+	/// - `branch_name`: The name of the branch.
+	pub async fn get_branch_protection(&self, organization_name: &str, repository_name: &str,
+		branch_name: &str)
+		-> Result<Option<crate::BranchProtectionResponse>, crate::Error>
+	{
+		match self.get(branch_protection_endpoint(organization_name, repository_name, branch_name))
+			.await
+		{
+			Ok(branch_protection) => Ok(Some(branch_protection)),
+			// GitHub responds with 404 Not Found for branches that aren’t protected
+			Err(error) if error.is_client_error_with_status(reqwest::StatusCode::NOT_FOUND) =>
+				Ok(None),
+			Err(error) => Err(error),
+		}
+	}
+
+	/// Protect a branch with the given settings, replacing any existing branch protection
+	/// settings.
+	///
+	/// # Arguments
+	/// - `organization_name`: The organization owning the repository.
+	/// - `repository_name`: The name of the repository.
+	/// - `branch_name`: The name of the branch.
+	/// - `request`: The branch protection settings to apply.
+	pub async fn protect_branch(&self, organization_name: &str, repository_name: &str,
+		branch_name: &str, request: &crate::ProtectBranchRequest)
+		-> Result<(), crate::Error>
+	{
+		self.put::<_, _, crate::IgnoreResponse>(
+			branch_protection_endpoint(organization_name, repository_name, branch_name), request)
+			.await?;
+
+		Ok(())
+	}
+
+	/// Create an issue in a repository.
+	///
+	/// # Arguments
+	/// - `organization_name`: The organization owning the repository.
+	/// - `repository_name`: The name of the repository.
+	/// - `request`: The title and body of the issue.
+	pub async fn create_issue(&self, organization_name: &str, repository_name: &str,
+		request: &crate::CreateIssueRequest<'_>)
+		-> Result<crate::CreateIssueResponse, crate::Error>
+	{
+		self.post(format!("{}/issues", repository_endpoint(organization_name, repository_name)),
+			request).await
+	}
+
+	/// Comment on a commit, which requires the `contents:write` permission.
+	///
+	/// # Arguments
+	/// - `organization_name`: The organization owning the repository.
+	/// - `repository_name`: The name of the repository.
+	/// - `commit_sha`: The SHA of the commit to comment on.
+	/// - `request`: The body of the comment.
+	pub async fn create_commit_comment(&self, organization_name: &str, repository_name: &str,
+		commit_sha: &str, request: &crate::CreateCommitCommentRequest<'_>)
+		-> Result<crate::CreateCommitCommentResponse, crate::Error>
+	{
+		self.post(format!("{}/commits/{}/comments",
+			repository_endpoint(organization_name, repository_name),
+			encode_path_segment(commit_sha)), request).await
+	}
+
+	/// Replace the body of an existing issue or change its state.
+	///
+	/// # Arguments
+	/// - `organization_name`: The organization owning the repository.
+	/// - `repository_name`: The name of the repository.
+	/// - `issue_number`: The number of the issue.
+	/// - `request`: The new body or state of the issue.
+	pub async fn update_issue(&self, organization_name: &str, repository_name: &str,
+		issue_number: u64, request: &crate::UpdateIssueRequest<'_>)
+		-> Result<crate::Issue, crate::Error>
+	{
+		self.patch(format!("{}/issues/{issue_number}",
+			repository_endpoint(organization_name, repository_name)), request).await
+	}
+
+	/// Search issues and pull requests across repositories, returning the first page of results
+	/// (up to 100 items) in the order of relevance.
+	///
+	/// # Arguments
+	/// - `query`: The search query in GitHub’s search syntax (example:
+	///   `repo:example-organization/example-repository is:issue is:open`).
+	pub async fn search_issues(&self, query: &str) -> Result<Vec<crate::Issue>, crate::Error>
+	{
+		let response: crate::SearchIssuesResponse = self.get(format!(
+			"search/issues?q={}&per_page={PAGE_SIZE}", encode_path_segment(query))).await?;
+
+		Ok(response.items)
+	}
+
+	/// Check whether a branch is the head of an open pull request in its repository.
+	///
+	/// # Arguments
+	/// - `organization_name`: The organization owning the repository.
+	/// - `repository_name`: The name of the repository.
+	/// - `branch_name`: The name of the branch.
+	pub async fn has_open_pull_request(&self, organization_name: &str, repository_name: &str,
+		branch_name: &str)
+		-> Result<bool, crate::Error>
+	{
+		let pull_requests: Vec<crate::PullRequest> = self.get(format!(
+			"{}/pulls?state=open&head={}&per_page=1",
+			repository_endpoint(organization_name, repository_name),
+			encode_path_segment(&format!("{organization_name}:{branch_name}")))).await?;
+
+		Ok(!pull_requests.is_empty())
+	}
+
+	/// Retrieve a branch, including the commit it points to.
+	///
+	/// # Arguments
+	/// - `organization_name`: The organization owning the repository.
+	/// - `repository_name`: The name of the repository.
+	/// - `branch_name`: The name of the branch.
+	pub async fn get_branch(&self, organization_name: &str, repository_name: &str,
+		branch_name: &str)
+		-> Result<crate::Branch, crate::Error>
+	{
+		self.get(format!("{}/branches/{}", repository_endpoint(organization_name, repository_name),
+			encode_path_segment(branch_name))).await
+	}
+
+	/// Create a completed check run for a commit, which requires the `checks:write` permission.
+	///
+	/// # Arguments
+	/// - `organization_name`: The organization owning the repository.
+	/// - `repository_name`: The name of the repository.
+	/// - `head_sha`: The SHA of the commit to report the check for.
+	/// - `conclusion`: The conclusion of the check run (example: `success`).
+	/// - `summary`: The summary of the check run in Markdown format.
+	pub async fn create_check_run(&self, organization_name: &str, repository_name: &str,
+		head_sha: &str, conclusion: &str, summary: &str)
+		-> Result<crate::CreateCheckRunResponse, crate::Error>
+	{
+		let request = crate::CreateCheckRunRequest
+		{
+			name: CHECK_RUN_NAME,
+			head_sha,
+			status: "completed",
+			conclusion,
+			output: crate::CheckRunOutput
+			{
+				title: CHECK_RUN_NAME,
+				summary,
+			},
+		};
+
+		self.post(format!("{}/check-runs", repository_endpoint(organization_name,
+			repository_name)), &request).await
+	}
+
+	/// Create a discussion in a repository via the GraphQL API, as there’s no stable REST API
+	/// endpoint for this. This requires the `discussions` permission.
+	///
+	/// # Arguments
+	/// - `organization_name`: The organization owning the repository.
+	/// - `repository_name`: The name of the repository.
+	/// - `category_name`: The name of the discussion category to create the discussion in.
+	/// - `title`: The title of the discussion.
+	/// - `body`: The contents of the discussion in Markdown format.
+	pub async fn create_discussion(&self, organization_name: &str, repository_name: &str,
+		category_name: &str, title: &str, body: &str)
+		-> Result<crate::Discussion, crate::Error>
+	{
+		let response: DiscussionRepositoryQueryData = self.graphql(DISCUSSION_REPOSITORY_QUERY,
+			&serde_json::json!({"owner": organization_name, "name": repository_name})).await?;
+		let repository = response.repository;
+
+		if !repository.has_discussions_enabled
+		{
+			return Err(crate::Error::DiscussionsDisabled{repository: repository_name.to_owned()});
+		}
+
+		let category = repository.discussion_categories.nodes.into_iter()
+			.find(|category| category.name == category_name)
+			.ok_or_else(|| crate::Error::DiscussionCategoryNotFound
+			{
+				repository: repository_name.to_owned(),
+				category: category_name.to_owned(),
+			})?;
+
+		let response: CreateDiscussionMutationData = self.graphql(CREATE_DISCUSSION_MUTATION,
+			&serde_json::json!(
+			{
+				"repositoryId": repository.id,
+				"categoryId": category.id,
+				"title": title,
+				"body": body,
+			})).await?;
+
+		Ok(response.create_discussion.discussion)
+	}
+
+	/// Pin an issue to the top of the issue list of its repository via the GraphQL API, which fails
+	/// if the repository already has the maximum number of pinned issues.
+	///
+	/// # Arguments
+	/// - `issue_node_id`: The global node ID of the issue.
+	pub async fn pin_issue(&self, issue_node_id: &str) -> Result<(), crate::Error>
+	{
+		self.graphql::<_, crate::IgnoreResponse>(PIN_ISSUE_MUTATION,
+			&serde_json::json!({"issueId": issue_node_id})).await?;
+
+		Ok(())
+	}
+
+	/// Retrieve a repository, including the name of its default branch.
+	///
+	/// # Arguments
+	/// - `organization_name`: The organization owning the repository.
+	/// - `repository_name`: The name of the repository.
+	pub async fn get_repository(&self, organization_name: &str, repository_name: &str)
+		-> Result<crate::Repository, crate::Error>
+	{
+		self.get(repository_endpoint(organization_name, repository_name)).await
+	}
+
+	/// List all branches of a repository, including whether they’re protected.
+	///
+	/// # Arguments
+	/// - `organization_name`: The organization owning the repository.
+	/// - `repository_name`: The name of the repository.
+	pub async fn list_branches(&self, organization_name: &str, repository_name: &str)
+		-> Result<Vec<crate::Branch>, crate::Error>
+	{
+		self.get_paginated(format!("{}/branches",
+			repository_endpoint(organization_name, repository_name))).await
+	}
+
+	/// List the names of all protected branches of a repository.
+	///
+	/// # Arguments
+	/// - `organization_name`: The organization owning the repository.
+	/// - `repository_name`: The name of the repository.
+	#[allow(dead_code)]
+	pub async fn list_protected_branches(&self, organization_name: &str, repository_name: &str)
+		-> Result<Vec<String>, crate::Error>
+	{
+		let branches: Vec<crate::Branch> = self.get_paginated(format!("{}/branches?protected=true",
+			repository_endpoint(organization_name, repository_name))).await?;
+
+		Ok(branches.into_iter().map(|branch| branch.name).collect())
+	}
+
+	/// Check whether a file or directory exists in the default branch of a repository.
+	///
+	/// # Arguments
+	/// - `organization_name`: The organization owning the repository.
+	/// - `repository_name`: The name of the repository.
+	/// - `path`: The path of the file relative to the root of the repository (example:
+	///   `.github/no-auto-protect`).
+	pub async fn file_exists(&self, organization_name: &str, repository_name: &str, path: &str)
+		-> Result<bool, crate::Error>
 	{
-		let config = std::sync::Arc::new(config);
+		let path = path.trim_matches('/').split('/').map(encode_path_segment)
+			.collect::<Vec<_>>().join("/");
 
-		// Read and parse the GitHub App’s private key from the .pem file
-		let private_key = std::fs::read(&config.private_key_path)
-			.map_err(crate::Error::ReadPrivateGitHubAppKeyFile)?;
-		let private_key = jsonwebtoken::EncodingKey::from_rsa_pem(&private_key)
-			.map_err(crate::Error::ParsePrivateGitHubAppKeyFile)?;
+		// Directories are listed as arrays, so accept any JSON value
+		let result = self.get::<_, serde_json::Value>(format!("{}/contents/{path}",
+			repository_endpoint(organization_name, repository_name))).await;
 
-		// Initialize a new HTTP client
-		let reqwest_client = reqwest::ClientBuilder::new()
-			// Set a recognizable user agent to get meaningful debugging information from GitHub
-			.user_agent(concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")))
-			.build().map_err(crate::Error::CreateHttpClient)?;
+		match result
+		{
+			Ok(_) => Ok(true),
+			// Missing files are the normal case rather than an error
+			Err(error) if error.is_client_error_with_status(reqwest::StatusCode::NOT_FOUND) =>
+				Ok(false),
+			Err(error) => Err(error),
+		}
+	}
 
-		// Wrap the HTTP client in middleware that retries requests for up to 5 minutes in case of
-		// network failures
-		let retry_policy = reqwest_retry::policies::ExponentialBackoff::builder()
-			.backoff_exponent(2)
-			.retry_bounds(std::time::Duration::from_secs(1), std::time::Duration::from_secs(60))
-			.build_with_total_retry_duration(std::time::Duration::from_secs(5 * 60));
-		let retry_transient_middleware =
-			reqwest_retry::RetryTransientMiddleware::new_with_policy(retry_policy);
+	/// Retrieve the contents of a file in the default branch of a repository.
+	///
+	/// # Arguments
+	/// - `organization_name`: The organization owning the repository.
+	/// - `repository_name`: The name of the repository.
+	/// - `path`: The path of the file relative to the root of the repository (example:
+	///   `branch-protection.yaml`).
+	pub async fn get_file_contents(&self, organization_name: &str, repository_name: &str,
+		path: &str)
+		-> Result<Vec<u8>, crate::Error>
+	{
+		use base64::Engine as _;
 
-		let reqwest_client = reqwest_middleware::ClientBuilder::new(reqwest_client)
-			.with(retry_transient_middleware)
-			.build();
+		let path = path.trim_matches('/').split('/').map(encode_path_segment)
+			.collect::<Vec<_>>().join("/");
 
-		// Request an initial access token from GitHub for this GitHub App and the organization it’s
-		// installed to
-		log::info!("requesting GitHub App installation access token");
-		let access_token = AccessToken::new(&config, &private_key, &reqwest_client).await?;
-		let access_token = std::sync::Arc::new(tokio::sync::RwLock::new(access_token));
+		let response: crate::FileContentsResponse = self.get(format!("{}/contents/{path}",
+			repository_endpoint(organization_name, repository_name))).await?;
 
-		Ok(Self
+		// Larger files aren’t included in the response, which is reported as an empty encoding
+		if response.encoding != "base64"
 		{
-			config,
-			reqwest_client,
-			private_key,
-			access_token,
-		})
+			return Err(crate::Error::UnsupportedFileEncoding{encoding: response.encoding});
+		}
+
+		// The contents are wrapped into lines, which the decoder doesn’t accept
+		let content: String =
+			response.content.chars().filter(|character| !character.is_ascii_whitespace()).collect();
+
+		base64::engine::general_purpose::STANDARD.decode(content)
+			.map_err(crate::Error::DecodeFileContents)
 	}
 
-	/// Make an HTTP request to the GitHub API.
+	/// Retrieve the SHA of the blob of a file in a branch, returning `None` if the file doesn’t
+	/// exist in the branch.
 	///
 	/// # Arguments
-	/// - `method`: The HTTP method to use (example: [reqwest::Method::POST]).
-	/// - `endpoint`: The API endpoint (without host and leading slash, example:
-	///   `repos/example_organization`).
-	/// - `body`: A serializable type containing the request body.
-	pub async fn request<S, B, R>(&self, method: reqwest::Method, endpoint: S, body: Option<&B>)
-		-> Result<R, crate::Error>
-	where
-		S: AsRef<str>,
-		B: serde::Serialize,
-		R: serde::de::DeserializeOwned,
+	/// - `organization_name`: The organization owning the repository.
+	/// - `repository_name`: The name of the repository.
+	/// - `path`: The path of the file relative to the root of the repository.
+	/// - `branch_name`: The name of the branch.
+	pub async fn get_file_sha(&self, organization_name: &str, repository_name: &str, path: &str,
+		branch_name: &str)
+		-> Result<Option<String>, crate::Error>
 	{
-		let endpoint = endpoint.as_ref();
+		let path = path.trim_matches('/').split('/').map(encode_path_segment)
+			.collect::<Vec<_>>().join("/");
 
-		// Copy the access token by value, as we might need to check whether its value changed if we
-		// need to make a second attempt because of an expired access token
-		let mut access_token = (*self.access_token.read().await).clone();
+		let result = self.get::<_, crate::FileContentsResponse>(format!("{}/contents/{path}?ref={}",
+			repository_endpoint(organization_name, repository_name),
+			encode_path_segment(branch_name))).await;
 
-		// Try making the GitHub API request with the provided access token
-		match request(&self.config, &self.reqwest_client, method.clone(), endpoint, body,
-			&access_token).await
+		match result
 		{
-			// If the request failed with a 401 Unauthorized status code, check if the access token
-			// has expired and retry with a fresh one
-			Err(crate::Error::ReceivedGitHubApiClientError{status_code, ..})
-				if status_code == reqwest::StatusCode::UNAUTHORIZED =>
+			Ok(response) => Ok(Some(response.sha)),
+			Err(error) if error.is_client_error_with_status(reqwest::StatusCode::NOT_FOUND) =>
+				Ok(None),
+			Err(error) => Err(error),
+		}
+	}
+
+	/// Create or update a file in a branch by committing it, which requires the `contents:write`
+	/// permission.
+	///
+	/// # Arguments
+	/// - `organization_name`: The organization owning the repository.
+	/// - `repository_name`: The name of the repository.
+	/// - `path`: The path of the file relative to the root of the repository.
+	/// - `message`: The commit message.
+	/// - `content`: The new contents of the file.
+	/// - `branch_name`: The name of the branch to commit to.
+	/// - `existing_sha`: The SHA of the blob of the file if it exists already (see
+	///   [Self::get_file_sha]).
+	#[allow(clippy::too_many_arguments)]
+	pub async fn put_file_contents(&self, organization_name: &str, repository_name: &str,
+		path: &str, message: &str, content: &[u8], branch_name: &str, existing_sha: Option<&str>)
+		-> Result<(), crate::Error>
+	{
+		use base64::Engine as _;
+
+		let path = path.trim_matches('/').split('/').map(encode_path_segment)
+			.collect::<Vec<_>>().join("/");
+
+		let request = crate::PutFileContentsRequest
+		{
+			message,
+			content: base64::engine::general_purpose::STANDARD.encode(content),
+			branch: branch_name,
+			sha: existing_sha,
+		};
+
+		self.put::<_, _, crate::IgnoreResponse>(format!("{}/contents/{path}",
+			repository_endpoint(organization_name, repository_name)), &request).await?;
+
+		Ok(())
+	}
+
+	/// Create a branch pointing to a commit, which requires the `contents:write` permission.
+	/// GitHub responds with 422 Unprocessable Entity if the branch exists already.
+	///
+	/// # Arguments
+	/// - `organization_name`: The organization owning the repository.
+	/// - `repository_name`: The name of the repository.
+	/// - `branch_name`: The name of the branch to create.
+	/// - `commit_sha`: The SHA of the commit the branch points to.
+	pub async fn create_branch(&self, organization_name: &str, repository_name: &str,
+		branch_name: &str, commit_sha: &str)
+		-> Result<(), crate::Error>
+	{
+		let request = crate::CreateReferenceRequest
+		{
+			ref_: &format!("refs/heads/{branch_name}"),
+			sha: commit_sha,
+		};
+
+		self.post::<_, _, crate::IgnoreResponse>(format!("{}/git/refs",
+			repository_endpoint(organization_name, repository_name)), &request).await?;
+
+		Ok(())
+	}
+
+	/// Open a pull request, which requires the `pull_requests:write` permission. GitHub responds
+	/// with 422 Unprocessable Entity if a pull request from the same branch is open already.
+	///
+	/// # Arguments
+	/// - `organization_name`: The organization owning the repository.
+	/// - `repository_name`: The name of the repository.
+	/// - `request`: The title, description, and branches of the pull request.
+	pub async fn create_pull_request(&self, organization_name: &str, repository_name: &str,
+		request: &crate::CreatePullRequestRequest<'_>)
+		-> Result<crate::CreatePullRequestResponse, crate::Error>
+	{
+		self.post(format!("{}/pulls", repository_endpoint(organization_name, repository_name)),
+			request).await
+	}
+
+	/// Look up a public key GitHub signs webhook payloads with by its identifier, returning `None`
+	/// if GitHub doesn’t publish a supported key with this identifier. Keys are cached, and unknown
+	/// identifiers cause all keys to be looked up again, so that rotated keys are picked up. As
+	/// anyone can send payloads naming unknown identifiers, the keys are looked up at most once per
+	/// [PUBLIC_KEYS_REFRESH_INTERVAL], and identifiers still unknown afterward are remembered as
+	/// such for as long.
+	///
+	/// # Arguments
+	/// - `key_identifier`: The identifier of the public key.
+	#[doc(hidden)]
+	async fn webhook_public_key(&self, key_identifier: &str)
+		-> Result<Option<WebhookPublicKey>, crate::Error>
+	{
+		let key_identifier = key_identifier.to_owned();
+
+		if let Some(public_key) = self.public_keys_cache.get(&key_identifier)
+		{
+			return Ok(Some(public_key));
+		}
+
+		if self.unknown_key_identifiers.get(&key_identifier).is_some()
+		{
+			return Ok(None);
+		}
+
+		let mut public_keys_requested_at = self.public_keys_requested_at.lock().await;
+
+		// Another request may have looked up the keys while waiting for the lock
+		if let Some(public_key) = self.public_keys_cache.get(&key_identifier)
+		{
+			return Ok(Some(public_key));
+		}
+
+		if public_keys_requested_at.is_some_and(|requested_at|
+			requested_at.elapsed() < PUBLIC_KEYS_REFRESH_INTERVAL)
+		{
+			log::warn!("not looking up public keys for unknown key identifier “{key_identifier}”, \
+				as they were looked up recently");
+			return Ok(None);
+		}
+
+		// Failed lookups count, too, so that an unavailable endpoint isn’t hammered
+		*public_keys_requested_at = Some(std::time::Instant::now());
+
+		let response: crate::PublicKeysResponse = self.get(&self.config.public_keys_endpoint)
+			.await
+			.map_err(|error| crate::Error::ObtainPublicKeys(Box::new(error)))?;
+
+		let mut requested_public_key = None;
+
+		for public_key in response.public_keys
+		{
+			let parsed_public_key = match WebhookPublicKey::from_pem(&public_key.key)
 			{
+				Some(parsed_public_key) => parsed_public_key,
+				None =>
 				{
-					let mut access_token_locked = self.access_token.write().await;
+					log::warn!("ignoring public key “{}” of unsupported type",
+						public_key.key_identifier);
+					continue;
+				},
+			};
 
-					// The access token might already have been refreshed in another task since the
-					// first attempt for this request was made in this task. Only refresh it if it
-					// wasn’t done yet
-					if *access_token_locked == access_token
-					{
-						log::info!("GitHub App installation access token has possibly expired, \
-							requesting a fresh one");
-
-						*access_token_locked =
-							AccessToken::new(&self.config, &self.private_key, &self.reqwest_client)
-								.await?;
-						access_token = access_token_locked.clone();
-					}
+			if public_key.key_identifier == key_identifier
+			{
+				requested_public_key = Some(parsed_public_key.clone());
+			}
 
-					// Drop the lock on the access token so other tasks can make requests again
-				}
+			self.public_keys_cache.insert(public_key.key_identifier, parsed_public_key);
+		}
 
-				// Retry the request with the refreshed access token
-				request(&self.config, &self.reqwest_client, method, endpoint, body, &access_token)
-					.await
-			},
-			// If the request succeeded or failed with for a different reason than a possibly
-			// expired access token, return the result as is
-			result => result,
+		if requested_public_key.is_none()
+		{
+			self.unknown_key_identifiers.insert(key_identifier, ());
 		}
+
+		Ok(requested_public_key)
 	}
 
-	/// Make an HTTP DELETE request to the GitHub API (for arguments, see [Client::request]).
-	#[allow(dead_code)]
-	pub async fn delete<S, R>(&self, endpoint: S) -> Result<R, crate::Error>
-	where
-		S: AsRef<str>,
-		R: serde::de::DeserializeOwned,
+	/// Retrieve the topics a repository is tagged with.
+	///
+	/// # Arguments
+	/// - `organization_name`: The organization owning the repository.
+	/// - `repository_name`: The name of the repository.
+	pub async fn get_repository_topics(&self, organization_name: &str, repository_name: &str)
+		-> Result<crate::RepositoryTopicsResponse, crate::Error>
 	{
-		self.request(reqwest::Method::DELETE, endpoint, NO_BODY).await
+		self.get(format!("{}/topics", repository_endpoint(organization_name, repository_name)))
+			.await
 	}
 
-	/// Make an HTTP GET request to the GitHub API (for arguments, see [Client::request]).
-	#[allow(dead_code)]
-	pub async fn get<S, R>(&self, endpoint: S) -> Result<R, crate::Error>
+	/// Retrieve the current rate limit status of the GitHub App installation, which doesn’t count
+	/// against the rate limit itself.
+	pub async fn get_rate_limit(&self) -> Result<crate::RateLimit, crate::Error>
+	{
+		let response: crate::RateLimitResponse = self.get("rate_limit").await?;
+
+		Ok(response.resources)
+	}
+
+	/// Make a request to the GitHub GraphQL API and decode the data of the response, failing if
+	/// the response reports any errors.
+	///
+	/// # Arguments
+	/// - `query`: The GraphQL query or mutation.
+	/// - `variables`: A serializable type containing the variables referenced in the query.
+	pub async fn graphql<V, R>(&self, query: &str, variables: &V) -> Result<R, crate::Error>
 	where
-		S: AsRef<str>,
+		V: serde::Serialize,
 		R: serde::de::DeserializeOwned,
 	{
-		self.request(reqwest::Method::GET, endpoint, NO_BODY).await
+		// On GitHub Enterprise Server, the GraphQL API isn’t located below the REST API
+		let endpoint = match self.config.base_url.path().ends_with("/api/v3/")
+		{
+			true => "../graphql",
+			false => "graphql",
+		};
+
+		let response: GraphQlResponse<R> =
+			self.post(endpoint, &GraphQlRequest{query, variables}).await?;
+
+		if !response.errors.is_empty()
+		{
+			let messages: Vec<_> = response.errors.into_iter().map(|error| error.message).collect();
+			return Err(crate::Error::GraphQlRequestFailed(messages.join("; ")));
+		}
+
+		response.data.ok_or_else(||
+			crate::Error::GraphQlRequestFailed("the response doesn’t contain any data".into()))
 	}
 
 	/// Make an HTTP HEAD request to the GitHub API (for arguments, see [Client::request]).
@@ -230,49 +1549,300 @@ impl Client
 	}
 }
 
+/// Percent-encode a value so that it can be used as a single path segment of an API endpoint. This
+/// is necessary for names that may contain characters such as `/`, `#`, or spaces, most notably
+/// branch names.
+pub fn encode_path_segment(segment: &str) -> String
+{
+	percent_encoding::utf8_percent_encode(segment, PATH_SEGMENT_ENCODE_SET).to_string()
+}
+
+/// Build the API endpoint of a repository with percent-encoded path segments.
+#[doc(hidden)]
+fn repository_endpoint(organization_name: &str, repository_name: &str) -> String
+{
+	format!("repos/{}/{}", encode_path_segment(organization_name),
+		encode_path_segment(repository_name))
+}
+
+/// Build the API endpoint of the protection settings of a branch with percent-encoded path
+/// segments. The `branches` endpoints only resolve names under `refs/heads/`, so a tag with the
+/// same name as the branch is never affected, and the branch name must not be fully qualified.
+#[doc(hidden)]
+fn branch_protection_endpoint(organization_name: &str, repository_name: &str, branch_name: &str)
+	-> String
+{
+	format!("{}/branches/{}/protection", repository_endpoint(organization_name, repository_name),
+		encode_path_segment(branch_name))
+}
+
+/// Characters that are percent-encoded in path segments, which are all except the unreserved
+/// characters as defined in RFC 3986.
+const PATH_SEGMENT_ENCODE_SET: &percent_encoding::AsciiSet = &percent_encoding::NON_ALPHANUMERIC
+	.remove(b'-')
+	.remove(b'.')
+	.remove(b'_')
+	.remove(b'~');
+
 /// When making requests without a request body, we don’t care which type is used to represent it.
 /// However, the compiler needs to know some type at compile time. This alias is used in order not
 /// to have to spell out the dummy type.
 pub const NO_BODY: Option<&()> = None;
 
+/// The number of items to request per page from paginated GitHub API endpoints (the maximum
+/// supported by GitHub).
+const PAGE_SIZE: usize = 100;
+
+/// The name and title of the check runs created by this service.
+const CHECK_RUN_NAME: &str = "Branch protection applied";
+
+/// GraphQL query for the ID and discussion categories of a repository.
+const DISCUSSION_REPOSITORY_QUERY: &str = "\
+	query($owner: String!, $name: String!) {
+		repository(owner: $owner, name: $name) {
+			id
+			hasDiscussionsEnabled
+			discussionCategories(first: 100) { nodes { id name } }
+		}
+	}";
+
+/// GraphQL mutation creating a discussion.
+const CREATE_DISCUSSION_MUTATION: &str = "\
+	mutation($repositoryId: ID!, $categoryId: ID!, $title: String!, $body: String!) {
+		createDiscussion(input: {repositoryId: $repositoryId, categoryId: $categoryId,
+			title: $title, body: $body}) {
+			discussion { url }
+		}
+	}";
+
+/// GraphQL mutation pinning an issue.
+const PIN_ISSUE_MUTATION: &str = "\
+	mutation($issueId: ID!) {
+		pinIssue(input: {issueId: $issueId}) {
+			issue { id }
+		}
+	}";
+
+/// Part of the message GitHub responds with when its abuse detection mechanism was triggered.
+const ABUSE_DETECTION_MESSAGE: &str = "abuse detection mechanism";
+
+/// Part of the message GitHub responds with when requesting an access token for a suspended
+/// installation.
+const INSTALLATION_SUSPENDED_MESSAGE: &str = "installation has been suspended";
+
+/// How long to back off after triggering GitHub’s abuse detection mechanism if GitHub doesn’t
+/// indicate how long to wait.
+const DEFAULT_ABUSE_DETECTION_BACKOFF_DURATION: std::time::Duration =
+	std::time::Duration::from_secs(60);
+
+/// The longest time to back off after exceeding the GitHub API rate limit before retrying, as
+/// requests are failed right away if the rate limit is reset later.
+const MAXIMUM_RATE_LIMIT_BACKOFF_DURATION: std::time::Duration =
+	std::time::Duration::from_secs(5 * 60);
+
+/// The header containing how many requests are left within the current rate limit window.
+const RATE_LIMIT_REMAINING_HEADER: &str = "x-ratelimit-remaining";
+
+/// The header containing when the current rate limit window is reset in seconds since the epoch.
+const RATE_LIMIT_RESET_HEADER: &str = "x-ratelimit-reset";
+
+/// How long to back off before retrying a request for the first time after a network issue or
+/// server error.
+const MINIMUM_RETRY_BACKOFF_DURATION: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// The longest time to back off before retrying a request after a network issue or server error,
+/// as the backoff doubles with each retry.
+const MAXIMUM_RETRY_BACKOFF_DURATION: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// How long to keep retrying a request after network issues or server errors unless limited.
+const TOTAL_RETRY_DURATION: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
+/// The header containing the identifier of the public key a payload was signed with.
+const PUBLIC_KEY_IDENTIFIER_HEADER: &str = "github-public-key-identifier";
+
+/// The header containing the Base64-encoded asymmetric signature of a payload.
+const PUBLIC_KEY_SIGNATURE_HEADER: &str = "github-public-key-signature";
+
+/// How long to remember the public keys payloads are signed with. Keys that aren’t known yet are
+/// looked up right away regardless, unless looked up within [PUBLIC_KEYS_REFRESH_INTERVAL].
+const PUBLIC_KEYS_CACHE_TIME_TO_LIVE: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// How long to wait before looking up the public keys payloads are signed with again for unknown
+/// key identifiers, which is also how long key identifiers are remembered as unknown.
+const PUBLIC_KEYS_REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// The DER encoding of a P-256 ECDSA public key without the uncompressed point that follows.
+const P256_PUBLIC_KEY_PREFIX: &[u8] = &[0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce,
+	0x3d, 0x02, 0x01, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07, 0x03, 0x42, 0x00];
+
+/// The DER encoding of an Ed25519 public key without the raw key that follows.
+const ED25519_PUBLIC_KEY_PREFIX: &[u8] =
+	&[0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00];
+
 /// Internal method for making HTTP requests in the initialization phase.
 #[doc(hidden)]
-async fn request<S, B, R>(
+async fn request<S, B, R, C>(
 	config: &Config,
 	reqwest_client: &reqwest_middleware::ClientWithMiddleware,
 	method: reqwest::Method,
 	endpoint: S,
 	body: Option<&B>,
-	access_token: &AccessToken)
+	credential: &C)
 	-> Result<R, crate::Error>
 where
 	S: AsRef<str>,
 	B: serde::Serialize,
 	R: serde::de::DeserializeOwned,
+	C: Credential,
+{
+	let response =
+		request_raw(config, reqwest_client, method, endpoint, body, credential).await?;
+
+	decode_response(response).await
+}
+
+/// Internal method for making HTTP requests, returning the response as is unless there was a
+/// client error (for arguments, see [request]).
+#[doc(hidden)]
+async fn request_raw<S, B, C>(
+	config: &Config,
+	reqwest_client: &reqwest_middleware::ClientWithMiddleware,
+	method: reqwest::Method,
+	endpoint: S,
+	body: Option<&B>,
+	credential: &C)
+	-> Result<reqwest::Response, crate::Error>
+where
+	S: AsRef<str>,
+	B: serde::Serialize,
+	C: Credential,
 {
 	// Build the API endpoint URL from the base URL and the endpoint path
 	let url = config.base_url.join(endpoint.as_ref()).map_err(crate::Error::ParseUrl)?;
-	let mut request = reqwest_client.request(method, url);
 
-	if let Some(body) = body
+	let mut rate_limit_error_retries = 0;
+
+	let response = loop
+	{
+		let mut request = reqwest_client.request(method.clone(), url.clone());
+
+		if let Some(body) = body
+		{
+			// Append the request body if provided
+			request = request.json(&body);
+		}
+
+		let response = request
+			// Provide the credential using the Authentication header
+			.header(reqwest::header::AUTHORIZATION,
+				config.auth_header_scheme.header_value(credential))
+			// Request the v3 REST API, as recommended by GitHub’s documentation
+			.header(reqwest::header::ACCEPT, "application/vnd.github.v3+json")
+			// Send the request
+			.send().await.map_err(crate::Error::from_request_error)?;
+
+		// Return the response for further processing unless there was a client error according to
+		// the response’s HTTP status
+		if !response.status().is_client_error()
+		{
+			break response;
+		}
+
+		let status_code = response.status();
+		let url = response.url().to_owned();
+
+		// GitHub may ask us to wait for a while before making further requests
+		let retry_after = response.headers().get(reqwest::header::RETRY_AFTER)
+			.and_then(|retry_after| retry_after.to_str().ok())
+			.and_then(|retry_after| retry_after.parse().ok())
+			.map(std::time::Duration::from_secs)
+			.or_else(|| rate_limit_reset_duration(response.headers()));
+
+		// Decode the body for debugging purposes
+		let response_body = response.text().await.map_err(map_reqwest_error)?;
+
+		// GitHub’s abuse detection mechanism responds with 403 Forbidden and a specific message,
+		// which we handle separately by backing off for the indicated time before trying again
+		if status_code == reqwest::StatusCode::FORBIDDEN
+			&& response_body.contains(ABUSE_DETECTION_MESSAGE)
+		{
+			let retry_after = retry_after.unwrap_or(DEFAULT_ABUSE_DETECTION_BACKOFF_DURATION);
+
+			if rate_limit_error_retries >= config.retry_limits.rate_limit_errors
+			{
+				return Err(crate::Error::AbuseDetected{url, retry_after});
+			}
+
+			log::warn!("triggered GitHub’s abuse detection mechanism, waiting {} seconds before \
+				retrying", retry_after.as_secs());
+
+			tokio::time::sleep(retry_after).await;
+			rate_limit_error_retries += 1;
+
+			continue;
+		}
+
+		let error = crate::Error::ReceivedGitHubApiClientError{status_code, url, response_body};
+
+		// Exceeding the primary or secondary rate limits is handled the same way, unless the rate
+		// limit is only reset so late that the event is better handled as failed, which leaves
+		// waiting to callers such as backfilling
+		if error.is_rate_limit_exceeded()
+		{
+			let retry_after = retry_after.unwrap_or(DEFAULT_ABUSE_DETECTION_BACKOFF_DURATION);
+
+			if rate_limit_error_retries >= config.retry_limits.rate_limit_errors
+				|| retry_after > MAXIMUM_RATE_LIMIT_BACKOFF_DURATION
+			{
+				return Err(error);
+			}
+
+			log::warn!("exceeded the GitHub API rate limit, waiting {} seconds before retrying",
+				retry_after.as_secs());
+
+			tokio::time::sleep(retry_after).await;
+			rate_limit_error_retries += 1;
+
+			continue;
+		}
+
+		return Err(error);
+	};
+
+	Ok(response)
+}
+
+/// How long until the rate limit a response says was exhausted is reset, if it says so.
+///
+/// # Arguments
+/// - `headers`: The headers of the response.
+#[doc(hidden)]
+fn rate_limit_reset_duration(headers: &reqwest::header::HeaderMap) -> Option<std::time::Duration>
+{
+	let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+
+	if header(RATE_LIMIT_REMAINING_HEADER) != Some("0")
 	{
-		// Append the request body if provided
-		request = request.json(&body);
+		return None;
 	}
 
-	let map_reqwest_error =
-		|error| crate::Error::MakeGitHubApiRequest(reqwest_middleware::Error::Reqwest(error));
+	let reset_at = std::time::UNIX_EPOCH
+		+ std::time::Duration::from_secs(header(RATE_LIMIT_RESET_HEADER)?.parse().ok()?);
 
-	let response = request
-		// Provide the access token using the Authentication header
-		.bearer_auth(access_token)
-		// Request the v3 REST API, as recommended by GitHub’s documentation
-		.header(reqwest::header::ACCEPT, "application/vnd.github.v3+json")
-		// Send the request
-		.send().await.map_err(crate::Error::MakeGitHubApiRequest)?;
+	// If the reset time already passed, retry right away
+	Some(reset_at.duration_since(std::time::SystemTime::now()).unwrap_or_default())
+}
 
-	// Return an error if there was a client error according to the response’s HTTP status
-	if response.status().is_client_error()
+/// Internal method for decoding the JSON body of a response into the desired type, returning an
+/// error if there was a server error according to the response’s HTTP status.
+#[doc(hidden)]
+async fn decode_response<R>(response: reqwest::Response) -> Result<R, crate::Error>
+where
+	R: serde::de::DeserializeOwned,
+{
+	// Return an error if there was a server error according to the response’s HTTP status, which
+	// isn’t a network error, as GitHub was reached after all
+	if response.status().is_server_error()
 	{
 		let status_code = response.status();
 		let url = response.url().to_owned();
@@ -280,14 +1850,11 @@ where
 		// Decode the body for debugging purposes
 		let response_body = response.text().await.map_err(map_reqwest_error)?;
 
-		return Err(crate::Error::ReceivedGitHubApiClientError{status_code, url, response_body});
+		return Err(crate::Error::ReceivedGitHubApiServerError{status_code, url, response_body});
 	}
 
-	let mut response_body = response
-		// Return an error if there was a server error according to the response’s HTTP status
-		.error_for_status().map_err(map_reqwest_error)?
-		// Read the full response if there was no server error
-		.bytes().await.map_err(map_reqwest_error)?;
+	// Read the full response if there was no server error
+	let mut response_body = response.bytes().await.map_err(map_reqwest_error)?;
 
 	// Allow deserializing empty responses as empty dictionaries instead, as empty strings are
 	// invalid JSON
@@ -299,19 +1866,65 @@ where
 	serde_json::from_slice(&response_body).map_err(crate::Error::DecodeGitHubApiResponseBody)
 }
 
+#[doc(hidden)]
+fn map_reqwest_error(error: reqwest::Error) -> crate::Error
+{
+	crate::Error::from_request_error(reqwest_middleware::Error::Reqwest(error))
+}
+
 /// Verify a webhook event payload by checking the provided signature.
+///
+/// As documented by GitHub, the signature is the hex-encoded HMAC digest of the raw payload bytes
+/// as received (before decoding them from JSON) with the webhook secret as the key, prefixed with
+/// the name of the algorithm. For example, the payload `Hello, World!` with the secret
+/// `It's a Secret to Everybody` has the SHA-256 signature
+/// `sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17`.
+///
+/// The secret is selected before verifying anything: if the payload names an organization with a
+/// secret of its own, that secret is used, and otherwise the general webhook secret. As the
+/// organization is read from the unverified payload, a sender knowing the secret of one
+/// organization could otherwise sign payloads acting on repositories of another organization.
+/// Therefore, payloads whose `organization` field names a different organization than the owner of
+/// the repository, which is what events are acted on, are rejected.
+///
+/// The accepted algorithms are tried in order, and the payload is verified with the first one whose
+/// signature header is present. Signatures created with other algorithms are ignored.
+///
+/// # Arguments
+/// - `headers`: The headers of the request, which include the signatures.
+/// - `payload`: The raw payload bytes.
+/// - `secret`: The webhook secret, if configured.
+/// - `organization_secrets`: The webhook secrets by organization slug.
+/// - `algorithms`: The accepted algorithms in order of preference.
 #[doc(hidden)]
 fn verify_payload_signature(
-	provided_signature: Option<String>,
+	headers: &warp::http::HeaderMap,
 	payload: &[u8],
-	secret: Option<&str>)
+	secret: Option<&str>,
+	organization_secrets: &std::collections::HashMap<String, String>,
+	algorithms: &[SignatureAlgorithm])
 	-> Result<(), crate::Error>
 {
-	let secret = match secret
+	let organization_secret = match organization_secrets.is_empty()
 	{
-		Some(secret) => secret,
+		true => None,
+		false => payload_organization(payload)?.and_then(|organization|
+			organization_secrets.iter()
+				.find(|(name, _)| name.eq_ignore_ascii_case(&organization))
+				.map(|(_, secret)| secret.as_str())),
+	};
+
+	let secret = match (organization_secret, secret)
+	{
+		(Some(secret), _) | (None, Some(secret)) => secret,
+		// Organizations without a secret of their own must not bypass verification
+		(None, None) if !organization_secrets.is_empty() =>
+		{
+			log::warn!("received payload of an organization without a webhook secret");
+			return Err(crate::Error::InvalidPayloadSignature);
+		},
 		// If no secret was configured, accept all payloads
-		None =>
+		(None, None) =>
 		{
 			log::warn!("no webhook secret configured, ignoring payload signature (this should be \
 				configured for production use)");
@@ -319,23 +1932,19 @@ fn verify_payload_signature(
 		}
 	};
 
-	// Otherwise, require a valid payload signature. If none is provided, reject the request
-	let provided_signature = provided_signature.ok_or(crate::Error::MissingPayloadSignature)?;
+	// Otherwise, require a valid payload signature. If none is provided with any of the accepted
+	// algorithms, reject the request
+	let (algorithm, provided_signature) = algorithms.iter()
+		.find_map(|algorithm| headers.get(algorithm.header_name())
+			.map(|provided_signature| (*algorithm, provided_signature)))
+		.ok_or(crate::Error::MissingPayloadSignature)?;
 
-	// Only SHA-256 signatures are supported, reject anything else
-	let provided_signature = provided_signature.strip_prefix("sha256=")
+	let provided_signature = provided_signature.to_str().ok()
+		.and_then(|provided_signature| provided_signature.strip_prefix(algorithm.prefix()))
 		.ok_or(crate::Error::InvalidPayloadSignature)?;
 
-	use hmac::Mac as _;
-
 	// Compute the expected signature
-	let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(secret.as_bytes())
-		.expect("this call is infallible because HMAC supports keys of arbitrary size");
-
-	mac.update(payload);
-
-	let expected_signature = mac.finalize().into_bytes();
-	let expected_signature = hex::encode(&expected_signature);
+	let expected_signature = algorithm.sign(payload, secret);
 
 	// Compare the provided signature with what we expect it to be. Use a secure string wrapper that
 	// provides a constant-time equality comparator to prevent timing attacks
@@ -344,7 +1953,7 @@ fn verify_payload_signature(
 
 	if provided_signature == expected_signature
 	{
-		log::debug!("successfully verified payload signature");
+		log::debug!("successfully verified payload signature using {algorithm:?}");
 		Ok(())
 	}
 	else
@@ -354,34 +1963,216 @@ fn verify_payload_signature(
 	}
 }
 
+/// The slug of the organization a webhook payload is about, if any, which is read from the raw
+/// payload before verifying it, only to select the webhook secret to verify it with. The
+/// organization is taken from the owner of the repository if present, or else from the
+/// `organization` field. Payloads naming a different organization in the `organization` field than
+/// the owner of the repository are rejected, as they would be verified with the secret of one
+/// organization but acted on in another.
+///
+/// # Arguments
+/// - `payload`: The raw payload bytes.
+#[doc(hidden)]
+fn payload_organization(payload: &[u8]) -> Result<Option<String>, crate::Error>
+{
+	let payload: PayloadOrganization = match serde_json::from_slice(payload)
+	{
+		Ok(payload) => payload,
+		Err(_) => return Ok(None),
+	};
+
+	let organization = payload.organization.map(|organization| organization.login);
+	let repository_owner = payload.repository.map(|repository| repository.owner.login);
+
+	match (organization, repository_owner)
+	{
+		(Some(organization), Some(repository_owner))
+			if !organization.eq_ignore_ascii_case(&repository_owner) =>
+		{
+			log::warn!("received payload of organization “{organization}” for a repository \
+				owned by “{repository_owner}”");
+			Err(crate::Error::InvalidPayloadSignature)
+		},
+		(organization, repository_owner) => Ok(repository_owner.or(organization)),
+	}
+}
+
+/// Verify a webhook event payload by checking its asymmetric signature against the public keys
+/// GitHub publishes, which are looked up by the identifier provided along with the signature.
+///
+/// The signature is the Base64-encoded signature of the raw payload bytes as received, which is an
+/// ASN.1-encoded ECDSA signature with P-256 and SHA-256 or an Ed25519 signature, depending on the
+/// key.
+///
+/// # Arguments
+/// - `client`: The GitHub API client, which looks up and caches the public keys.
+/// - `headers`: The headers of the request, which include the signature and the key identifier.
+/// - `payload`: The raw payload bytes.
+#[doc(hidden)]
+async fn verify_public_key_signature(client: &Client, headers: &warp::http::HeaderMap,
+	payload: &[u8])
+	-> Result<(), crate::Error>
+{
+	use base64::Engine as _;
+
+	let header = |name| headers.get(name).map(|value| value.to_str()
+		.map_err(|_| crate::Error::InvalidPayloadSignature));
+
+	let (key_identifier, signature) =
+		match (header(PUBLIC_KEY_IDENTIFIER_HEADER), header(PUBLIC_KEY_SIGNATURE_HEADER))
+	{
+		(Some(key_identifier), Some(signature)) => (key_identifier?, signature?),
+		_ => return Err(crate::Error::MissingPayloadSignature),
+	};
+
+	let signature = base64::engine::general_purpose::STANDARD.decode(signature)
+		.map_err(|_| crate::Error::InvalidPayloadSignature)?;
+
+	let public_key = match client.webhook_public_key(key_identifier).await?
+	{
+		Some(public_key) => public_key,
+		None =>
+		{
+			log::warn!("received payload signed with unknown public key “{key_identifier}”");
+			return Err(crate::Error::InvalidPayloadSignature);
+		},
+	};
+
+	ring::signature::UnparsedPublicKey::new(public_key.algorithm, &public_key.key)
+		.verify(payload, &signature)
+		.map_err(|_|
+		{
+			log::warn!("received payload with invalid signature");
+			crate::Error::InvalidPayloadSignature
+		})?;
+
+	log::debug!("successfully verified payload signature using public key “{key_identifier}”");
+
+	Ok(())
+}
+
+/// A public key GitHub signs webhook payloads with, along with its signature algorithm.
+#[doc(hidden)]
+#[derive(Clone)]
+struct WebhookPublicKey
+{
+	algorithm: &'static dyn ring::signature::VerificationAlgorithm,
+	key: Vec<u8>,
+}
+
+impl WebhookPublicKey
+{
+	/// Parse a PEM-encoded public key as published by GitHub, returning `None` for unsupported
+	/// keys. Only P-256 ECDSA and Ed25519 keys are supported, whose DER encodings consist of a
+	/// fixed prefix followed by the raw key.
+	fn from_pem(pem: &str) -> Option<Self>
+	{
+		use base64::Engine as _;
+
+		let der: String = pem.lines()
+			.filter(|line| !line.starts_with("-----"))
+			.flat_map(|line| line.trim().chars())
+			.collect();
+		let der = base64::engine::general_purpose::STANDARD.decode(der).ok()?;
+
+		if let Some(key) = der.strip_prefix(P256_PUBLIC_KEY_PREFIX)
+		{
+			return Some(Self
+			{
+				algorithm: &ring::signature::ECDSA_P256_SHA256_ASN1,
+				key: key.to_vec(),
+			});
+		}
+
+		der.strip_prefix(ED25519_PUBLIC_KEY_PREFIX)
+			.map(|key| Self{algorithm: &ring::signature::ED25519, key: key.to_vec()})
+	}
+}
+
+/// Outcome of checking a signature header value with [check_signature_header].
+pub struct SignatureCheck
+{
+	/// The algorithm the signature was created with according to its prefix, if supported.
+	pub algorithm: Option<SignatureAlgorithm>,
+	/// The signature computed from the payload and the secret including the prefix, as it would be
+	/// expected in the signature header, if the algorithm is supported.
+	pub computed_signature: Option<String>,
+	/// Whether the signature is valid, or why not.
+	pub result: Result<(), crate::Error>,
+}
+
+/// Check whether the value of a signature header is a valid signature of a payload for a webhook
+/// secret, exactly as when receiving a webhook event (see [verify_payload_signature]). All
+/// supported algorithms are accepted, as indicated by the prefix of the signature.
+///
+/// # Arguments
+/// - `payload`: The raw payload bytes.
+/// - `secret`: The webhook secret.
+/// - `signature`: The value of the signature header, including the prefix (example:
+///   `sha256=757107ea…`).
+pub fn check_signature_header(payload: &[u8], secret: &str, signature: &str) -> SignatureCheck
+{
+	let algorithm = SignatureAlgorithm::ALL.into_iter()
+		.find(|algorithm| signature.starts_with(algorithm.prefix()));
+
+	let algorithm = match algorithm
+	{
+		Some(algorithm) => algorithm,
+		None => return SignatureCheck
+		{
+			algorithm: None,
+			computed_signature: None,
+			result: Err(crate::Error::InvalidPayloadSignature),
+		},
+	};
+
+	let mut headers = warp::http::HeaderMap::new();
+
+	let result = match warp::http::HeaderValue::from_str(signature)
+	{
+		Ok(signature) =>
+		{
+			headers.insert(algorithm.header_name(), signature);
+			verify_payload_signature(&headers, payload, Some(secret), &Default::default(),
+				&[algorithm])
+		},
+		Err(_) => Err(crate::Error::InvalidPayloadSignature),
+	};
+
+	SignatureCheck
+	{
+		algorithm: Some(algorithm),
+		computed_signature:
+			Some(format!("{}{}", algorithm.prefix(), algorithm.sign(payload, secret))),
+		result,
+	}
+}
+
 /// [1]: <https://github.com/seanmonstar/warp/blob/3ff2eaf41eb5ac9321620e5a6434d5b5ec6f313f/examples/todos.rs#L99-L101>
-/// [2]: <https://github.com/seanmonstar/warp/blob/3ff2eaf41eb5ac9321620e5a6434d5b5ec6f313f/src/filters/body.rs#L228-L237>
-/// [warp] filter allowing us to extract the payload, verify its signature if configured, and decode
-/// it from JSON into a struct of the desired type. Returns the decoded payload and a handle to the
-/// GitHub API client for further usage as arguments to subsequent handlers in that order. Inspired
-/// by the [to-do example][1] and [JSON decode implementation][2] provided by [warp].
+/// [warp] filter allowing us to extract the payload and verify its signature if configured.
+/// Returns the raw payload for decoding it with [decode_payload] or further usage as an argument to
+/// subsequent handlers. Inspired by the [to-do example][1] provided by [warp].
 ///
 /// # Arguments
-/// - `client`: The handle to the GitHub API client.
-pub fn with_validated_payload_and_client<T>(client: Client)
-	-> impl warp::Filter<Extract = (T, Client), Error = warp::Rejection> + Clone
-where
-	T: serde::de::DeserializeOwned + Send,
+/// - `client`: The handle to the GitHub API client, whose configuration includes the webhook
+///   secret.
+pub fn with_verified_payload(client: Client)
+	-> impl warp::Filter<Extract = (warp::hyper::body::Bytes,), Error = warp::Rejection> + Clone
 {
 	use warp::Filter as _;
 
 	warp::any()
 		// Relay a handle to the client
 		.map(move || {client.clone()})
-		// Relay the body as raw bytes for payload signature validation and JSON decoding
+		// Relay the body as raw bytes for payload signature validation
 		.and(warp::body::bytes())
-		// Relay the payload signature header if present
-		.and(warp::header::optional::<String>("x-hub-signature-256"))
-		// Validate the payload signature if configured and decode the body into JSON
+		// Relay the headers, which include the payload signatures if present
+		.and(warp::header::headers_cloned())
+		// Validate the payload signature if configured
 		.and_then(
 			|client: Client,
 				mut bytes: warp::hyper::body::Bytes,
-				provided_signature: Option<String>|
+				headers: warp::http::HeaderMap|
 			async move
 			{
 				use warp::Buf as _;
@@ -389,22 +2180,33 @@ where
 				// Resize the payload buffer view to the size that was actually written
 				let bytes = bytes.copy_to_bytes(bytes.remaining());
 
-				// Decode the payload from JSON
-				let payload = serde_json::from_slice(&bytes)
-					.map_err(crate::Error::DecodePayloadBody)
-					.map_err(warp::reject::custom)?;
-
-				// If configured, require a valid payload signature
-				verify_payload_signature(provided_signature, &bytes,
-					client.config.webhook_secret.as_deref())
-						.map_err(warp::reject::custom)?;
+				// If configured, require a valid payload signature before looking at the payload,
+				// so that deliveries not coming from GitHub are reported as such
+				match client.config.signature_verification
+				{
+					SignatureVerification::Hmac => verify_payload_signature(&headers, &bytes,
+						client.config.webhook_secret.as_deref(), &client.config.webhook_secrets,
+						&client.config.signature_algorithms),
+					SignatureVerification::PublicKey =>
+						verify_public_key_signature(&client, &headers, &bytes).await,
+				}
+				.map_err(warp::reject::custom)?;
 
-				Ok::<_, warp::Rejection>((payload, client))
+				Ok::<_, warp::Rejection>(bytes)
 			})
-		// The last call returned the payload and client as a tuple, but we’d like subsequent calls
-		// in the filter chain to receive them as top-level arguments and not nested within a single
-		// tuple
-		.untuple_one()
+}
+
+/// [1]: <https://github.com/seanmonstar/warp/blob/3ff2eaf41eb5ac9321620e5a6434d5b5ec6f313f/src/filters/body.rs#L228-L237>
+/// Decode a payload verified with [with_verified_payload] from JSON into a struct of the desired
+/// type. Inspired by the [JSON decode implementation][1] provided by [warp].
+///
+/// # Arguments
+/// - `payload`: The raw payload.
+pub fn decode_payload<T>(payload: &[u8]) -> Result<T, crate::Error>
+where
+	T: serde::de::DeserializeOwned,
+{
+	serde_json::from_slice(payload).map_err(crate::Error::DecodePayloadBody)
 }
 
 #[doc(hidden)]
@@ -421,17 +2223,23 @@ struct JwtClaims<'a>
 	issuer: &'a str,
 }
 
+/// Credentials GitHub API requests can be authenticated with.
+#[doc(hidden)]
+trait Credential
+{
+	/// The secret value sent in the `Authorization` header.
+	fn secret(&self) -> &str;
+}
+
+/// JWT authenticating as the GitHub App itself, which is only accepted for bootstrapping
+/// installation access tokens and expires after a few minutes.
 #[doc(hidden)]
-#[derive(Clone, Eq, Ord, PartialEq, PartialOrd)]
-struct AccessToken(String);
+struct AppJwt(String);
 
-impl AccessToken
+impl AppJwt
 {
-	async fn new(
-		config: &Config,
-		private_key: &jsonwebtoken::EncodingKey,
-		reqwest_client: &reqwest_middleware::ClientWithMiddleware)
-		-> Result<Self, crate::Error>
+	/// Create a JWT signed with the GitHub App’s private key.
+	fn new(config: &Config, private_key: &jsonwebtoken::EncodingKey) -> Result<Self, crate::Error>
 	{
 		let now = chrono::Utc::now();
 
@@ -439,8 +2247,8 @@ impl AccessToken
 		// [1] https://docs.github.com/en/developers/apps/building-github-apps/authenticating-with-github-apps#authenticating-as-a-github-app
 		let jwt_claims = JwtClaims
 		{
-			// Pretend that the JWT was issued a minute ago to allow for clock drift
-			issued_at: now - chrono::Duration::minutes(1),
+			// Pretend that the JWT was issued a while ago to allow for clock drift
+			issued_at: now - config.jwt_issued_at_backdate()?,
 			// Ask for the JWT to expire in 10 minutes
 			expires_at: now + chrono::Duration::minutes(10),
 			// Specify that this JWT was issued by our GitHub App
@@ -450,26 +2258,91 @@ impl AccessToken
 		let jwt_header = jsonwebtoken::Header::new(jsonwebtoken::Algorithm::RS256);
 
 		// Encode the payload with the GitHub App’s private key to obtain the JWT
-		let jwt = jsonwebtoken::encode(&jwt_header, &jwt_claims, &private_key)
+		let jwt = jsonwebtoken::encode(&jwt_header, &jwt_claims, private_key)
 			.map_err(crate::Error::CreateJwt)?;
-		// We can use the JWT in lieu of a regular access token for the following API requests
-		let access_token = AccessToken(jwt);
+
+		Ok(Self(jwt))
+	}
+}
+
+impl Credential for AppJwt
+{
+	fn secret(&self) -> &str
+	{
+		&self.0
+	}
+}
+
+/// Access token of the GitHub App’s installation on the organization, which all regular GitHub API
+/// requests are authenticated with.
+#[doc(hidden)]
+#[derive(Clone, Eq, PartialEq)]
+struct InstallationToken(String);
+
+impl InstallationToken
+{
+	/// Request a fresh installation access token, authenticating as the GitHub App with a JWT.
+	async fn new(
+		config: &Config,
+		private_key: &jsonwebtoken::EncodingKey,
+		reqwest_client: &reqwest_middleware::ClientWithMiddleware)
+		-> Result<Self, crate::Error>
+	{
+		// The JWT is only used for the following two bootstrapping requests
+		let jwt = AppJwt::new(config, private_key)?;
 
 		// Make a request to the /orgs/{org}/installation API to get the installation ID on the
 		// organization
 		let get_organization_installation_url =
 			format!("orgs/{}/installation", config.organization);
-		let response: GitHubAppInstallationResponse = request(config, reqwest_client,
-			reqwest::Method::GET, get_organization_installation_url, NO_BODY, &access_token).await
-				.map_err(Box::new).map_err(crate::Error::ObtainGitHubAppInstallationToken)?;
+		let response: GitHubAppInstallationResponse = match request(config, reqwest_client,
+			reqwest::Method::GET, get_organization_installation_url, NO_BODY, &jwt).await
+		{
+			Ok(response) => response,
+			// GitHub rejects JWTs issued in the future or expiring too late, which is usually
+			// caused by clock skew between this host and GitHub
+			Err(crate::Error::ReceivedGitHubApiClientError{status_code, response_body, ..})
+				if status_code == reqwest::StatusCode::UNAUTHORIZED =>
+			{
+				log::error!("GitHub rejected the JWT of this GitHub App ({response_body}), which \
+					is often caused by the clock of this host being off. Check that the clock is \
+					synchronized or increase github_api.jwt_issued_at_backdate_seconds");
+				return Err(crate::Error::JwtRejected{response_body});
+			},
+			Err(error) => return Err(crate::Error::ObtainGitHubAppInstallationToken(
+				Box::new(error))),
+		};
 		let installation_id = response.id;
 
+		// Tokens can’t be obtained for suspended installations, which needs to be fixed by an
+		// organization owner, so report this separately from other failures
+		let installation_suspended = || crate::Error::InstallationSuspended
+		{
+			organization: config.organization.clone(),
+		};
+
+		if let Some(suspended_at) = response.suspended_at
+		{
+			log::error!("GitHub App installation for the organization “{}” was suspended at \
+				{suspended_at}", config.organization);
+			return Err(installation_suspended());
+		}
+
 		// Make another request to generate an access token we can use for this installation
 		let get_installation_access_token_url =
 			format!("app/installations/{installation_id}/access_tokens");
-		let response: GitHubAppAccessTokenResponse = request(config, reqwest_client,
-			reqwest::Method::POST, get_installation_access_token_url, NO_BODY, &access_token).await
-				.map_err(Box::new).map_err(crate::Error::ObtainGitHubAppInstallationToken)?;
+		let response: GitHubAppAccessTokenResponse = match request(config, reqwest_client,
+			reqwest::Method::POST, get_installation_access_token_url, NO_BODY, &jwt).await
+		{
+			Ok(response) => response,
+			// The installation may have been suspended since we looked it up
+			Err(crate::Error::ReceivedGitHubApiClientError{status_code, response_body, ..})
+				if status_code == reqwest::StatusCode::FORBIDDEN
+					&& response_body.contains(INSTALLATION_SUSPENDED_MESSAGE) =>
+				return Err(installation_suspended()),
+			Err(error) => return Err(crate::Error::ObtainGitHubAppInstallationToken(
+				Box::new(error))),
+		};
 
 		log::info!("successfully obtained installation access token for the organization “{}”",
 			config.organization);
@@ -478,14 +2351,107 @@ impl AccessToken
 	}
 }
 
-impl std::fmt::Display for AccessToken
+/// Look up the metadata of the GitHub App, such as its slug (example: `branch-autoprotector`),
+/// authenticating as the GitHub App with a JWT.
+#[doc(hidden)]
+async fn request_app_metadata(config: &Config, private_key: &jsonwebtoken::EncodingKey,
+	reqwest_client: &reqwest_middleware::ClientWithMiddleware)
+	-> Result<crate::AppMetadata, crate::Error>
+{
+	let jwt = AppJwt::new(config, private_key)?;
+
+	let app_metadata: crate::AppMetadata =
+		request(config, reqwest_client, reqwest::Method::GET, "app", NO_BODY, &jwt).await
+			.map_err(|error| crate::Error::ObtainGitHubAppMetadata(Box::new(error)))?;
+
+	log::info!("this GitHub App’s slug is “{}”", app_metadata.slug);
+
+	Ok(app_metadata)
+}
+
+impl Credential for InstallationToken
 {
-	fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result
+	fn secret(&self) -> &str
 	{
-		write!(formatter, "{}", self.0)
+		&self.0
 	}
 }
 
+/// Request to the GitHub GraphQL API.
+#[doc(hidden)]
+#[derive(serde::Serialize)]
+struct GraphQlRequest<'a, V>
+{
+	query: &'a str,
+	variables: &'a V,
+}
+
+/// Response from a request to the GitHub GraphQL API, which may contain errors despite succeeding
+/// according to its HTTP status.
+#[doc(hidden)]
+#[derive(Debug, serde::Deserialize)]
+struct GraphQlResponse<R>
+{
+	data: Option<R>,
+	#[serde(default)]
+	errors: Vec<GraphQlError>,
+}
+
+/// Error reported in a response from the GitHub GraphQL API.
+#[doc(hidden)]
+#[derive(Debug, serde::Deserialize)]
+struct GraphQlError
+{
+	message: String,
+	// We just need the message, so ignore all other fields
+}
+
+/// Data of the response to [DISCUSSION_REPOSITORY_QUERY].
+#[doc(hidden)]
+#[derive(Debug, serde::Deserialize)]
+struct DiscussionRepositoryQueryData
+{
+	repository: crate::DiscussionRepositoryResponse,
+}
+
+/// Data of the response to [CREATE_DISCUSSION_MUTATION].
+#[doc(hidden)]
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateDiscussionMutationData
+{
+	create_discussion: CreatedDiscussionPayload,
+}
+
+/// Payload of the mutation creating a discussion.
+#[doc(hidden)]
+#[derive(Debug, serde::Deserialize)]
+struct CreatedDiscussionPayload
+{
+	discussion: crate::Discussion,
+}
+
+/// The fields of a webhook payload naming the organization it’s about.
+#[doc(hidden)]
+#[derive(Debug, serde::Deserialize)]
+struct PayloadOrganization
+{
+	#[serde(default)]
+	organization: Option<crate::Organization>,
+	#[serde(default)]
+	repository: Option<PayloadRepository>,
+	// We just need the organization, so ignore all other fields
+}
+
+/// The owner of the repository a webhook payload is about.
+#[doc(hidden)]
+#[derive(Debug, serde::Deserialize)]
+struct PayloadRepository
+{
+	owner: crate::User,
+	// We just need the owner, so ignore all other fields
+}
+
 /// Response from a request to retrieve the GitHub App installation for a given organization.
 #[doc(hidden)]
 #[derive(Debug, serde::Deserialize)]
@@ -493,7 +2459,10 @@ impl std::fmt::Display for AccessToken
 struct GitHubAppInstallationResponse
 {
 	pub id: u64,
-	// We just need the installation ID, so ignore all other fields
+	/// When the installation was suspended, if it is.
+	#[serde(default)]
+	pub suspended_at: Option<String>,
+	// We just need the installation ID and suspension status, so ignore all other fields
 }
 
 /// Response from a request to obtain an access token for a given installation of a GitHub App.
@@ -505,3 +2474,241 @@ struct GitHubAppAccessTokenResponse
 	pub token: String,
 	// We just need the token itself, so ignore all other fields
 }
+
+#[cfg(test)]
+mod tests
+{
+	/// The payload of GitHub’s documented example of a payload signature.
+	const EXAMPLE_PAYLOAD: &[u8] = b"Hello, World!";
+
+	/// The webhook secret of GitHub’s documented example of a payload signature.
+	const EXAMPLE_SECRET: &str = "It's a Secret to Everybody";
+
+	/// The signature header of GitHub’s documented example of a payload signature.
+	const EXAMPLE_SIGNATURE: &str =
+		"sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+
+	/// The headers of a request carrying GitHub’s documented example signature.
+	fn example_signature_headers() -> warp::http::HeaderMap
+	{
+		let mut headers = warp::http::HeaderMap::new();
+		headers.insert("x-hub-signature-256", EXAMPLE_SIGNATURE.parse().unwrap());
+
+		headers
+	}
+
+	#[test]
+	fn verify_payload_signature_accepts_github_example()
+	{
+		assert!(super::verify_payload_signature(&example_signature_headers(), EXAMPLE_PAYLOAD,
+			Some(EXAMPLE_SECRET), &Default::default(), &[super::SignatureAlgorithm::Sha256])
+			.is_ok());
+	}
+
+	#[test]
+	fn verify_payload_signature_rejects_modified_payload()
+	{
+		let mut payload = EXAMPLE_PAYLOAD.to_vec();
+		payload[0] = b'J';
+
+		assert!(matches!(super::verify_payload_signature(&example_signature_headers(), &payload,
+			Some(EXAMPLE_SECRET), &Default::default(), &[super::SignatureAlgorithm::Sha256]),
+			Err(crate::Error::InvalidPayloadSignature)));
+	}
+
+	/// The payload of an event in another organization than the example organization.
+	const OTHER_ORGANIZATION_PAYLOAD: &[u8] =
+		br#"{"ref": "main", "organization": {"login": "other-organization"}}"#;
+
+	/// Verify a payload signed with one secret against the configured secrets.
+	///
+	/// # Arguments
+	/// - `payload`: The raw payload bytes.
+	/// - `signing_secret`: The secret the payload is signed with.
+	/// - `secret`: The general webhook secret, if configured.
+	/// - `organization_secrets`: The webhook secrets by organization slug.
+	fn verify_signed_payload(payload: &[u8], signing_secret: &str, secret: Option<&str>,
+		organization_secrets: &[(&str, &str)])
+		-> Result<(), crate::Error>
+	{
+		let algorithm = super::SignatureAlgorithm::Sha256;
+
+		let mut headers = warp::http::HeaderMap::new();
+		headers.insert("x-hub-signature-256",
+			format!("sha256={}", algorithm.sign(payload, signing_secret)).parse().unwrap());
+
+		let organization_secrets = organization_secrets.iter()
+			.map(|(organization, secret)| (organization.to_string(), secret.to_string()))
+			.collect();
+
+		super::verify_payload_signature(&headers, payload, secret, &organization_secrets,
+			&[algorithm])
+	}
+
+	#[test]
+	fn verify_payload_signature_uses_secret_of_organization()
+	{
+		let organization_secrets = [("Other-Organization", "other secret")];
+
+		assert!(verify_signed_payload(OTHER_ORGANIZATION_PAYLOAD, "other secret",
+			Some(EXAMPLE_SECRET), &organization_secrets).is_ok());
+		assert!(matches!(verify_signed_payload(OTHER_ORGANIZATION_PAYLOAD, EXAMPLE_SECRET,
+			Some(EXAMPLE_SECRET), &organization_secrets),
+			Err(crate::Error::InvalidPayloadSignature)));
+	}
+
+	#[test]
+	fn verify_payload_signature_reads_organization_from_repository_owner()
+	{
+		let payload = br#"{"repository": {"name": "x", "owner": {"login": "other-organization"}}}"#;
+
+		assert!(verify_signed_payload(payload, "other secret", None,
+			&[("other-organization", "other secret")]).is_ok());
+	}
+
+	#[test]
+	fn verify_payload_signature_rejects_organization_differing_from_repository_owner()
+	{
+		let payload = br#"{"organization": {"login": "other-organization"},
+			"repository": {"name": "x", "owner": {"login": "example-organization"}}}"#;
+		let organization_secrets =
+			[("other-organization", "other secret"), ("example-organization", "example secret")];
+
+		assert!(matches!(verify_signed_payload(payload, "other secret", None,
+			&organization_secrets), Err(crate::Error::InvalidPayloadSignature)));
+		assert!(matches!(verify_signed_payload(payload, "example secret", None,
+			&organization_secrets), Err(crate::Error::InvalidPayloadSignature)));
+	}
+
+	#[test]
+	fn verify_payload_signature_falls_back_to_general_secret()
+	{
+		let organization_secrets = [("example-organization", "example secret")];
+
+		assert!(verify_signed_payload(OTHER_ORGANIZATION_PAYLOAD, EXAMPLE_SECRET,
+			Some(EXAMPLE_SECRET), &organization_secrets).is_ok());
+		assert!(verify_signed_payload(b"not JSON", EXAMPLE_SECRET, Some(EXAMPLE_SECRET),
+			&organization_secrets).is_ok());
+	}
+
+	#[test]
+	fn verify_payload_signature_rejects_organization_without_secret()
+	{
+		assert!(matches!(verify_signed_payload(OTHER_ORGANIZATION_PAYLOAD, "any secret", None,
+			&[("example-organization", "example secret")]),
+			Err(crate::Error::InvalidPayloadSignature)));
+	}
+
+	#[test]
+	fn sign_computes_github_example_digest()
+	{
+		assert_eq!(format!("sha256={}",
+			super::SignatureAlgorithm::Sha256.sign(EXAMPLE_PAYLOAD, EXAMPLE_SECRET)),
+			EXAMPLE_SIGNATURE);
+	}
+
+	#[test]
+	fn encode_path_segment_encodes_reserved_characters()
+	{
+		assert_eq!(super::encode_path_segment("main"), "main");
+		assert_eq!(super::encode_path_segment("release-1.0_rc~1"), "release-1.0_rc~1");
+		assert_eq!(super::encode_path_segment("feature/foo bar"), "feature%2Ffoo%20bar");
+		assert_eq!(super::encode_path_segment("fix#42"), "fix%2342");
+		assert_eq!(super::encode_path_segment("50%?&"), "50%25%3F%26");
+		assert_eq!(super::encode_path_segment("über"), "%C3%BCber");
+	}
+
+	#[test]
+	fn branch_protection_endpoint_encodes_branch_name_as_single_segment()
+	{
+		assert_eq!(super::branch_protection_endpoint("example-organization",
+			"example-repository", "feature/foo bar"),
+			"repos/example-organization/example-repository/branches/feature%2Ffoo%20bar/\
+			protection");
+		assert_eq!(super::branch_protection_endpoint("example-organization",
+			"example.repository", "fix#42"),
+			"repos/example-organization/example.repository/branches/fix%2342/protection");
+	}
+
+	#[tokio::test]
+	async fn list_protected_branches_collects_all_pages()
+	{
+		const ENDPOINT: &str = "repos/example-organization/example-repository/branches\
+			?protected=true&per_page=100";
+
+		let first_page: Vec<_> = (1..=100)
+			.map(|number|
+				serde_json::json!({"name": format!("release/{number}"), "protected": true}))
+			.collect();
+
+		let mock_github = crate::test_support::MockGitHub::new();
+		mock_github
+			.respond("GET", &format!("{ENDPOINT}&page=1"), 200, serde_json::json!(first_page))
+			.respond("GET", &format!("{ENDPOINT}&page=2"), 200,
+				serde_json::json!([{"name": "main", "protected": true}]));
+		let client = mock_github.client(serde_json::json!({})).await;
+
+		let branches = client.list_protected_branches("example-organization", "example-repository")
+			.await.unwrap();
+
+		assert_eq!(branches.len(), 101);
+		assert_eq!(branches[0], "release/1");
+		assert_eq!(branches[99], "release/100");
+		assert_eq!(branches[100], "main");
+		assert!(mock_github.requests("GET", &format!("{ENDPOINT}&page=3")).is_empty());
+	}
+
+	#[tokio::test]
+	async fn server_errors_are_not_network_errors()
+	{
+		const ENDPOINT: &str = "repos/example-organization/example-repository";
+
+		let mock_github = crate::test_support::MockGitHub::new();
+		mock_github.respond("GET", ENDPOINT, 502, serde_json::json!({"message": "Bad Gateway"}));
+		let client = mock_github.client(serde_json::json!({"retry_limits": {"server_errors": 0}}))
+			.await;
+
+		let error = client.get_repository("example-organization", "example-repository").await
+			.unwrap_err();
+
+		assert!(matches!(error, crate::Error::ReceivedGitHubApiServerError{status_code, ..}
+			if status_code == reqwest::StatusCode::BAD_GATEWAY), "{error:?}");
+		assert_eq!(error.network_error_kind(), None);
+	}
+
+	#[tokio::test]
+	async fn app_metadata_is_requested_once()
+	{
+		let mock_github = crate::test_support::MockGitHub::new();
+		let client = mock_github.client(serde_json::json!({})).await;
+
+		let app_metadata = client.app_metadata().await.unwrap();
+
+		assert_eq!(app_metadata.slug, "branch-autoprotector");
+		assert_eq!(app_metadata.permissions.get("administration").map(String::as_str),
+			Some("write"));
+		assert_eq!(app_metadata.events, ["create"]);
+		assert_eq!(app_metadata.bot_login(), "branch-autoprotector[bot]");
+
+		client.app_metadata().await.unwrap();
+
+		assert_eq!(mock_github.requests("GET", "app").len(), 1);
+		assert_eq!(client.app_bot_login().as_deref(), Some("branch-autoprotector[bot]"));
+	}
+
+	#[tokio::test]
+	async fn webhook_public_key_looks_up_keys_once_for_unknown_identifiers()
+	{
+		let mock_github = crate::test_support::MockGitHub::new();
+		mock_github.respond("GET", "meta/public_keys/secret_scanning", 200,
+			serde_json::json!({"public_keys": []}));
+		let client = mock_github.client(serde_json::json!({})).await;
+
+		for key_identifier in ["unknown", "unknown", "other-unknown", "unknown"]
+		{
+			assert!(client.webhook_public_key(key_identifier).await.unwrap().is_none());
+		}
+
+		assert_eq!(mock_github.requests("GET", "meta/public_keys/secret_scanning").len(), 1);
+	}
+}
@@ -0,0 +1,97 @@
+/// Tracks whether this service is in a degraded state in which it can’t act on webhook events,
+/// such as when GitHub App installation access tokens can’t be obtained repeatedly.
+///
+/// The degradation flag can safely be shared between threads, as cloning it only clones a handle
+/// to the underlying data.
+#[derive(Clone, Default)]
+pub struct Degradation
+{
+	#[doc(hidden)]
+	state: std::sync::Arc<std::sync::Mutex<DegradationState>>,
+}
+
+#[doc(hidden)]
+#[derive(Default)]
+struct DegradationState
+{
+	consecutive_token_failures: u32,
+	reason: Option<String>,
+}
+
+impl Degradation
+{
+	/// Record a failure to obtain an access token, which degrades the service once it happened
+	/// [DEGRADED_AFTER_TOKEN_FAILURES] times in a row.
+	///
+	/// # Arguments
+	/// - `error`: Why the access token couldn’t be obtained.
+	pub fn record_token_failure(&self, error: &crate::Error)
+	{
+		let mut state = self.lock();
+		state.consecutive_token_failures += 1;
+
+		if state.consecutive_token_failures >= DEGRADED_AFTER_TOKEN_FAILURES
+		{
+			if state.reason.is_none()
+			{
+				log::error!("service is degraded, as the GitHub App installation access token \
+					couldn’t be obtained {} times in a row", state.consecutive_token_failures);
+			}
+
+			state.reason = Some(format!("could not obtain GitHub App installation access token \
+				{} times in a row: {error}", state.consecutive_token_failures));
+		}
+	}
+
+	/// Record that an access token was obtained, which ends a degradation caused by failures to
+	/// obtain access tokens.
+	pub fn record_token_success(&self)
+	{
+		let mut state = self.lock();
+
+		if state.reason.is_some()
+		{
+			log::info!("service recovered, as the GitHub App installation access token was \
+				obtained");
+		}
+
+		*state = Default::default();
+	}
+
+	/// Why the service is degraded, or `None` if it isn’t.
+	pub fn reason(&self) -> Option<String>
+	{
+		self.lock().reason.clone()
+	}
+
+	#[doc(hidden)]
+	fn lock(&self) -> std::sync::MutexGuard<'_, DegradationState>
+	{
+		// The state stays consistent even if a thread panicked while holding the lock
+		self.state.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+	}
+}
+
+/// [warp] filter reporting that this service is up at `/health`, which doesn’t require GitHub to
+/// be reachable. If `strict_health_check` is enabled, a degraded service is reported with 503
+/// Service Unavailable and the reason instead.
+///
+/// # Arguments
+/// - `state`: The shared state, including the degradation flag and the configuration.
+pub fn health_route(state: crate::AppState)
+	-> impl warp::Filter<Extract = (crate::EventReply,), Error = warp::Rejection> + Clone
+{
+	use warp::Filter as _;
+
+	warp::path!("health")
+		.and(warp::get())
+		.map(move || match state.degradation.reason()
+		{
+			Some(reason) if state.config().strict_health_check => crate::EventReply::error(
+				warp::http::StatusCode::SERVICE_UNAVAILABLE, format!("degraded: {reason}")),
+			_ => crate::EventReply::info(warp::http::StatusCode::OK, "healthy"),
+		})
+}
+
+/// How many times in a row obtaining an access token needs to fail for the service to be degraded.
+pub const DEGRADED_AFTER_TOKEN_FAILURES: u32 = 3;
@@ -1,28 +1,237 @@
 #[doc(hidden)]
+mod backfill;
+#[doc(hidden)]
+mod branch_pattern;
+#[doc(hidden)]
+mod cache;
+#[doc(hidden)]
+mod central_policy;
+#[doc(hidden)]
+mod cli;
+#[doc(hidden)]
+mod concurrency;
+#[doc(hidden)]
 mod config;
 #[doc(hidden)]
+mod decision_hook;
+#[doc(hidden)]
+mod diagnostics;
+#[doc(hidden)]
+mod dry_run;
+#[doc(hidden)]
 mod error;
+#[doc(hidden)]
+mod event_sink;
+#[doc(hidden)]
+mod export;
+#[doc(hidden)]
+mod failure_notification;
+#[doc(hidden)]
+mod fault_injection;
 pub mod github_api;
 #[doc(hidden)]
+mod health;
+#[doc(hidden)]
+mod metrics;
+#[doc(hidden)]
 mod models;
+#[doc(hidden)]
+mod notification_limit;
+#[doc(hidden)]
+mod payload_capture;
+#[doc(hidden)]
+mod protection;
+#[doc(hidden)]
+mod reply;
+#[doc(hidden)]
+mod retry_failed;
+#[doc(hidden)]
+mod ruleset_pull_request;
+#[doc(hidden)]
+mod source_ip_allow_list;
+#[doc(hidden)]
+mod state;
+#[doc(hidden)]
+mod step_summary;
+#[doc(hidden)]
+mod telemetry;
+#[cfg(test)]
+mod test_support;
 
-pub use config::Config;
+pub use backfill::{backfill, repository_skip_reason};
+pub use branch_pattern::*;
+pub use cache::ExpiringCache;
+pub use central_policy::*;
+pub use concurrency::*;
+pub use config::{AcknowledgmentConfig, BranchProtectionSettings, Config,
+	DeletedProtectionConfig, ExistingProtectionPolicy, NotificationOrder, NotificationTarget};
+pub use decision_hook::*;
+pub use diagnostics::*;
+pub use dry_run::*;
 pub use error::Error;
+pub use event_sink::*;
+pub use export::{export, retry_if_rate_limited};
+pub use failure_notification::*;
+pub use fault_injection::*;
+pub use health::*;
+pub use metrics::*;
 pub use models::*;
+pub use notification_limit::*;
+pub use payload_capture::*;
+pub use protection::*;
+pub use reply::*;
+pub use retry_failed::retry_failed;
+pub use ruleset_pull_request::*;
+pub use source_ip_allow_list::*;
+pub use state::*;
+pub use step_summary::*;
+pub use telemetry::*;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()>
 {
 	pretty_env_logger::init();
 
+	use clap::Parser as _;
+
+	let arguments = cli::Arguments::parse();
+
+	// Verifying signatures doesn’t require the configuration, so that it works anywhere
+	if let Some(cli::Command::VerifySignature{payload, secret, signature}) = &arguments.command
+	{
+		let payload = std::fs::read(payload).map_err(crate::Error::ReadPayloadFile)?;
+		let check = github_api::check_signature_header(&payload, secret, signature);
+
+		match check.algorithm
+		{
+			Some(algorithm) => println!("algorithm: {algorithm:?}"),
+			None => println!("algorithm: unsupported (expected a prefix such as “sha256=”)"),
+		}
+
+		println!("provided signature: {signature}");
+
+		if let Some(computed_signature) = &check.computed_signature
+		{
+			println!("computed signature: {computed_signature}");
+		}
+
+		match check.result
+		{
+			Ok(()) => println!("result: pass"),
+			Err(error) =>
+			{
+				println!("result: fail");
+				return Err(error.into());
+			},
+		}
+
+		return Ok(());
+	}
+
+	if let Some(cli::Command::Schema) = &arguments.command
+	{
+		println!("{}", Config::json_schema());
+		return Ok(());
+	}
+
 	// Read the config file
-	let config = Config::from_file("config.yaml")?;
+	let mut config = Config::from_file("config.yaml")?;
 
 	// Initialize a new GitHub API client using the GitHub App created for this service
-	let github_api_client = github_api::Client::from_config(config.github_api).await?;
+	let mut github_api_client =
+		github_api::Client::from_config(config.github_api.clone()).await?;
+
+	// Fault injection requires an explicit flag so that a copied staging configuration can’t
+	// enable it in production by accident
+	if let Some(fault_injection) = &config.debug.fault_injection
+	{
+		if !arguments.allow_fault_injection
+		{
+			return Err(crate::Error::InvalidConfig("debug.fault_injection is set, which requires \
+				passing --allow-fault-injection and must never be used in production".to_string())
+				.into());
+		}
+
+		github_api_client = github_api_client.with_fault_injection(fault_injection.clone());
+	}
+
+	// If configured, overlay the branch protection settings maintained in a central repository,
+	// which are remembered in case they can’t be fetched again when reloading the configuration
+	let central_policy_cache = CentralPolicyCache::default();
+	apply_central_policy(&mut config, &github_api_client, &central_policy_cache).await;
+
+	match arguments.command
+	{
+		None => (),
+		Some(cli::Command::Export{format, output}) =>
+		{
+			export(&github_api_client, format, output.as_deref()).await?;
+			return Ok(());
+		},
+		Some(cli::Command::Backfill{concurrency, fail_on_error}) =>
+		{
+			backfill(&github_api_client, std::sync::Arc::new(config), concurrency.into(),
+				fail_on_error).await?;
+			return Ok(());
+		},
+		Some(cli::Command::RetryFailed{records, concurrency, fail_on_error}) =>
+		{
+			let concurrency = concurrency.map(usize::from)
+				.or(config.max_concurrent_events)
+				.unwrap_or(DEFAULT_RETRY_FAILED_CONCURRENCY);
+
+			retry_failed(&github_api_client, std::sync::Arc::new(config), &records, concurrency,
+				fail_on_error).await?;
+			return Ok(());
+		},
+		Some(cli::Command::VerifySignature{..} | cli::Command::Schema) =>
+			unreachable!("these commands are handled before reading the configuration"),
+	}
+
+	init_tracing(config.otel_endpoint.as_ref())?;
+
+	let source_ip_allow_list = config.source_ip_allow_list.as_ref()
+		.map(|allow_list| SourceIpAllowList::new(allow_list, github_api_client.clone()));
+
+	// Summarize the policy enforced by this instance so misconfigurations become obvious right away
+	match &config.default_policy
+	{
+		Some(policy_name) => log::info!("protecting branches with the settings of policy \
+			“{policy_name}” by default"),
+		None => log::info!("protecting branches with the following settings by default: {}",
+			config.branch_protection),
+	}
+
+	let mut policy_names: Vec<_> = config.policies.keys().collect();
+	policy_names.sort();
+
+	for policy_name in policy_names
+	{
+		log::info!("policy “{policy_name}” has the following settings: {}",
+			config.policies[policy_name]);
+	}
+
+	if config.maintenance_mode
+	{
+		log::warn!("maintenance mode is enabled, so webhook events are acknowledged but not acted \
+			on");
+	}
+
+	// Bundle the state shared by all request handlers
+	let state = AppState::new(config, github_api_client)?;
+
+	// If requested, handle a single webhook event read from a file without binding a socket
+	if let (Some(payload_path), Some(event_type)) = (arguments.once, arguments.event)
+	{
+		let result = handle_event_once(&payload_path, event_type, &state).await;
+		shutdown_tracing();
+		return Ok(result?);
+	}
 
 	use warp::Filter as _;
 
+	let config = state.config();
+
 	let ref_creation_event_route =
 		// Only listen for requests to the root path
 		warp::path::end()
@@ -30,18 +239,360 @@ async fn main() -> anyhow::Result<()>
 		.and(warp::post())
 		// Only listen for ref creation events
 		.and(warp::header::exact_ignore_case("x-github-event", "create"))
-		// Reject payloads larger than 256 kB, which should be enough for all valid requests
-		.and(warp::body::content_length_limit(256 * 1024))
-		// Retrieve and validate the payload and pass it on along with the GitHub API client
-		.and(github_api::with_validated_payload_and_client(github_api_client))
+		// If configured, only accept webhook events from IP addresses on the allow list
+		.and(with_allowed_source_ip_address(source_ip_allow_list.clone()))
+		// Unless configured otherwise, reject payloads larger than 256 kB, which should be enough
+		// for all valid requests
+		.and(warp::body::content_length_limit(
+			config.payload_size_limit(Some("create"), 256 * 1024)))
+		// Retrieve and validate the payload and pass it on along with the shared state
+		.and(with_validated_payload_and_state(state.clone()))
+		// Pass on the delivery ID for tracing
+		.and(warp::header::optional("x-github-delivery"))
 		// Forward request to request handler
 		.and_then(handle_ref_creation_event);
 
-	let routes = ref_creation_event_route
-		.recover(handle_rejection);
+	let push_event_route =
+		// Only listen for POST requests to the root path
+		warp::path::end()
+		.and(warp::post())
+		// Only listen for push events
+		.and(warp::header::exact_ignore_case("x-github-event", "push"))
+		.and(with_allowed_source_ip_address(source_ip_allow_list.clone()))
+		// Push events may list many commits, so allow larger payloads than for ref creation events
+		.and(warp::body::content_length_limit(
+			config.payload_size_limit(Some("push"), 25 * 1024 * 1024)))
+		.and(with_validated_payload_and_state(state.clone()))
+		.and(warp::header::optional("x-github-delivery"))
+		.and_then(handle_push_event);
+
+	let branch_protection_rule_event_route =
+		// Only listen for POST requests to the root path
+		warp::path::end()
+		.and(warp::post())
+		// Only listen for branch protection rule events
+		.and(warp::header::exact_ignore_case("x-github-event", "branch_protection_rule"))
+		.and(with_allowed_source_ip_address(source_ip_allow_list.clone()))
+		.and(warp::body::content_length_limit(
+			config.payload_size_limit(Some("branch_protection_rule"), 256 * 1024)))
+		.and(with_validated_payload_and_state(state.clone()))
+		.and(warp::header::optional("x-github-delivery"))
+		.and_then(handle_branch_protection_rule_event);
+
+	let repository_event_route =
+		// Only listen for POST requests to the root path
+		warp::path::end()
+		.and(warp::post())
+		// Only listen for repository events, of which only transfers are acted on
+		.and(warp::header::exact_ignore_case("x-github-event", "repository"))
+		.and(with_allowed_source_ip_address(source_ip_allow_list.clone()))
+		.and(warp::body::content_length_limit(
+			config.payload_size_limit(Some("repository"), 256 * 1024)))
+		.and(with_validated_payload_and_state(state.clone()))
+		.and(warp::header::optional("x-github-delivery"))
+		.and_then(handle_repository_event);
+
+	let unhandled_event_route =
+		// Only listen for POST requests to the root path
+		warp::path::end()
+		.and(warp::post())
+		// If configured, listen for all events that no other route handles
+		.and(with_unhandled_event_type(state.clone()))
+		.and(with_allowed_source_ip_address(source_ip_allow_list.clone()))
+		// Payloads of unhandled events may be as large as GitHub sends them
+		.and(warp::body::content_length_limit(config.payload_size_limit(None, 25 * 1024 * 1024)))
+		// Verify the payload signature, but don’t decode the payload
+		.and(github_api::with_verified_payload(state.github_api_client.clone()))
+		.and(warp::header::optional("x-github-delivery"))
+		.and_then(handle_unhandled_event);
 
-	log::info!("listening for incoming webhook events on 127.0.0.1:2342");
-	warp::serve(routes).run(([127, 0, 0, 1], 2342)).await;
+	// Report that this service is up unless it’s degraded and configured to report this
+	let health_route = health_route(state.clone());
+
+	let metrics = state.metrics.clone();
+	let diagnostics_state = state.clone();
+	// Keep a handle to the state for recording lifecycle events
+	let lifecycle_state = state.clone();
+
+	let event_routes = health_route
+		.or(ref_creation_event_route).unify()
+		.or(push_event_route).unify()
+		.or(branch_protection_rule_event_route).unify()
+		.or(repository_event_route).unify()
+		.or(unhandled_event_route).unify()
+		.recover(move |error| handle_rejection(error, state.clone())).unify();
+
+	// If configured, include how long it took to reply, measured from receiving the request
+	let include_timing = config.debug.include_timing;
+
+	// Encode replies as JSON or plain text depending on the Accept header
+	let event_routes = warp::any().map(std::time::Instant::now)
+		.and(with_response_format())
+		.and(event_routes)
+		.map(move |received_at: std::time::Instant, format, reply: EventReply|
+		{
+			let reply = match include_timing
+			{
+				true => reply.with_processing_time(received_at.elapsed()),
+				false => reply,
+			};
+
+			reply.into_response(format)
+		});
+
+	let routes =
+		// Serve metrics about received webhook events
+		metrics_route(metrics).map(warp::Reply::into_response)
+		// Serve diagnostic information, including the GitHub API rate limit status
+		.or(diagnostics_route(diagnostics_state).map(warp::Reply::into_response)).unify()
+		// Describe this service on GET requests to the webhook URL for debugging
+		.or(index_route().map(warp::Reply::into_response)).unify()
+		// As rejections of the webhook event routes are recovered from, they need to come last
+		.or(event_routes).unify()
+		// Add the security headers and the configured headers to all replies
+		.with(warp::reply::with::headers(config.response_headers()));
+
+	let (address, server) = warp::serve(routes)
+		.bind_with_graceful_shutdown(([127, 0, 0, 1], 2342),
+		{
+			let lifecycle_state = lifecycle_state.clone();
+
+			async move
+			{
+				wait_for_shutdown_signal().await;
+
+				log::info!("shutting down after handling pending webhook events");
+				lifecycle_state.event_sink.record_lifecycle(LifecycleEvent::Stopping,
+					lifecycle_state.github_api_client.organization(), &lifecycle_state.config())
+					.await;
+			}
+		});
+
+	log::info!("listening for incoming webhook events on {address}");
+	lifecycle_state.event_sink.record_lifecycle(LifecycleEvent::Started,
+		lifecycle_state.github_api_client.organization(), &lifecycle_state.config()).await;
+
+	let pending_events = lifecycle_state.pending_events.clone();
+	tokio::spawn(reload_config_on_hangup(lifecycle_state, central_policy_cache));
+
+	server.await;
+
+	// Replies to webhook events are sent before handling them, so wait for those still pending
+	if pending_events.count() > 0
+	{
+		log::info!("waiting for {} pending webhook events to be handled",
+			pending_events.count());
+		pending_events.wait().await;
+	}
+
+	shutdown_tracing();
+
+	Ok(())
+}
+
+/// Reload the configuration from `config.yaml` whenever SIGHUP is received, which applies to
+/// webhook events handled from then on. Invalid configurations are logged and otherwise ignored,
+/// and options that are only applied at startup, such as the GitHub API client’s, keep their
+/// values until the next restart.
+///
+/// # Arguments
+/// - `state`: The shared state whose configuration is replaced.
+/// - `central_policy_cache`: The central branch protection settings last fetched successfully.
+async fn reload_config_on_hangup(state: AppState, central_policy_cache: CentralPolicyCache)
+{
+	let mut hangup_signal = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+		.expect("this call is infallible because SIGHUP can be handled within a runtime");
+
+	while hangup_signal.recv().await.is_some()
+	{
+		let mut config = match Config::from_file("config.yaml")
+		{
+			Ok(config) => config,
+			Err(error) =>
+			{
+				log::error!("could not reload configuration, keeping the current one: {error}");
+				continue;
+			},
+		};
+
+		// Fetch the central branch protection settings again, as they may have changed, too
+		apply_central_policy(&mut config, &state.github_api_client, &central_policy_cache).await;
+
+		for option in config.keep_restart_only_options(&state.config())
+		{
+			log::warn!("option “{option}” changed but is only applied after a restart");
+		}
+
+		match (state.config().maintenance_mode, config.maintenance_mode)
+		{
+			(false, true) => log::warn!("entering maintenance mode, so webhook events are \
+				acknowledged but not acted on"),
+			(true, false) => log::info!("leaving maintenance mode"),
+			_ => (),
+		}
+
+		state.replace_config(config);
+
+		log::info!("reloaded configuration");
+		state.event_sink.record_lifecycle(LifecycleEvent::ConfigReloaded,
+			state.github_api_client.organization(), &state.config()).await;
+	}
+}
+
+/// Wait until this service is asked to shut down by SIGTERM, as sent by systemd, or SIGINT, as
+/// sent when pressing Ctrl+C.
+async fn wait_for_shutdown_signal()
+{
+	let mut terminate_signal =
+		tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+			.expect("this call is infallible because SIGTERM can be handled within a runtime");
+
+	tokio::select!
+	{
+		_ = terminate_signal.recv() => (),
+		_ = tokio::signal::ctrl_c() => (),
+	}
+}
+
+/// Handle a single webhook event whose payload is read from a file, waiting for the branch to be
+/// protected and printing the outcome, which is also added to the job summary if invoked from
+/// GitHub Actions. This is meant for debugging and replaying events locally, which is why payload
+/// signatures aren’t verified.
+///
+/// # Arguments
+/// - `payload_path`: Path to the file containing the webhook event payload.
+/// - `event_type`: The type of the webhook event.
+/// - `state`: The shared state, including the GitHub API client and the configuration.
+async fn handle_event_once(payload_path: &std::path::Path, event_type: cli::EventType,
+	state: &AppState)
+	-> Result<(), crate::Error>
+{
+	let payload = std::fs::read(payload_path).map_err(crate::Error::ReadPayloadFile)?;
+	let config = state.config();
+
+	// Replayed events aren’t acted on in maintenance mode either, just like webhook events
+	if config.maintenance_mode
+	{
+		log::info!("maintenance mode: not acting on webhook event read from “{}”",
+			payload_path.display());
+		println!("ignored: not acting on webhook event, as maintenance mode is enabled");
+		return Ok(());
+	}
+
+	let (target, outcome) = match event_type
+	{
+		cli::EventType::Create =>
+		{
+			let payload = serde_json::from_slice(&payload)
+				.map_err(crate::Error::DecodePayloadBody)?;
+
+			match ProtectionTarget::from_ref_creation_event(payload, &config)
+			{
+				Ok(mut creation) =>
+				{
+					let outcome = match creation.resolve(&config, &state.github_api_client).await
+					{
+						Ok(()) => match consult_decision_hook(&config, &mut creation.target).await
+						{
+							Ok(()) => protect_default_branch(state, &creation.target).await,
+							Err(outcome) => outcome,
+						},
+						Err(outcome) => outcome,
+					};
+					(Some(creation.target), outcome)
+				},
+				Err(outcome) => (None, outcome),
+			}
+		},
+		cli::EventType::Push =>
+		{
+			let payload = serde_json::from_slice(&payload)
+				.map_err(crate::Error::DecodePayloadBody)?;
+
+			match ProtectionTarget::from_push_event(payload, &config)
+			{
+				Ok(mut target) =>
+				{
+					let outcome = match consult_decision_hook(&config, &mut target).await
+					{
+						Ok(()) => protect_default_branch_if_unprotected(state, &target).await,
+						Err(outcome) => outcome,
+					};
+					(Some(target), outcome)
+				},
+				Err(outcome) => (None, outcome),
+			}
+		},
+		cli::EventType::Repository =>
+		{
+			let payload = serde_json::from_slice(&payload)
+				.map_err(crate::Error::DecodePayloadBody)?;
+
+			match ProtectionTarget::from_repository_event(payload, &config)
+			{
+				Ok(mut target) =>
+				{
+					let outcome = match consult_decision_hook(&config, &mut target).await
+					{
+						Ok(()) => protect_default_branch_if_unprotected(state, &target).await,
+						Err(outcome) => outcome,
+					};
+					(Some(target), outcome)
+				},
+				Err(outcome) => (None, outcome),
+			}
+		},
+		cli::EventType::BranchProtectionRule =>
+		{
+			let payload = serde_json::from_slice(&payload)
+				.map_err(crate::Error::DecodePayloadBody)?;
+
+			match ProtectionTarget::from_branch_protection_rule_event(payload, &config)
+			{
+				Ok(mut target) =>
+				{
+					let outcome = match consult_decision_hook(&config, &mut target).await
+					{
+						Ok(()) => handle_deleted_protection(state, &target).await,
+						Err(outcome) => outcome,
+					};
+					(Some(target), outcome)
+				},
+				Err(outcome) => (None, outcome),
+			}
+		},
+	};
+
+	// When invoked from GitHub Actions, summarize the outcome in the job summary
+	append_step_summary(&[StepSummaryRow::from_outcome(target.as_ref(), &outcome)]);
+
+	// Failures are reported with the error chain and a nonzero exit code by the caller
+	match outcome
+	{
+		HandledOutcome::Ignored(reason) => println!("ignored: {reason}"),
+		HandledOutcome::Protected{issue_url} => println!("protected: issue_url={}",
+			issue_url.map(|issue_url| issue_url.to_string()).unwrap_or_default()),
+		HandledOutcome::Proposed{pull_request_url} => println!("proposed: pull_request_url={}",
+			pull_request_url.map(|pull_request_url| pull_request_url.to_string())
+				.unwrap_or_default()),
+		HandledOutcome::DriftReported{issue_url} => println!("drift reported: issue_url={}",
+			issue_url.map(|issue_url| issue_url.to_string()).unwrap_or_default()),
+		HandledOutcome::DeletionHandled{restored, issue_url} =>
+		{
+			println!("deletion handled: restored={restored} issue_url={}",
+				issue_url.map(|issue_url| issue_url.to_string()).unwrap_or_default());
+		},
+		HandledOutcome::DryRun{changes} =>
+		{
+			println!("dry run: {} changes", changes.len());
+
+			for change in changes
+			{
+				println!("{change}");
+			}
+		},
+		HandledOutcome::Failed(error) => return Err(error),
+	}
 
 	Ok(())
 }
@@ -50,117 +601,454 @@ async fn main() -> anyhow::Result<()>
 ///
 /// # Arguments
 /// - `payload`: The decoded webhook event payload.
-/// - `github_api_client`: A handle to the GitHub API client.
-async fn handle_ref_creation_event(
-	payload: RefCreationEventPayload,
-	github_api_client: github_api::Client)
-	-> Result<impl warp::Reply, std::convert::Infallible>
+/// - `state`: The shared state, including the GitHub API client and the configuration.
+/// - `delivery_id`: The ID of the webhook delivery, if provided.
+async fn handle_ref_creation_event(payload: RefCreationEventPayload, state: AppState,
+	delivery_id: Option<String>)
+	-> Result<EventReply, std::convert::Infallible>
 {
-	let branch_name = payload.ref_;
-	let default_branch_name = payload.master_branch;
+	use tracing::Instrument as _;
 
-	// Ignore all actions other than the creation of a branch. Also, if the newly created branch is
-	// not the default branch, this isn’t the first branch being created, so don’t set up branch
-	// protection rules either. In both cases, return a successful HTTP response
-	if payload.ref_type != RefType::Branch || branch_name != default_branch_name
-	{
-		log::debug!("unrelated ref creation event, ignoring");
+	let config = state.config();
 
-		let message = "not listening to this ref creation event";
-		let response = warp::reply::json(&InfoResponse{info: message});
+	if let Err(outcome) = check_sender(&payload.sender, config.act_on_self,
+		state.github_api_client.app_bot_login().as_deref())
+	{
+		return Ok(outcome_reply(outcome, config.ignored_event_status_code()));
+	}
 
-		return Ok(warp::reply::with_status(response, warp::http::StatusCode::OK));
+	if let Err(outcome) = check_event_age(&payload.repository, config.max_event_age())
+	{
+		return Ok(outcome_reply(outcome, config.ignored_event_status_code()));
 	}
 
-	let creator_name = payload.sender.login;
-	let organization_name = payload.repository.owner.login;
-	let repository_name = payload.repository.name;
+	let creation = match ProtectionTarget::from_ref_creation_event(payload, &config)
+	{
+		Ok(creation) => creation,
+		Err(outcome) =>
+			return Ok(outcome_reply(outcome, config.ignored_event_status_code())),
+	};
+
+	let action = "creating branch protection rules and notifying creator of the default branch";
+
+	if config.maintenance_mode
+	{
+		return Ok(maintenance_reply(action, &creation.target));
+	}
 
-	log::info!("repository “{repository_name}” was created in organization “{organization_name}” \
-		with a new default branch “{branch_name}”");
+	let reply = deferred_event_reply(action, &config.acknowledgment, &creation.target);
+	let span = webhook_event_span("create", delivery_id.as_deref(), &creation.target);
 
 	// Protect the default branch and inform about this in an issue in a separate task so as to
 	// immediately acknowledge the webhook event without blocking
-	tokio::spawn(
+	state.pending_events.clone().spawn(
+		async move
+		{
+			// If configured, give freshly created repositories some time to settle first
+			tokio::time::sleep(config.protection_delay()).await;
+
+			// If configured, wait until fewer events are being handled
+			let mut creation = creation;
+			let _permit = state.concurrency_limiter.acquire(&creation.target.organization_name)
+				.await;
+
+			if entered_maintenance_mode(&state, action, &creation.target)
+			{
+				return;
+			}
+
+			// Look up the default branch if the payload didn’t include it only now, so as not to
+			// delay acknowledging the webhook event, and if configured, let the decision hook veto
+			// acting on the event or select a policy
+			let outcome = match creation.resolve(&config, &state.github_api_client).await
+			{
+				Ok(()) => match consult_decision_hook(&state.config(), &mut creation.target).await
+				{
+					Ok(()) => protect_default_branch(&state, &creation.target).await,
+					Err(outcome) => outcome,
+				},
+				Err(outcome) => outcome,
+			};
+
+			outcome.report(&state, &creation.target).await;
+		}.instrument(span));
+
+	// Acknowledge the successful receipt of this webhook event as quickly as possible
+	Ok(reply)
+}
+
+/// Request handler for valid push events.
+///
+/// Repositories that are created empty don’t have a default branch yet. If configured, the default
+/// branch of such a repository is protected when it’s pushed for the first time and isn’t protected
+/// yet, as GitHub doesn’t necessarily deliver a ref creation event in this case.
+///
+/// # Arguments
+/// - `payload`: The decoded webhook event payload.
+/// - `state`: The shared state, including the GitHub API client and the configuration.
+/// - `delivery_id`: The ID of the webhook delivery, if provided.
+async fn handle_push_event(payload: PushEventPayload, state: AppState,
+	delivery_id: Option<String>)
+	-> Result<EventReply, std::convert::Infallible>
+{
+	use tracing::Instrument as _;
+
+	let config = state.config();
+
+	if let Err(outcome) = check_sender(&payload.sender, config.act_on_self,
+		state.github_api_client.app_bot_login().as_deref())
+	{
+		return Ok(outcome_reply(outcome, config.ignored_event_status_code()));
+	}
+
+	if let Err(outcome) = check_event_age(&payload.repository, config.max_event_age())
+	{
+		return Ok(outcome_reply(outcome, config.ignored_event_status_code()));
+	}
+
+	let target = match ProtectionTarget::from_push_event(payload, &config)
+	{
+		Ok(target) => target,
+		Err(outcome) =>
+			return Ok(outcome_reply(outcome, config.ignored_event_status_code())),
+	};
+
+	let action = "creating branch protection rules and notifying creator of the default branch \
+		unless already protected";
+
+	if config.maintenance_mode
+	{
+		return Ok(maintenance_reply(action, &target));
+	}
+
+	let reply = deferred_event_reply(action, &config.acknowledgment, &target);
+	let span = webhook_event_span("push", delivery_id.as_deref(), &target);
+
+	// Check whether the default branch is protected already and protect it otherwise in a separate
+	// task so as to immediately acknowledge the webhook event without blocking
+	state.pending_events.clone().spawn(
 		async move
 		{
-			// Protect the new default branch by disallowing users from pushing directly (including
-			// administrators) and requiring at least one pull request review
-			let protect_branch_request = ProtectBranchRequest
+			tokio::time::sleep(config.protection_delay()).await;
+
+			let _permit = state.concurrency_limiter.acquire(&target.organization_name).await;
+
+			if entered_maintenance_mode(&state, action, &target)
 			{
-				required_status_checks: None,
-				enforce_admins: Some(true),
-				required_pull_request_reviews: Some(RequiredPullRequestReviews{}),
-				restrictions: None,
+				return;
+			}
+
+			let mut target = target;
+			let outcome = match consult_decision_hook(&state.config(), &mut target).await
+			{
+				Ok(()) => protect_default_branch_if_unprotected(&state, &target).await,
+				Err(outcome) => outcome,
 			};
 
-			if let Err(error) = github_api_client.put::<_, _, IgnoreResponse>(
-				format!("repos/{organization_name}/{repository_name}/branches/{branch_name}\
-					/protection"),
-				&protect_branch_request).await
+			outcome.report(&state, &target).await;
+		}.instrument(span));
+
+	// Acknowledge the successful receipt of this webhook event as quickly as possible
+	Ok(reply)
+}
+
+/// Request handler for valid repository events.
+///
+/// The default branches of repositories transferred into the organization are protected unless
+/// they’re protected already, for example, because a ref creation event was handled for them, too.
+///
+/// # Arguments
+/// - `payload`: The decoded webhook event payload.
+/// - `state`: The shared state, including the GitHub API client and the configuration.
+/// - `delivery_id`: The ID of the webhook delivery, if provided.
+async fn handle_repository_event(payload: RepositoryEventPayload, state: AppState,
+	delivery_id: Option<String>)
+	-> Result<EventReply, std::convert::Infallible>
+{
+	use tracing::Instrument as _;
+
+	let config = state.config();
+
+	if let Err(outcome) = check_sender(&payload.sender, config.act_on_self,
+		state.github_api_client.app_bot_login().as_deref())
+	{
+		return Ok(outcome_reply(outcome, config.ignored_event_status_code()));
+	}
+
+	let target = match ProtectionTarget::from_repository_event(payload, &config)
+	{
+		Ok(target) => target,
+		Err(outcome) =>
+			return Ok(outcome_reply(outcome, config.ignored_event_status_code())),
+	};
+
+	let action = "creating branch protection rules and notifying creator of the default branch \
+		unless already protected";
+
+	if config.maintenance_mode
+	{
+		return Ok(maintenance_reply(action, &target));
+	}
+
+	let reply = deferred_event_reply(action, &config.acknowledgment, &target);
+	let span = webhook_event_span("repository", delivery_id.as_deref(), &target);
+
+	// Protect the default branch of the transferred repository unless it’s protected already in a
+	// separate task so as to immediately acknowledge the webhook event without blocking
+	state.pending_events.clone().spawn(
+		async move
+		{
+			tokio::time::sleep(config.protection_delay()).await;
+
+			let _permit = state.concurrency_limiter.acquire(&target.organization_name).await;
+
+			if entered_maintenance_mode(&state, action, &target)
 			{
-				log::error!("could not set up branch protection rule for branch “{branch_name}” in \
-					repository “{repository_name}”");
-				log::error!("{:?}", anyhow::Error::from(error));
 				return;
 			}
 
-			log::info!("set up branch protection rule for branch “{branch_name}” in repository \
-				“{repository_name}”");
-
-			// Notify the user triggering the branch creation event of the newly set-up branch
-			// protection rules
-			let issue_title = "Branch protection automatically set up";
-			let issue_body = format!(
-				"@{creator_name}: The default branch [`{branch_name}`](../tree/{branch_name}) was \
-				automatically protected to comply with our corporate policies. Please submit pull \
-				requests in order to contribute changes, as direct pushes to this branch are not \
-				allowed. Every pull request needs to be approved by at least one person before it \
-				can be merged. Please review the [branch protection rules in the repository \
-				settings](../settings/branches) and extend them as necessary.\
-				\n\
-				\n\
-				This issue is just for your information and can be closed after reviewing the \
-				branch protection rules.");
-
-			let create_issue_request_body = CreateIssueRequest
-			{
-				title: issue_title,
-				body: Some(&issue_body),
+			let mut target = target;
+			let outcome = match consult_decision_hook(&state.config(), &mut target).await
+			{
+				Ok(()) => protect_default_branch_if_unprotected(&state, &target).await,
+				Err(outcome) => outcome,
 			};
 
-			let created_issue: CreateIssueResponse = match github_api_client.post(
-				format!("repos/{organization_name}/{repository_name}/issues"),
-				&create_issue_request_body).await
+			outcome.report(&state, &target).await;
+		}.instrument(span));
+
+	// Acknowledge the successful receipt of this webhook event as quickly as possible
+	Ok(reply)
+}
+
+/// Request handler for verified webhook events of types this service doesn’t handle, which are
+/// acknowledged with 200 OK if `acknowledge_unhandled_events` is enabled.
+///
+/// # Arguments
+/// - `event_type`: The type of the webhook event (example: `pull_request`).
+/// - `_payload`: The raw webhook event payload, which isn’t looked at.
+/// - `delivery_id`: The ID of the webhook delivery, if provided.
+async fn handle_unhandled_event(event_type: String, _payload: warp::hyper::body::Bytes,
+	delivery_id: Option<String>)
+	-> Result<EventReply, std::convert::Infallible>
+{
+	log::debug!("acknowledging unhandled webhook event “{event_type}” (delivery ID: {})",
+		delivery_id.as_deref().unwrap_or("unknown"));
+
+	Ok(EventReply::info(warp::http::StatusCode::OK,
+		format!("acknowledged webhook event “{event_type}”, which this service doesn’t handle")))
+}
+
+/// [warp] filter passing on the type of a webhook event if it’s none of [HANDLED_EVENT_TYPES] and
+/// `acknowledge_unhandled_events` is enabled, which is checked per request so that reloading the
+/// configuration takes effect. Otherwise, the request is rejected as not found, so that the
+/// reasons other routes rejected it for take precedence.
+///
+/// # Arguments
+/// - `state`: The shared state, including the configuration.
+fn with_unhandled_event_type(state: AppState)
+	-> impl warp::Filter<Extract = (String,), Error = warp::Rejection> + Clone
+{
+	use warp::Filter as _;
+
+	warp::header::<String>("x-github-event")
+		.and_then(move |event_type: String|
+		{
+			let acknowledge_unhandled_events = state.config().acknowledge_unhandled_events;
+
+			async move
 			{
-				Ok(created_issue) => created_issue,
-				Err(error) =>
+				let is_handled = HANDLED_EVENT_TYPES.iter()
+					.any(|handled_event_type| handled_event_type.eq_ignore_ascii_case(&event_type));
+
+				match acknowledge_unhandled_events && !is_handled
 				{
-					log::error!("could not notify repository creator about new branch protection \
-						rules set up for repository “{repository_name}”");
-					log::error!("{:?}", anyhow::Error::from(error));
-					return;
+					true => Ok(event_type),
+					false => Err(warp::reject::not_found()),
 				}
+			}
+		})
+}
+
+/// Request handler for valid branch protection rule events.
+///
+/// If configured, the branch protection rules of protected branches that are deleted manually are
+/// restored and/or the deletion is reported in an issue.
+///
+/// # Arguments
+/// - `payload`: The decoded webhook event payload.
+/// - `state`: The shared state, including the GitHub API client and the configuration.
+/// - `delivery_id`: The ID of the webhook delivery, if provided.
+async fn handle_branch_protection_rule_event(payload: BranchProtectionRuleEventPayload,
+	state: AppState, delivery_id: Option<String>)
+	-> Result<EventReply, std::convert::Infallible>
+{
+	use tracing::Instrument as _;
+
+	let config = state.config();
+
+	if let Err(outcome) = check_sender(&payload.sender, config.act_on_self,
+		state.github_api_client.app_bot_login().as_deref())
+	{
+		return Ok(outcome_reply(outcome, config.ignored_event_status_code()));
+	}
+
+	let target = match ProtectionTarget::from_branch_protection_rule_event(payload, &config)
+	{
+		Ok(target) => target,
+		Err(outcome) =>
+			return Ok(outcome_reply(outcome, config.ignored_event_status_code())),
+	};
+
+	let action = "handling deleted branch protection rule as configured";
+
+	if config.maintenance_mode
+	{
+		return Ok(maintenance_reply(action, &target));
+	}
+
+	let reply = deferred_event_reply(action, &config.acknowledgment, &target);
+	let span = webhook_event_span("branch_protection_rule", delivery_id.as_deref(), &target);
+
+	// Handle the deleted branch protection rule in a separate task so as to immediately
+	// acknowledge the webhook event without blocking
+	state.pending_events.clone().spawn(
+		async move
+		{
+			let _permit = state.concurrency_limiter.acquire(&target.organization_name).await;
+
+			if entered_maintenance_mode(&state, action, &target)
+			{
+				return;
+			}
+
+			let mut target = target;
+			let outcome = match consult_decision_hook(&state.config(), &mut target).await
+			{
+				Ok(()) => handle_deleted_protection(&state, &target).await,
+				Err(outcome) => outcome,
 			};
 
-			log::info!("created issue informing about branch protection: {}",
-				created_issue.html_url);
-		});
+			outcome.report(&state, &target).await;
+		}.instrument(span));
 
 	// Acknowledge the successful receipt of this webhook event as quickly as possible
-	let message = "creating branch protection rules and notifying creator of the default branch";
-	let response = warp::reply::json(&InfoResponse{info: message});
+	Ok(reply)
+}
 
-	Ok(warp::reply::with_status(response, warp::http::StatusCode::OK))
+/// Build the reply to a webhook event from the outcome of handling it.
+///
+/// # Arguments
+/// - `outcome`: The outcome of handling the webhook event.
+/// - `ignored_event_status`: The HTTP status code to respond with to events we don’t act on.
+fn outcome_reply(outcome: HandledOutcome, ignored_event_status: warp::http::StatusCode)
+	-> EventReply
+{
+	match outcome
+	{
+		HandledOutcome::Ignored(reason) =>
+		{
+			log::debug!("ignoring event: {reason}");
+			ignored_event_reply(&reason, ignored_event_status)
+		},
+		HandledOutcome::Protected{..} =>
+			accepted_event_reply("created branch protection rules and notified creator of the \
+				default branch"),
+		HandledOutcome::Proposed{..} =>
+			accepted_event_reply("proposed branch protection rules in a pull request"),
+		HandledOutcome::DriftReported{..} =>
+			accepted_event_reply("reported differences from the configured branch protection \
+				settings"),
+		HandledOutcome::DeletionHandled{..} =>
+			accepted_event_reply("handled deleted branch protection rule"),
+		HandledOutcome::DryRun{..} =>
+			accepted_event_reply("determined changes to branch protection rules in dry-run mode"),
+		HandledOutcome::Failed(error) =>
+		{
+			log::error!("{:?}", anyhow::Error::from(error));
+
+			EventReply::error(warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+				"internal server error")
+		},
+	}
+}
+
+/// Build the reply acknowledging a webhook event that is handled in the background, which includes
+/// the branch acted on so that it shows up in the delivery log of the GitHub App.
+///
+/// # Arguments
+/// - `default_message`: Human-readable information about how this event is handled, unless
+///   another message is configured.
+/// - `config`: The configuration of acknowledgments.
+/// - `target`: The branch acted on.
+fn deferred_event_reply(default_message: &str, config: &AcknowledgmentConfig,
+	target: &ProtectionTarget)
+	-> EventReply
+{
+	EventReply::info(warp::http::StatusCode::OK,
+		config.message.as_deref().unwrap_or(default_message))
+		.with_acknowledgment(&config.code, target)
+}
+
+/// Build the reply acknowledging a webhook event that isn’t acted on because maintenance mode is
+/// enabled, logging what would have been done instead.
+///
+/// # Arguments
+/// - `action`: Human-readable description of what would have been done.
+/// - `target`: The branch the webhook event asks us to act on.
+fn maintenance_reply(action: &str, target: &ProtectionTarget) -> EventReply
+{
+	log::info!("maintenance mode: not acting on branch “{}” in repository “{}/{}”, which would \
+		have meant {action}", target.branch_name, target.organization_name,
+		target.repository_name);
+
+	EventReply::info(warp::http::StatusCode::OK,
+		"not acting on webhook event, as maintenance mode is enabled")
+		.with_acknowledgment(MAINTENANCE_ACKNOWLEDGMENT_CODE, target)
+}
+
+/// Check whether maintenance mode was enabled by reloading the configuration while a webhook event
+/// that was acknowledged before was waiting to be handled, in which case it isn’t acted on either
+/// and what would have been done is only logged.
+///
+/// # Arguments
+/// - `state`: The shared state, including the configuration.
+/// - `action`: Human-readable information about how this event would have been handled.
+/// - `target`: The branch that would have been acted on.
+fn entered_maintenance_mode(state: &AppState, action: &str, target: &ProtectionTarget) -> bool
+{
+	let maintenance_mode = state.config().maintenance_mode;
+
+	if maintenance_mode
+	{
+		log::info!("maintenance mode: not acting on branch “{}” in repository “{}/{}” anymore, \
+			which would have meant {action}", target.branch_name, target.organization_name,
+			target.repository_name);
+	}
+
+	maintenance_mode
+}
+
+/// Build the reply acknowledging a webhook event we act on.
+///
+/// # Arguments
+/// - `message`: Human-readable information about how this event is handled.
+fn accepted_event_reply(message: &str) -> EventReply
+{
+	EventReply::info(warp::http::StatusCode::OK, message)
 }
 
 /// Request handler for all requests that were rejected previously.
 ///
 /// # Arguments
 /// - `error`: Reasons for why this request was rejected by all routes.
-async fn handle_rejection(error: warp::Rejection)
-	-> Result<impl warp::Reply, std::convert::Infallible>
+/// - `state`: The shared state, including the configuration and the metrics counting rejected
+///   webhook deliveries.
+async fn handle_rejection(error: warp::Rejection, state: AppState)
+	-> Result<EventReply, std::convert::Infallible>
 {
+	let metrics = &state.metrics;
+
 	let status_code;
 	let message;
 
@@ -178,32 +1066,60 @@ async fn handle_rejection(error: warp::Rejection)
 	{
 		status_code = warp::http::StatusCode::BAD_REQUEST;
 		message = "payload too large";
-	}
-	else if let Some(_) = error.find::<warp::reject::MissingHeader>()
-	{
-		status_code = warp::http::StatusCode::BAD_REQUEST;
-		message = "missing webhook event header";
-	}
-	// Don’t treat events that we don’t react to as errors and report a 200 OK instead
-	else if let Some(_) = error.find::<warp::reject::InvalidHeader>()
-	{
-		status_code = warp::http::StatusCode::OK;
-		message = "not listening to this webhook event";
+
+		metrics.record_rejection(RejectionStage::ContentLength);
 	}
 	else if let Some(crate::Error::DecodePayloadBody(_)) = error.find()
 	{
 		status_code = warp::http::StatusCode::BAD_REQUEST;
 		message = "malformed payload body";
+
+		metrics.record_rejection(RejectionStage::Decode);
 	}
 	else if let Some(crate::Error::MissingPayloadSignature) = error.find()
 	{
 		status_code = warp::http::StatusCode::BAD_REQUEST;
 		message = "missing payload signature";
+
+		metrics.record_rejection(RejectionStage::Signature);
 	}
 	else if let Some(crate::Error::InvalidPayloadSignature) = error.find()
 	{
 		status_code = warp::http::StatusCode::BAD_REQUEST;
 		message = "invalid payload signature";
+
+		metrics.record_rejection(RejectionStage::Signature);
+	}
+	else if let Some(crate::Error::SourceIpAddressNotAllowed(_)) = error.find()
+	{
+		status_code = warp::http::StatusCode::FORBIDDEN;
+		message = "source IP address not allowed";
+
+		metrics.record_rejection(RejectionStage::SourceIp);
+	}
+	// Errors that occurred while processing the request shouldn’t be masked by mismatching headers
+	else if let Some(error) = error.find::<crate::Error>()
+	{
+		status_code = warp::http::StatusCode::INTERNAL_SERVER_ERROR;
+		message = "internal server error";
+
+		log::error!("{:?}", error);
+	}
+	// As requests are matched against several routes, the rejections of routes listening for other
+	// webhook events are combined with the actual reason for rejecting a request. Hence, only check
+	// for mismatching headers after all other reasons
+	else if let Some(_) = error.find::<warp::reject::MissingHeader>()
+	{
+		status_code = warp::http::StatusCode::BAD_REQUEST;
+		message = "missing webhook event header";
+
+		metrics.record_rejection(RejectionStage::Header);
+	}
+	// Don’t treat events that we don’t react to as errors and report a success status instead
+	else if let Some(_) = error.find::<warp::reject::InvalidHeader>()
+	{
+		return Ok(ignored_event_reply("not listening to this webhook event",
+			state.config().ignored_event_status_code()));
 	}
 	// If users are able to trigger errors we did not anticipate, log the error chain so we can
 	// inspect this more closely later
@@ -215,27 +1131,37 @@ async fn handle_rejection(error: warp::Rejection)
 		log::error!("unhandled error: {:#?}", error);
 	}
 
-	let response = match status_code.is_success()
+	Ok(match status_code.is_success()
 	{
-		true => warp::reply::json(&InfoResponse{info: message}),
-		false => warp::reply::json(&ErrorResponse{error: message}),
-	};
-
-	Ok(warp::reply::with_status(response, status_code))
+		true => EventReply::info(status_code, message),
+		false => EventReply::error(status_code, message),
+	})
 }
 
-/// Response type acknowledging successfully handled webhook events (serialized to JSON).
-#[derive(serde::Serialize)]
-struct InfoResponse<'a>
+/// Build the reply to a webhook event we don’t act on. Only responses with the status code
+/// 200 OK include a body with an info message, as other success status codes such as
+/// 204 No Content are meant to be sent without a body.
+///
+/// # Arguments
+/// - `message`: Human-readable information about why this event was ignored.
+/// - `status_code`: The HTTP status code to respond with.
+fn ignored_event_reply(message: &str, status_code: warp::http::StatusCode) -> EventReply
 {
-	/// Info message with human-readable information about how this request was handled.
-	info: &'a str,
+	match status_code
+	{
+		warp::http::StatusCode::OK => EventReply::info(status_code, message),
+		_ => EventReply::empty(status_code),
+	}
 }
 
-/// Response type informing about errors while handling webhook events (serialized to JSON).
-#[derive(serde::Serialize)]
-struct ErrorResponse<'a>
-{
-	/// Error message with a human-readable explanation as to why this request failed.
-	error: &'a str,
-}
+/// How many branches the `retry-failed` command processes at the same time unless configured
+/// otherwise.
+const DEFAULT_RETRY_FAILED_CONCURRENCY: usize = 4;
+
+/// The webhook event types that have routes of their own, which the route acknowledging unhandled
+/// events needs to leave alone.
+const HANDLED_EVENT_TYPES: &[&str] = &["create", "push", "branch_protection_rule", "repository"];
+
+/// The acknowledgment code of webhook events that aren’t acted on because maintenance mode is
+/// enabled.
+const MAINTENANCE_ACKNOWLEDGMENT_CODE: &str = "maintenance";
@@ -0,0 +1,355 @@
+/// Metrics about the webhook events this service received, exposed in the Prometheus text format
+/// and optionally sent as StatsD metrics, too.
+///
+/// The metrics can safely be shared between threads, as cloning them only clones handles to the
+/// underlying data.
+#[derive(Clone)]
+pub struct Metrics
+{
+	#[doc(hidden)]
+	registry: prometheus::Registry,
+	#[doc(hidden)]
+	rejected_total: prometheus::IntCounterVec,
+	#[doc(hidden)]
+	events_total: prometheus::IntCounterVec,
+	#[doc(hidden)]
+	outcomes_total: prometheus::IntCounterVec,
+	#[doc(hidden)]
+	installation_suspended_total: prometheus::IntCounter,
+	#[doc(hidden)]
+	jwt_rejected_total: prometheus::IntCounter,
+	#[doc(hidden)]
+	time_to_protection_seconds: prometheus::Histogram,
+	#[doc(hidden)]
+	network_errors_total: prometheus::IntCounterVec,
+	#[doc(hidden)]
+	payload_size_bytes: prometheus::HistogramVec,
+	#[doc(hidden)]
+	github_api_request_duration_seconds: prometheus::Histogram,
+	#[doc(hidden)]
+	statsd_client: Option<std::sync::Arc<cadence::StatsdClient>>,
+}
+
+impl Default for Metrics
+{
+	/// Create and register all metrics, starting with all counters at zero.
+	fn default() -> Self
+	{
+		let registry = prometheus::Registry::new();
+
+		let rejected_total = prometheus::IntCounterVec::new(
+			prometheus::Opts::new("rejected_total",
+				"Number of webhook deliveries rejected, by the stage at which they were rejected"),
+			&["stage"])
+			.expect("this call is infallible because the metric definition is well-formed");
+		registry.register(Box::new(rejected_total.clone()))
+			.expect("this call is infallible because each metric is registered only once");
+
+		let events_total = prometheus::IntCounterVec::new(
+			prometheus::Opts::new("events_total",
+				"Number of webhook deliveries with valid signatures, by event type"),
+			&["event_type"])
+			.expect("this call is infallible because the metric definition is well-formed");
+		registry.register(Box::new(events_total.clone()))
+			.expect("this call is infallible because each metric is registered only once");
+
+		let outcomes_total = prometheus::IntCounterVec::new(
+			prometheus::Opts::new("outcomes_total",
+				"Number of handled webhook events, by outcome (such as protected or failed)"),
+			&["outcome"])
+			.expect("this call is infallible because the metric definition is well-formed");
+		registry.register(Box::new(outcomes_total.clone()))
+			.expect("this call is infallible because each metric is registered only once");
+
+		let installation_suspended_total = prometheus::IntCounter::new(
+			"installation_suspended_total",
+			"Number of actions that failed because the GitHub App installation is suspended")
+			.expect("this call is infallible because the metric definition is well-formed");
+		registry.register(Box::new(installation_suspended_total.clone()))
+			.expect("this call is infallible because each metric is registered only once");
+
+		let jwt_rejected_total = prometheus::IntCounter::new("jwt_rejected_total",
+			"Number of actions that failed because GitHub rejected the JWT, usually due to clock \
+				skew")
+			.expect("this call is infallible because the metric definition is well-formed");
+		registry.register(Box::new(jwt_rejected_total.clone()))
+			.expect("this call is infallible because each metric is registered only once");
+
+		// The buckets cover immediate protection as well as configured delays and lengthy retries
+		let time_to_protection_seconds = prometheus::Histogram::with_opts(
+			prometheus::HistogramOpts::new("time_to_protection_seconds",
+				"Time from receiving a webhook event until the branch it asks us to protect was \
+					protected")
+				.buckets(vec![0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0]))
+			.expect("this call is infallible because the metric definition is well-formed");
+		registry.register(Box::new(time_to_protection_seconds.clone()))
+			.expect("this call is infallible because each metric is registered only once");
+
+		let network_errors_total = prometheus::IntCounterVec::new(
+			prometheus::Opts::new("network_errors_total",
+				"Number of actions that failed because the GitHub API server couldn’t be reached, \
+					by the kind of network error"),
+			&["kind"])
+			.expect("this call is infallible because the metric definition is well-formed");
+		registry.register(Box::new(network_errors_total.clone()))
+			.expect("this call is infallible because each metric is registered only once");
+
+		// The buckets range from small ref creation events to the largest payloads GitHub sends
+		let payload_size_bytes = prometheus::HistogramVec::new(
+			prometheus::HistogramOpts::new("payload_size_bytes",
+				"Size of the payloads of webhook deliveries with valid signatures, by event type")
+				.buckets(prometheus::exponential_buckets(1024.0, 4.0, 9)
+					.expect("this call is infallible because the bucket definition is valid")),
+			&["event_type"])
+			.expect("this call is infallible because the metric definition is well-formed");
+		registry.register(Box::new(payload_size_bytes.clone()))
+			.expect("this call is infallible because each metric is registered only once");
+
+		// The buckets range from fast single requests to requests retried for several minutes
+		let github_api_request_duration_seconds = prometheus::Histogram::with_opts(
+			prometheus::HistogramOpts::new("github_api_request_duration_seconds",
+				"Time taken by GitHub API requests, including retries and access token renewals")
+				.buckets(vec![0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 300.0]))
+			.expect("this call is infallible because the metric definition is well-formed");
+		registry.register(Box::new(github_api_request_duration_seconds.clone()))
+			.expect("this call is infallible because each metric is registered only once");
+
+		// Initialize the counters of all stages so that they’re exported before the first rejection
+		for stage in RejectionStage::ALL
+		{
+			rejected_total.with_label_values(&[stage.label()]);
+		}
+
+		Self
+		{
+			registry,
+			rejected_total,
+			events_total,
+			outcomes_total,
+			installation_suspended_total,
+			jwt_rejected_total,
+			time_to_protection_seconds,
+			network_errors_total,
+			payload_size_bytes,
+			github_api_request_duration_seconds,
+			statsd_client: None,
+		}
+	}
+}
+
+impl Metrics
+{
+	/// Additionally send all metrics as StatsD metrics via UDP, with labels sent as tags. Sending
+	/// is non-blocking, and failures to send metrics are logged but otherwise ignored.
+	///
+	/// # Arguments
+	/// - `address`: The address of the StatsD server (example: `localhost:8125`).
+	pub fn with_statsd(mut self, address: &str) -> Result<Self, crate::Error>
+	{
+		let socket = std::net::UdpSocket::bind("0.0.0.0:0").map_err(crate::Error::SetUpStatsd)?;
+		socket.set_nonblocking(true).map_err(crate::Error::SetUpStatsd)?;
+
+		let sink = cadence::UdpMetricSink::from(address, socket)
+			.map_err(crate::Error::ResolveStatsdAddress)?;
+
+		let statsd_client = cadence::StatsdClient::builder(STATSD_PREFIX, sink)
+			.with_error_handler(|error| log::warn!("could not send StatsD metric: {error}"))
+			.build();
+
+		log::info!("sending StatsD metrics to “{address}”");
+
+		self.statsd_client = Some(std::sync::Arc::new(statsd_client));
+
+		Ok(self)
+	}
+
+	/// Count a webhook delivery that was rejected at a given stage.
+	pub fn record_rejection(&self, stage: RejectionStage)
+	{
+		self.rejected_total.with_label_values(&[stage.label()]).inc();
+		self.increment_statsd_counter("rejected_total", Some(("stage", stage.label())));
+	}
+
+	/// Count a webhook delivery with a valid signature.
+	///
+	/// # Arguments
+	/// - `event_type`: The type of the webhook event (example: `create`).
+	pub fn record_event(&self, event_type: &str)
+	{
+		self.events_total.with_label_values(&[event_type]).inc();
+		self.increment_statsd_counter("events_total", Some(("event_type", event_type)));
+	}
+
+	/// Count the outcome of handling a webhook event.
+	pub fn record_outcome(&self, outcome: &crate::HandledOutcome)
+	{
+		self.outcomes_total.with_label_values(&[outcome.name()]).inc();
+		self.increment_statsd_counter("outcomes_total", Some(("outcome", outcome.name())));
+
+		match outcome
+		{
+			crate::HandledOutcome::Failed(crate::Error::InstallationSuspended{..}) =>
+			{
+				self.installation_suspended_total.inc();
+				self.increment_statsd_counter("installation_suspended_total", None);
+			},
+			crate::HandledOutcome::Failed(crate::Error::JwtRejected{..}) =>
+			{
+				self.jwt_rejected_total.inc();
+				self.increment_statsd_counter("jwt_rejected_total", None);
+			},
+			crate::HandledOutcome::Failed(error) =>
+			{
+				// Network errors may be wrapped, for example, when obtaining an access token failed
+				let network_error_kind = std::iter::successors(
+					Some(error as &dyn std::error::Error), |error| error.source())
+					.filter_map(|error| error.downcast_ref::<crate::Error>())
+					.find_map(crate::Error::network_error_kind);
+
+				if let Some(kind) = network_error_kind
+				{
+					self.network_errors_total.with_label_values(&[kind]).inc();
+					self.increment_statsd_counter("network_errors_total", Some(("kind", kind)));
+				}
+			},
+			_ => (),
+		}
+	}
+
+	/// Record how long it took from receiving a webhook event until the branch it asks us to
+	/// protect was protected, including any configured delay and retries.
+	pub fn record_time_to_protection(&self, duration: std::time::Duration)
+	{
+		self.time_to_protection_seconds.observe(duration.as_secs_f64());
+
+		if let Some(statsd_client) = &self.statsd_client
+		{
+			use cadence::Timed as _;
+
+			statsd_client.time_with_tags("time_to_protection", duration).send();
+		}
+	}
+
+	/// Record the size of the payload of a webhook delivery by its event type, which helps choose
+	/// `max_payload_size_bytes` and `max_payload_size_bytes_by_event`.
+	pub fn record_payload_size(&self, event_type: &str, size: usize)
+	{
+		self.payload_size_bytes.with_label_values(&[event_type]).observe(size as f64);
+
+		if let Some(statsd_client) = &self.statsd_client
+		{
+			use cadence::Histogrammed as _;
+
+			statsd_client.histogram_with_tags("payload_size_bytes", size as u64)
+				.with_tag("event_type", event_type)
+				.send();
+		}
+	}
+
+	/// Record how long a GitHub API request took, including retries and access token renewals.
+	pub fn record_github_api_request_duration(&self, duration: std::time::Duration)
+	{
+		self.github_api_request_duration_seconds.observe(duration.as_secs_f64());
+
+		if let Some(statsd_client) = &self.statsd_client
+		{
+			use cadence::Timed as _;
+
+			statsd_client.time_with_tags("github_api_request_duration", duration).send();
+		}
+	}
+
+	/// Increment a counter in StatsD, if enabled.
+	///
+	/// # Arguments
+	/// - `name`: The name of the counter, which matches the Prometheus metric.
+	/// - `tag`: The label of the Prometheus metric as a tag, if any.
+	#[doc(hidden)]
+	fn increment_statsd_counter(&self, name: &str, tag: Option<(&str, &str)>)
+	{
+		use cadence::Counted as _;
+
+		let statsd_client = match &self.statsd_client
+		{
+			Some(statsd_client) => statsd_client,
+			None => return,
+		};
+
+		let mut metric = statsd_client.count_with_tags(name, 1);
+
+		if let Some((key, value)) = tag
+		{
+			metric = metric.with_tag(key, value);
+		}
+
+		metric.send();
+	}
+
+	/// Render all metrics in the Prometheus text format.
+	pub fn render(&self) -> String
+	{
+		use prometheus::Encoder as _;
+
+		let mut buffer = vec![];
+
+		prometheus::TextEncoder::new().encode(&self.registry.gather(), &mut buffer)
+			.expect("this call is infallible because writing to a vector doesn’t fail");
+
+		String::from_utf8(buffer)
+			.expect("this call is infallible because the text format is valid UTF-8")
+	}
+}
+
+/// The stages at which webhook deliveries may be rejected.
+#[derive(Clone, Copy, Debug)]
+pub enum RejectionStage
+{
+	/// The source IP address isn’t on the allow list.
+	SourceIp,
+	/// The payload exceeds the content length limit.
+	ContentLength,
+	/// The webhook event header is missing.
+	Header,
+	/// The payload signature is missing or invalid.
+	Signature,
+	/// The payload couldn’t be decoded.
+	Decode,
+}
+
+impl RejectionStage
+{
+	#[doc(hidden)]
+	const ALL: [Self; 5] =
+		[Self::SourceIp, Self::ContentLength, Self::Header, Self::Signature, Self::Decode];
+
+	/// The value of the `stage` label of this stage.
+	fn label(self) -> &'static str
+	{
+		match self
+		{
+			Self::SourceIp => "source_ip",
+			Self::ContentLength => "content_length",
+			Self::Header => "header",
+			Self::Signature => "signature",
+			Self::Decode => "decode",
+		}
+	}
+}
+
+/// The prefix of all StatsD metrics, separated from their names by a dot.
+const STATSD_PREFIX: &str = "branch_autoprotector";
+
+/// [warp] route serving all metrics in the Prometheus text format at `/metrics`.
+///
+/// # Arguments
+/// - `metrics`: A handle to the metrics.
+pub fn metrics_route(metrics: Metrics)
+	-> impl warp::Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone
+{
+	use warp::Filter as _;
+
+	warp::path!("metrics")
+		.and(warp::get())
+		.map(move || warp::reply::with_header(metrics.render(), "content-type",
+			prometheus::TEXT_FORMAT))
+}
@@ -8,6 +8,16 @@ pub struct User
 	// We don’t need the other fields, so ignore them
 }
 
+/// Partial organization data model as included in webhook event payloads.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Organization
+{
+	/// The organization’s handle.
+	pub login: String,
+	// We don’t need the other fields, so ignore them
+}
+
 /// Partial repository data model as returned in responses from the GitHub API.
 #[derive(Debug, serde::Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -17,9 +27,48 @@ pub struct Repository
 	pub name: String,
 	/// Handle of the user or organization owning the repository.
 	pub owner: User,
+	/// The name of the repository’s default branch (not included in all responses).
+	#[serde(default)]
+	pub default_branch: Option<String>,
+	/// Whether the repository is a fork of another repository.
+	#[serde(default)]
+	pub fork: bool,
+	/// When the repository was last pushed to, if ever (not included in all responses).
+	#[serde(default, deserialize_with = "deserialize_payload_timestamp")]
+	pub pushed_at: Option<chrono::DateTime<chrono::Utc>>,
 	// We don’t need the other fields, so ignore them
 }
 
+/// Timestamp as included in webhook event payloads, which GitHub encodes either as a Unix timestamp
+/// or in RFC 3339 format depending on the event type.
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum PayloadTimestamp
+{
+	UnixSeconds(i64),
+	Rfc3339(chrono::DateTime<chrono::Utc>),
+}
+
+#[doc(hidden)]
+fn deserialize_payload_timestamp<'de, D>(deserializer: D)
+	-> Result<Option<chrono::DateTime<chrono::Utc>>, D::Error>
+where
+	D: serde::Deserializer<'de>,
+{
+	use serde::de::Error as _;
+	use serde::Deserialize as _;
+
+	match Option::<PayloadTimestamp>::deserialize(deserializer)?
+	{
+		Some(PayloadTimestamp::UnixSeconds(seconds)) =>
+			chrono::DateTime::from_timestamp(seconds, 0)
+				.map(Some)
+				.ok_or_else(|| D::Error::custom(format!("timestamp {seconds} is out of range"))),
+		Some(PayloadTimestamp::Rfc3339(timestamp)) => Ok(Some(timestamp)),
+		None => Ok(None),
+	}
+}
+
 /// Type of a Git ref object.
 #[derive(Debug, Eq, PartialEq, serde::Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -38,9 +87,91 @@ pub struct RefCreationEventPayload
 	#[serde(rename = "ref")]
 	pub ref_: String,
 	/// The type of Git ref object created in the repository.
+	// Webhook proxies in enterprise setups sometimes reshape field names to camel case, so accept
+	// that casing, too
+	#[serde(alias = "refType")]
 	pub ref_type: RefType,
-	/// The name of the repository’s default branch (usually `main`).
-	pub master_branch: String,
+	/// The name of the repository’s default branch (usually `main`), which some webhook proxies
+	/// omit.
+	#[serde(alias = "masterBranch", default)]
+	pub master_branch: Option<String>,
+	/// The name of the repository’s default branch under the name GitHub might rename
+	/// `master_branch` to, matching the terminology of its newer payloads and APIs.
+	// Payloads in transition might include both fields, so this is kept separately rather than as
+	// an alias, which would fail decoding such payloads as a duplicate field
+	#[serde(alias = "defaultBranch", default)]
+	pub default_branch: Option<String>,
+	/// The repository for which this event is reported.
+	pub repository: Repository,
+	/// The organization owning the repository (only included for repositories owned by
+	/// organizations).
+	#[serde(default)]
+	pub organization: Option<Organization>,
+	/// Record of the user causing this event.
+	pub sender: User,
+	// We don’t need the other fields, so ignore them
+}
+
+impl RefCreationEventPayload
+{
+	/// The name of the repository’s default branch as included in the payload, if at all, which is
+	/// preferably taken from `master_branch`, then from `default_branch`, and finally from the
+	/// repository.
+	pub fn default_branch_name(&self) -> Option<&str>
+	{
+		self.master_branch.as_deref()
+			.or(self.default_branch.as_deref())
+			.or(self.repository.default_branch.as_deref())
+	}
+}
+
+/// Webhook event payload for push events as provided by the GitHub server.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PushEventPayload
+{
+	/// The full Git ref that was pushed (example: `refs/heads/main`).
+	#[serde(rename = "ref")]
+	pub ref_: String,
+	/// Whether this push created the ref.
+	#[serde(default)]
+	pub created: bool,
+	/// The repository for which this event is reported.
+	pub repository: Repository,
+	/// Record of the user causing this event.
+	pub sender: User,
+	// We don’t need the other fields, so ignore them
+}
+
+/// Action performed on a branch protection rule.
+#[derive(Debug, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BranchProtectionRuleAction
+{
+	Created,
+	Edited,
+	Deleted,
+}
+
+/// Partial branch protection rule data model as included in webhook event payloads.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct BranchProtectionRule
+{
+	/// The name of the branch or the branch name pattern the rule applies to.
+	pub name: String,
+	// We don’t need the other fields, so ignore them
+}
+
+/// Webhook event payload for branch protection rule events as provided by the GitHub server.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct BranchProtectionRuleEventPayload
+{
+	/// The action performed on the branch protection rule.
+	pub action: BranchProtectionRuleAction,
+	/// The branch protection rule the action was performed on.
+	pub rule: BranchProtectionRule,
 	/// The repository for which this event is reported.
 	pub repository: Repository,
 	/// Record of the user causing this event.
@@ -48,13 +179,76 @@ pub struct RefCreationEventPayload
 	// We don’t need the other fields, so ignore them
 }
 
+/// Action performed on a repository.
+#[derive(Debug, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepositoryAction
+{
+	Transferred,
+	/// Any other action, such as `created` or `renamed`, which we don’t act on.
+	#[serde(other)]
+	Other,
+}
+
+/// Changes reported in repository events.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RepositoryChanges
+{
+	/// The change of the repository’s owner (only included for transferred repositories).
+	#[serde(default)]
+	pub owner: Option<RepositoryOwnerChange>,
+	// We don’t need the other fields, so ignore them
+}
+
+/// The change of a repository’s owner.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RepositoryOwnerChange
+{
+	/// The previous owner of the repository.
+	pub from: RepositoryOwner,
+}
+
+/// Owner of a repository as included in repository events, which is either an organization or a
+/// user.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RepositoryOwner
+{
+	/// The owning organization, if owned by an organization.
+	#[serde(default)]
+	pub organization: Option<Organization>,
+	/// The owning user, if owned by a user.
+	#[serde(default)]
+	pub user: Option<User>,
+}
+
+/// Webhook event payload for repository events as provided by the GitHub server.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RepositoryEventPayload
+{
+	/// The action performed on the repository.
+	pub action: RepositoryAction,
+	/// The changes made to the repository (only included for some actions).
+	#[serde(default)]
+	pub changes: Option<RepositoryChanges>,
+	/// The repository for which this event is reported, whose owner is the new owner for
+	/// transferred repositories.
+	pub repository: Repository,
+	/// Record of the user causing this event.
+	pub sender: User,
+	// We don’t need the other fields, so ignore them
+}
+
 /// Partial data model for the parameters needed to make a GitHub API request to protect a branch.
 #[derive(Debug, serde::Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct ProtectBranchRequest
 {
-	/// Field currently unsupported, so leave this at `None`.
-	pub required_status_checks: Option<UnsupportedField>,
+	/// Require status checks to pass before merging. Set to `None` to disable.
+	pub required_status_checks: Option<RequiredStatusChecks>,
 	/// Enforce all configured restrictions for administrators. Set to `Some(true)` to enforce
 	/// required status checks for repository administrators. Set to `None` to disable.
 	pub enforce_admins: Option<bool>,
@@ -66,12 +260,29 @@ pub struct ProtectBranchRequest
 	// We don’t need to set the other optional fields, so ignore them
 }
 
+/// Partial data model for the parameters needed to make a GitHub API request to protect a branch.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RequiredStatusChecks
+{
+	/// Require branches to be up to date with the base branch before merging.
+	pub strict: bool,
+	/// The names of the status checks that need to pass before merging.
+	pub contexts: Vec<String>,
+}
+
 /// Partial data model for the parameters needed to make a GitHub API request to protect a branch.
 #[derive(Debug, serde::Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct RequiredPullRequestReviews
 {
-	// We currently don’t need any of the optional fields, so ignore them
+	/// The number of approving reviews required before merging (between 0 and 6).
+	pub required_approving_review_count: u8,
+	/// Dismiss approving reviews when new commits are pushed.
+	pub dismiss_stale_reviews: bool,
+	/// Require an approving review by a code owner.
+	pub require_code_owner_reviews: bool,
+	// We don’t need to set the other optional fields, so ignore them
 }
 
 /// Partial data model for the parameters needed to make a GitHub API request to create a new issue.
@@ -93,9 +304,562 @@ pub struct CreateIssueResponse
 {
 	/// User-facing URL of the created issue.
 	pub html_url: url::Url,
+	/// The global node ID of the created issue, as used by the GraphQL API.
+	pub node_id: String,
+	// We don’t need the other fields, so ignore them
+}
+
+/// Partial data model of a pull request as listed by the GitHub API.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PullRequest
+{
+	/// The number of the pull request.
+	pub number: u64,
+	// We don’t need the other fields, so ignore them
+}
+
+/// Partial data model for the parameters needed to make a GitHub API request to comment on a
+/// commit.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct CreateCommitCommentRequest<'a>
+{
+	/// The contents of the comment.
+	pub body: &'a str,
+	// We don’t need to set the other optional fields, so ignore them
+}
+
+/// Partial data model for the response of the GitHub API to a request to comment on a commit.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct CreateCommitCommentResponse
+{
+	/// User-facing URL of the created comment.
+	pub html_url: url::Url,
+	// We don’t need the other fields, so ignore them
+}
+
+/// Partial data model for the parameters needed to make a GitHub API request to update an issue.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct UpdateIssueRequest<'a>
+{
+	/// The new contents of the issue, if they should be changed.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub body: Option<&'a str>,
+	/// The new state of the issue (`open` or `closed`), if it should be changed.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub state: Option<&'a str>,
+	// We don’t need to update the other fields, so ignore them
+}
+
+/// Partial issue data model as returned by the GitHub API.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Issue
+{
+	/// The number of the issue within its repository.
+	pub number: u64,
+	/// User-facing URL of the issue.
+	pub html_url: url::Url,
+	/// The contents of the issue, if any.
+	#[serde(default)]
+	pub body: Option<String>,
+	/// The user who opened the issue, if included.
+	#[serde(default)]
+	pub user: Option<crate::User>,
+	// We don’t need the other fields, so ignore them
+}
+
+/// Partial data model for the response of the GitHub API to a request to search issues.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct SearchIssuesResponse
+{
+	/// The issues and pull requests matching the query.
+	pub items: Vec<Issue>,
+	// We don’t need the other fields, so ignore them
+}
+
+/// Partial repository data model as returned by the GitHub GraphQL API, including the discussion
+/// categories.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscussionRepositoryResponse
+{
+	/// The global node ID of the repository.
+	pub id: String,
+	/// Whether discussions are enabled for the repository.
+	pub has_discussions_enabled: bool,
+	/// The first discussion categories of the repository.
+	pub discussion_categories: DiscussionCategoriesResponse,
+}
+
+/// Connection of discussion categories as returned by the GitHub GraphQL API.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscussionCategoriesResponse
+{
+	/// The discussion categories.
+	pub nodes: Vec<DiscussionCategory>,
+}
+
+/// Partial discussion category data model as returned by the GitHub GraphQL API.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscussionCategory
+{
+	/// The global node ID of the discussion category.
+	pub id: String,
+	/// The name of the discussion category.
+	pub name: String,
+}
+
+/// Partial discussion data model as returned by the GitHub GraphQL API.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Discussion
+{
+	/// User-facing URL of the discussion.
+	pub url: url::Url,
+	// We don’t need the other fields, so ignore them
+}
+
+/// Partial data model for the response of the GitHub API to a request to get the protection
+/// settings of a branch.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct BranchProtectionResponse
+{
+	/// Required status checks, if enabled.
+	pub required_status_checks: Option<RequiredStatusChecksResponse>,
+	/// Whether the protection settings are enforced for administrators, too.
+	pub enforce_admins: Option<EnabledSettingResponse>,
+	/// Required pull request reviews, if enabled.
+	pub required_pull_request_reviews: Option<RequiredPullRequestReviewsResponse>,
+	/// Restrictions of who can push to the branch, if enabled.
+	pub restrictions: Option<BranchRestrictionsResponse>,
+	/// Whether force pushes to the branch are allowed.
+	pub allow_force_pushes: Option<EnabledSettingResponse>,
+	/// Whether the branch may be deleted.
+	pub allow_deletions: Option<EnabledSettingResponse>,
+	// We don’t need the other fields, so ignore them
+}
+
+/// Data model for branch protection settings that can only be enabled or disabled.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct EnabledSettingResponse
+{
+	/// Whether the setting is enabled.
+	pub enabled: bool,
+}
+
+/// Partial data model for the required status checks of a protected branch.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RequiredStatusChecksResponse
+{
+	/// Whether branches need to be up to date with the base branch before merging.
+	pub strict: bool,
+	/// The names of the status checks that need to pass before merging.
+	#[serde(default)]
+	pub contexts: Vec<String>,
+	// We don’t need the other fields, so ignore them
+}
+
+/// Partial data model for the required pull request reviews of a protected branch.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RequiredPullRequestReviewsResponse
+{
+	/// The number of approving reviews required before merging.
+	#[serde(default)]
+	pub required_approving_review_count: Option<u32>,
+	/// Whether approving reviews are dismissed when new commits are pushed.
+	#[serde(default)]
+	pub dismiss_stale_reviews: bool,
+	/// Whether code owners need to review pull requests.
+	#[serde(default)]
+	pub require_code_owner_reviews: bool,
+	// We don’t need the other fields, so ignore them
+}
+
+/// Partial data model for the restrictions of who can push to a protected branch.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct BranchRestrictionsResponse
+{
+	/// The users allowed to push to the branch.
+	#[serde(default)]
+	pub users: Vec<User>,
+	/// The teams allowed to push to the branch.
+	#[serde(default)]
+	pub teams: Vec<Team>,
+	// We don’t need the other fields, so ignore them
+}
+
+/// Partial team data model as returned in responses from the GitHub API.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Team
+{
+	/// The team’s handle.
+	pub slug: String,
+	// We don’t need the other fields, so ignore them
+}
+
+/// Partial branch data model as returned in responses from the GitHub API.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Branch
+{
+	/// The name of the branch.
+	pub name: String,
+	/// Whether the branch is protected.
+	#[serde(default)]
+	pub protected: bool,
+	/// The commit the branch points to (not included in all responses).
+	#[serde(default)]
+	pub commit: Option<BranchCommit>,
+	// We don’t need the other fields, so ignore them
+}
+
+/// Partial data model for the commit a branch points to.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct BranchCommit
+{
+	/// The SHA of the commit.
+	pub sha: String,
+	// We don’t need the other fields, so ignore them
+}
+
+/// Partial data model for the parameters needed to make a GitHub API request to create a completed
+/// check run.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct CreateCheckRunRequest<'a>
+{
+	/// The name of the check.
+	pub name: &'a str,
+	/// The SHA of the commit to report the check for.
+	pub head_sha: &'a str,
+	/// The status of the check run, which is always `completed` here.
+	pub status: &'a str,
+	/// The conclusion of the check run (example: `success`).
+	pub conclusion: &'a str,
+	/// The details shown with the check run.
+	pub output: CheckRunOutput<'a>,
+	// We don’t need to set the other optional fields, so ignore them
+}
+
+/// Partial data model for the details shown with a check run.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct CheckRunOutput<'a>
+{
+	/// The title of the check run.
+	pub title: &'a str,
+	/// The summary of the check run in Markdown format.
+	pub summary: &'a str,
+}
+
+/// Partial data model for the response of the GitHub API to a request to create a check run.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct CreateCheckRunResponse
+{
+	/// User-facing URL of the created check run.
+	pub html_url: url::Url,
+	// We don’t need the other fields, so ignore them
+}
+
+/// Data model for the response of the GitHub API to a request to list the public keys GitHub signs
+/// payloads with.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PublicKeysResponse
+{
+	/// The currently and previously used public keys.
+	pub public_keys: Vec<PublicKeyResponse>,
+}
+
+/// Partial data model of a public key GitHub signs payloads with.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PublicKeyResponse
+{
+	/// The identifier of the key, which is included with signatures created with it.
+	pub key_identifier: String,
+	/// The PEM-encoded public key.
+	pub key: String,
+	// We don’t need the other fields, so ignore them
+}
+
+/// Partial data model for the response of the GitHub API to a request to get the contents of a
+/// file.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct FileContentsResponse
+{
+	/// The contents of the file, encoded as described by `encoding`.
+	pub content: String,
+	/// The encoding of the contents, which is `base64` for files up to 1 MB.
+	pub encoding: String,
+	/// The SHA of the file’s blob, which is needed to update the file.
+	pub sha: String,
+	// We don’t need the other fields, so ignore them
+}
+
+/// Partial data model for the parameters needed to make a GitHub API request to create or update a
+/// file in a branch.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PutFileContentsRequest<'a>
+{
+	/// The commit message.
+	pub message: &'a str,
+	/// The new contents of the file, encoded in Base64.
+	pub content: String,
+	/// The branch to commit to.
+	pub branch: &'a str,
+	/// The SHA of the blob of the file being replaced, which is required for updating a file.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub sha: Option<&'a str>,
+}
+
+/// Data model for the parameters needed to make a GitHub API request to create a reference, such
+/// as a branch.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct CreateReferenceRequest<'a>
+{
+	/// The fully qualified name of the reference (example: `refs/heads/main`).
+	#[serde(rename = "ref")]
+	pub ref_: &'a str,
+	/// The SHA of the commit the reference points to.
+	pub sha: &'a str,
+}
+
+/// Partial data model for the parameters needed to make a GitHub API request to open a pull
+/// request.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct CreatePullRequestRequest<'a>
+{
+	/// The title of the pull request.
+	pub title: &'a str,
+	/// The name of the branch containing the changes.
+	pub head: &'a str,
+	/// The name of the branch the changes are to be merged into.
+	pub base: &'a str,
+	/// The description of the pull request in Markdown format.
+	pub body: &'a str,
+	// We don’t need to set the other optional fields, so ignore them
+}
+
+/// Partial data model for the response of the GitHub API to a request to open a pull request.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct CreatePullRequestResponse
+{
+	/// User-facing URL of the opened pull request.
+	pub html_url: url::Url,
 	// We don’t need the other fields, so ignore them
 }
 
+/// Partial data model of a repository ruleset as imported and exported by GitHub, which is
+/// committed as a file by [crate::open_ruleset_pull_request].
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Ruleset
+{
+	/// The name of the ruleset.
+	pub name: String,
+	/// What the ruleset applies to, which is always `branch` here.
+	pub target: &'static str,
+	/// Whether the ruleset is enforced, which is always `active` here.
+	pub enforcement: &'static str,
+	/// Which branches the ruleset applies to.
+	pub conditions: RulesetConditions,
+	/// The rules enforced for these branches.
+	pub rules: Vec<RulesetRule>,
+	/// Who may bypass the rules.
+	pub bypass_actors: Vec<RulesetBypassActor>,
+}
+
+/// Data model of the conditions of a ruleset.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RulesetConditions
+{
+	/// The references the ruleset applies to.
+	pub ref_name: RulesetRefNameCondition,
+}
+
+/// Data model of the references a ruleset applies to.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RulesetRefNameCondition
+{
+	/// The fully qualified names of the references the ruleset applies to.
+	pub include: Vec<String>,
+	/// The fully qualified names of the references excluded from the ruleset.
+	pub exclude: Vec<String>,
+}
+
+/// Partial data model of a rule of a ruleset.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case", tag = "type", content = "parameters")]
+pub enum RulesetRule
+{
+	/// Prevent deleting branches.
+	Deletion,
+	/// Prevent force pushes.
+	NonFastForward,
+	/// Require pull requests before merging.
+	PullRequest(RulesetPullRequestParameters),
+	/// Require status checks to pass before merging.
+	RequiredStatusChecks(RulesetRequiredStatusChecksParameters),
+}
+
+/// Data model of the parameters of a ruleset rule requiring pull requests.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RulesetPullRequestParameters
+{
+	/// The number of approving reviews required before merging.
+	pub required_approving_review_count: u8,
+	/// Dismiss approving reviews when new commits are pushed.
+	pub dismiss_stale_reviews_on_push: bool,
+	/// Require an approving review by a code owner.
+	pub require_code_owner_review: bool,
+	/// Require the most recent push to be approved by someone else, which isn’t configurable here.
+	pub require_last_push_approval: bool,
+	/// Require all review threads to be resolved, which isn’t configurable here.
+	pub required_review_thread_resolution: bool,
+}
+
+/// Data model of the parameters of a ruleset rule requiring status checks.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RulesetRequiredStatusChecksParameters
+{
+	/// Require branches to be up to date with the base branch before merging.
+	pub strict_required_status_checks_policy: bool,
+	/// The status checks that need to pass before merging.
+	pub required_status_checks: Vec<RulesetStatusCheck>,
+}
+
+/// Partial data model of a status check required by a ruleset.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RulesetStatusCheck
+{
+	/// The name of the status check.
+	pub context: String,
+}
+
+/// Data model of an actor who may bypass the rules of a ruleset.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RulesetBypassActor
+{
+	/// The ID of the actor, such as the ID of a repository role.
+	pub actor_id: u64,
+	/// The kind of actor (example: `RepositoryRole`).
+	pub actor_type: &'static str,
+	/// When the actor may bypass the rules (example: `always`).
+	pub bypass_mode: &'static str,
+}
+
+/// Data model for the response of the GitHub API to a request to list the topics of a repository.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RepositoryTopicsResponse
+{
+	/// The names of all topics the repository is tagged with.
+	pub names: Vec<String>,
+}
+
+/// Partial data model for the response of the GitHub API to a request to get meta information
+/// about GitHub.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct GitHubMetaResponse
+{
+	/// The IP address ranges from which GitHub delivers webhook events.
+	#[serde(default)]
+	pub hooks: Vec<ipnet::IpNet>,
+	// We don’t need the other fields, so ignore them
+}
+
+/// Partial data model for the response of the GitHub API to a request to get the rate limit
+/// status.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RateLimitResponse
+{
+	/// The rate limit status per category of requests.
+	pub resources: RateLimit,
+	// We don’t need the other fields, so ignore them
+}
+
+/// The rate limit status of the categories of GitHub API requests relevant to this service.
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RateLimit
+{
+	/// The rate limit of most REST API requests.
+	pub core: RateLimitStatus,
+	/// The rate limit of search requests.
+	pub search: RateLimitStatus,
+	// We don’t need the other categories, so ignore them
+}
+
+/// The rate limit status of a category of GitHub API requests.
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RateLimitStatus
+{
+	/// The maximum number of requests per hour.
+	pub limit: u64,
+	/// The number of requests remaining until the rate limit is reset.
+	pub remaining: u64,
+	/// When the rate limit is reset (encoded as a Unix timestamp).
+	#[serde(with = "chrono::serde::ts_seconds")]
+	pub reset: chrono::DateTime<chrono::Utc>,
+}
+
+/// Partial data model for the response of the GitHub API to a request to get the authenticated
+/// GitHub App itself.
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct AppMetadata
+{
+	/// The slug of the GitHub App (example: `branch-autoprotector`).
+	pub slug: String,
+	/// The permissions granted to the GitHub App by resource (example: `administration`) and level
+	/// (example: `write`).
+	#[serde(default)]
+	pub permissions: std::collections::BTreeMap<String, String>,
+	/// The webhook events the GitHub App is subscribed to (example: `create`).
+	#[serde(default)]
+	pub events: Vec<String>,
+	// We don’t need the other fields, so ignore them
+}
+
+impl AppMetadata
+{
+	/// The login of the bot user this GitHub App acts as (example: `branch-autoprotector[bot]`).
+	pub fn bot_login(&self) -> String
+	{
+		format!("{}[bot]", self.slug)
+	}
+}
+
 /// A field that is currently unsupported and needs to be set to `None` currently.
 #[derive(Debug, serde::Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -106,3 +870,80 @@ pub struct UnsupportedField;
 pub struct IgnoreResponse
 {
 }
+
+#[cfg(test)]
+mod tests
+{
+	/// A `create` event as delivered by GitHub when a repository is created with a default branch.
+	const CREATE_EVENT_FIXTURE: &str = include_str!("../tests/fixtures/create.json");
+
+	#[test]
+	fn decode_create_event_fixture()
+	{
+		let payload: super::RefCreationEventPayload =
+			serde_json::from_str(CREATE_EVENT_FIXTURE).unwrap();
+
+		assert_eq!(payload.ref_, "main");
+		assert_eq!(payload.ref_type, super::RefType::Branch);
+		assert_eq!(payload.master_branch.as_deref(), Some("main"));
+		assert_eq!(payload.repository.name, "example-repository");
+		assert_eq!(payload.repository.owner.login, "example-organization");
+		assert_eq!(payload.repository.default_branch.as_deref(), Some("main"));
+		assert!(!payload.repository.fork);
+		assert_eq!(payload.organization.map(|organization| organization.login).as_deref(),
+			Some("example-organization"));
+		assert_eq!(payload.sender.login, "octocat");
+	}
+
+	#[test]
+	fn decode_default_branch_under_newer_name()
+	{
+		let mut payload: serde_json::Value = serde_json::from_str(CREATE_EVENT_FIXTURE).unwrap();
+		let payload_object = payload.as_object_mut().unwrap();
+		payload_object.remove("master_branch");
+		payload_object.insert("default_branch".into(), "trunk".into());
+
+		let payload: super::RefCreationEventPayload = serde_json::from_value(payload).unwrap();
+
+		assert_eq!(payload.master_branch, None);
+		assert_eq!(payload.default_branch.as_deref(), Some("trunk"));
+		assert_eq!(payload.default_branch_name(), Some("trunk"));
+	}
+
+	#[test]
+	fn decode_default_branch_under_both_names()
+	{
+		let mut payload: serde_json::Value = serde_json::from_str(CREATE_EVENT_FIXTURE).unwrap();
+		payload["default_branch"] = "trunk".into();
+
+		let payload: super::RefCreationEventPayload = serde_json::from_value(payload).unwrap();
+
+		assert_eq!(payload.master_branch.as_deref(), Some("main"));
+		assert_eq!(payload.default_branch.as_deref(), Some("trunk"));
+		assert_eq!(payload.default_branch_name(), Some("main"));
+	}
+
+	#[test]
+	fn decode_fork_status_of_repository()
+	{
+		let repository: super::Repository = serde_json::from_value(serde_json::json!(
+		{
+			"name": "example-repository",
+			"owner": {"login": "example-organization"},
+			"fork": true,
+		}))
+		.unwrap();
+
+		assert!(repository.fork);
+
+		// Not all responses include the fork status
+		let repository: super::Repository = serde_json::from_value(serde_json::json!(
+		{
+			"name": "example-repository",
+			"owner": {"login": "example-organization"},
+		}))
+		.unwrap();
+
+		assert!(!repository.fork);
+	}
+}
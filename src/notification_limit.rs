@@ -0,0 +1,60 @@
+/// Limits how many notifications are posted per repository within [NOTIFICATION_LIMIT_WINDOW], as
+/// a safety valve against flooding repositories with issues if something goes wrong, such as a
+/// loop of branches being deleted and created again.
+///
+/// The limiter can safely be shared between threads, as cloning it only clones a handle to the
+/// underlying data.
+#[derive(Clone, Default)]
+pub struct NotificationLimiter
+{
+	#[doc(hidden)]
+	// When notifications were posted by repository, oldest first, limited to the current window
+	posted_at: std::sync::Arc<std::sync::Mutex<
+		std::collections::HashMap<String, std::collections::VecDeque<std::time::Instant>>>>,
+}
+
+impl NotificationLimiter
+{
+	/// Record a notification about to be posted in a repository and return `true`, unless the
+	/// limit of notifications in this repository within the window is reached already, in which
+	/// case nothing is recorded and `false` is returned.
+	///
+	/// # Arguments
+	/// - `repository`: The full name of the repository, including the organization.
+	/// - `limit`: How many notifications may be posted in the repository within the window.
+	pub fn try_acquire(&self, repository: &str, limit: u32) -> bool
+	{
+		let now = std::time::Instant::now();
+
+		// The timestamps stay consistent even if a thread panicked while holding the lock
+		let mut posted_at = self.posted_at.lock()
+			.unwrap_or_else(std::sync::PoisonError::into_inner);
+
+		// Forget notifications that left the window, including those of other repositories, so
+		// that the map doesn’t grow without bounds
+		posted_at.retain(|_, timestamps|
+		{
+			while timestamps.front().is_some_and(
+				|timestamp| now.duration_since(*timestamp) >= NOTIFICATION_LIMIT_WINDOW)
+			{
+				timestamps.pop_front();
+			}
+
+			!timestamps.is_empty()
+		});
+
+		let timestamps = posted_at.entry(repository.to_owned()).or_default();
+
+		if timestamps.len() >= limit as usize
+		{
+			return false;
+		}
+
+		timestamps.push_back(now);
+
+		true
+	}
+}
+
+/// The window in which notifications per repository are limited by `notification.max_per_hour`.
+pub const NOTIFICATION_LIMIT_WINDOW: std::time::Duration = std::time::Duration::from_secs(60 * 60);
@@ -0,0 +1,142 @@
+/// Writes the raw payloads of received webhook events to files in a directory for forensics,
+/// keeping only the most recent ones.
+///
+/// Payloads are only captured after their signatures were verified, so that forged payloads aren’t
+/// persisted. Failures to write them are logged but otherwise ignored, so that they don’t affect
+/// handling webhook events.
+#[derive(Clone)]
+pub struct PayloadCapture
+{
+	#[doc(hidden)]
+	directory: std::sync::Arc<std::path::PathBuf>,
+	#[doc(hidden)]
+	max_files: usize,
+	#[doc(hidden)]
+	// Held while writing and rotating files so that concurrent deliveries don’t interfere
+	lock: std::sync::Arc<std::sync::Mutex<()>>,
+}
+
+impl PayloadCapture
+{
+	/// Create a new payload capture.
+	///
+	/// # Arguments
+	/// - `directory`: The directory to write captured payloads to, which is created if needed.
+	/// - `max_files`: How many captured payloads to keep before deleting the oldest ones.
+	pub fn new(directory: std::path::PathBuf, max_files: usize) -> Self
+	{
+		Self
+		{
+			directory: std::sync::Arc::new(directory),
+			max_files,
+			lock: Default::default(),
+		}
+	}
+
+	/// Write the raw payload of a webhook event along with the headers identifying the delivery to
+	/// a timestamped file, and delete the oldest files exceeding the retention limit.
+	///
+	/// # Arguments
+	/// - `headers`: The headers of the request delivering the webhook event.
+	/// - `payload`: The raw payload as received.
+	pub fn capture(&self, headers: &warp::http::HeaderMap, payload: &[u8])
+	{
+		// A poisoned lock only means that another capture failed, which doesn’t affect this one
+		let _guard = self.lock.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+
+		let result = self.write(headers, payload)
+			.and_then(|path|
+			{
+				log::debug!("captured payload to {}", path.display());
+				self.rotate()
+			});
+
+		if let Err(error) = result
+		{
+			log::warn!("could not capture payload in {}: {error}", self.directory.display());
+		}
+	}
+
+	/// Write a captured payload to a new file that only the current user can read.
+	#[doc(hidden)]
+	fn write(&self, headers: &warp::http::HeaderMap, payload: &[u8])
+		-> std::io::Result<std::path::PathBuf>
+	{
+		use std::io::Write as _;
+		use std::os::unix::fs::OpenOptionsExt as _;
+
+		std::fs::create_dir_all(&*self.directory)?;
+
+		// The delivery ID isn’t covered by the payload signature, so only keep characters that are
+		// safe to use in file names
+		let delivery_id: String = headers.get("x-github-delivery")
+			.and_then(|delivery_id| delivery_id.to_str().ok())
+			.unwrap_or("unknown")
+			.chars()
+			.filter(|character| character.is_ascii_alphanumeric() || *character == '-')
+			.take(64)
+			.collect();
+
+		// Timestamps are formatted so that file names sort chronologically
+		let file_name = format!("{FILE_NAME_PREFIX}{}-{delivery_id}.http",
+			chrono::Utc::now().format("%Y%m%dT%H%M%S%.6fZ"));
+		let path = self.directory.join(file_name);
+
+		let mut file = std::fs::OpenOptions::new()
+			.write(true)
+			.create_new(true)
+			.mode(0o600)
+			.open(&path)?;
+
+		for (name, value) in headers
+		{
+			if CAPTURED_HEADERS.contains(&name.as_str()) || name.as_str().starts_with("x-github-")
+			{
+				write!(file, "{name}: ")?;
+				file.write_all(value.as_bytes())?;
+				writeln!(file)?;
+			}
+		}
+
+		writeln!(file)?;
+		file.write_all(payload)?;
+
+		Ok(path)
+	}
+
+	/// Delete the oldest captured payloads exceeding the retention limit.
+	#[doc(hidden)]
+	fn rotate(&self) -> std::io::Result<()>
+	{
+		let mut file_names = vec![];
+
+		for entry in std::fs::read_dir(&*self.directory)?
+		{
+			let file_name = entry?.file_name();
+
+			if file_name.to_str().is_some_and(|file_name| file_name.starts_with(FILE_NAME_PREFIX))
+			{
+				file_names.push(file_name);
+			}
+		}
+
+		file_names.sort();
+
+		let excess_count = file_names.len().saturating_sub(self.max_files);
+
+		for file_name in &file_names[..excess_count]
+		{
+			std::fs::remove_file(self.directory.join(file_name))?;
+		}
+
+		Ok(())
+	}
+}
+
+/// Prefix of the names of files containing captured payloads, which distinguishes them from other
+/// files in the same directory that must not be deleted.
+const FILE_NAME_PREFIX: &str = "payload-";
+
+/// Headers captured along with payloads in addition to the ones starting with `X-GitHub-`, such as
+/// the delivery ID and event type.
+const CAPTURED_HEADERS: [&str; 3] = ["content-type", "user-agent", "x-hub-signature-256"];
@@ -0,0 +1,2678 @@
+/// The branch a webhook event asks us to protect, which is either the default branch or one of the
+/// explicitly configured protected branches.
+#[derive(Debug)]
+pub struct ProtectionTarget
+{
+	/// The organization owning the repository.
+	pub organization_name: String,
+	/// The name of the repository.
+	pub repository_name: String,
+	/// The name of the branch.
+	pub branch_name: String,
+	/// Whether the branch is the default branch of the repository.
+	pub is_default_branch: bool,
+	/// The handle of the user who caused the event, which is usually the user who created the
+	/// branch.
+	pub creator_name: String,
+	/// When the webhook event was received, for measuring how long it takes to protect the branch.
+	pub received_at: std::time::Instant,
+	/// The type of the webhook event asking us to protect the branch (example: `create`).
+	pub event_type: &'static str,
+	/// The policy to protect the branch with instead of the one configured for the repository, as
+	/// selected by the decision hook, if any.
+	pub policy: Option<String>,
+}
+
+/// Branch protection as it was applied to a branch, which lacks the configured settings GitHub
+/// rejected if `best_effort_protection` is enabled.
+#[derive(Debug)]
+struct AppliedProtection
+{
+	/// The request the branch was protected with.
+	request: crate::ProtectBranchRequest,
+	/// The names of the configured settings that GitHub rejected, which were dropped from the
+	/// request.
+	dropped_settings: Vec<&'static str>,
+}
+
+/// A ref creation event that’s acted on, whose branch may still have to be compared with the
+/// default branch of the repository, which some webhook proxies don’t include in the payload, or
+/// checked against predicates requiring GitHub API requests.
+#[derive(Debug)]
+pub struct RefCreation
+{
+	/// The branch to protect, which isn’t known to be the default branch yet if the default branch
+	/// still has to be looked up.
+	pub target: ProtectionTarget,
+	/// Whether the default branch still has to be looked up via the GitHub API.
+	look_up_default_branch: bool,
+	/// Whether the configured predicates still have to be evaluated via the GitHub API.
+	check_predicates: bool,
+}
+
+impl RefCreation
+{
+	/// Finish deciding whether to act on the ref creation event, looking up the default branch via
+	/// the GitHub API if the payload didn’t include it and evaluating the configured predicates for
+	/// branches other than the default branch. This is meant to be called after acknowledging the
+	/// webhook event, returning the outcome of ignoring the event if the branch isn’t of interest.
+	///
+	/// # Arguments
+	/// - `config`: The configuration, including the predicates deciding which branches to protect
+	///   in addition to the default branch.
+	/// - `github_api_client`: The GitHub API client for looking up the default branch and
+	///   evaluating predicates if needed.
+	pub async fn resolve(&mut self, config: &crate::Config,
+		github_api_client: &crate::github_api::Client)
+		-> Result<(), HandledOutcome>
+	{
+		let target = &mut self.target;
+
+		if self.look_up_default_branch
+		{
+			log::debug!("ref creation event for repository “{}” doesn’t include the default \
+				branch, looking it up", target.repository_name);
+
+			let default_branch_name = github_api_client
+				.get_repository(&target.organization_name, &target.repository_name)
+				.await
+				.map_err(HandledOutcome::Failed)?
+				.default_branch;
+
+			target.is_default_branch = default_branch_name.as_ref() == Some(&target.branch_name);
+			self.look_up_default_branch = false;
+		}
+
+		// If the newly created branch is not the default branch, this isn’t the first branch being
+		// created, so don’t set up branch protection rules either unless any of the configured
+		// predicates holds for the branch
+		if self.check_predicates && !target.is_default_branch
+		{
+			self.check_predicates = false;
+
+			if !matches_branch_predicates(config, github_api_client, &target.organization_name,
+				&target.repository_name, &target.branch_name).await?
+			{
+				return Err(HandledOutcome::Ignored(
+					"not listening to this ref creation event".into()));
+			}
+		}
+
+		match target.is_default_branch
+		{
+			true => log::info!("repository “{}” was created in organization “{}” with a new \
+				default branch “{}”", target.repository_name, target.organization_name,
+				target.branch_name),
+			false => log::info!("branch “{}”, which is configured to be protected, was created in \
+				repository “{}” in organization “{}”", target.branch_name, target.repository_name,
+				target.organization_name),
+		}
+
+		Ok(())
+	}
+}
+
+impl ProtectionTarget
+{
+	/// Decide whether to act on a ref creation event, returning the branch to protect or the
+	/// outcome of ignoring the event, without making any GitHub API requests. If the payload
+	/// doesn’t include the name of the default branch, as is the case with some webhook proxies, or
+	/// predicates requiring GitHub API requests need to be evaluated, deciding is left to
+	/// [`RefCreation::resolve`], which is called after the event has been acknowledged.
+	///
+	/// # Arguments
+	/// - `payload`: The decoded webhook event payload.
+	/// - `config`: The configuration, including the predicates deciding which branches to protect
+	///   in addition to the default branch and whether to protect branches of forked repositories.
+	pub fn from_ref_creation_event(payload: crate::RefCreationEventPayload,
+		config: &crate::Config)
+		-> Result<RefCreation, HandledOutcome>
+	{
+		// Take the time before looking up the default branch, which counts toward protecting it
+		let received_at = std::time::Instant::now();
+
+		// Ignore all actions other than the creation of a branch
+		if payload.ref_type != crate::RefType::Branch
+		{
+			return Err(HandledOutcome::Ignored("not listening to this ref creation event".into()));
+		}
+
+		check_fork(&payload.repository, config.protect_forks)?;
+
+		let default_branch_name = payload.default_branch_name().map(str::to_owned);
+		let look_up_default_branch = default_branch_name.is_none();
+
+		// Prefer the organization included in the payload, which is unambiguous, over the owner of
+		// the repository
+		let organization_name = match payload.organization
+		{
+			Some(organization) => organization.login,
+			None => payload.repository.owner.login,
+		};
+
+		let branch_name = payload.ref_;
+		let is_default_branch = default_branch_name.as_ref() == Some(&branch_name);
+
+		check_branch_deny_patterns(&branch_name, &payload.repository.name, config)?;
+
+		// If the newly created branch is not the default branch, this isn’t the first branch being
+		// created, so don’t set up branch protection rules either unless any of the configured
+		// predicates holds for the branch. Predicates requiring GitHub API requests are only
+		// evaluated once the event has been acknowledged
+		let predicates = &config.branch_predicates;
+		let check_predicates = if look_up_default_branch
+		{
+			true
+		}
+		else if is_default_branch || (predicates.protect_if_matches_pattern
+			&& config.protected_branch_rule(&branch_name).is_some())
+		{
+			false
+		}
+		else if predicates.protect_if_has_open_pr
+		{
+			true
+		}
+		else
+		{
+			return Err(HandledOutcome::Ignored("not listening to this ref creation event".into()));
+		};
+
+		let target = Self
+		{
+			organization_name,
+			repository_name: payload.repository.name,
+			branch_name,
+			is_default_branch,
+			creator_name: payload.sender.login,
+			received_at,
+			event_type: "create",
+			policy: None,
+		};
+
+		Ok(RefCreation{target, look_up_default_branch, check_predicates})
+	}
+
+	/// Decide whether to act on a push event, returning the branch to protect or the outcome of
+	/// ignoring the event. Only pushes creating the default branch or one of the explicitly
+	/// configured protected branches are of interest. The default branch can only be recognized if
+	/// the payload includes its name.
+	///
+	/// # Arguments
+	/// - `payload`: The decoded webhook event payload.
+	/// - `config`: The configuration, including whether to act on push events at all, the
+	///   branches to protect in addition to the default branch, and whether to protect branches of
+	///   forked repositories.
+	pub fn from_push_event(payload: crate::PushEventPayload, config: &crate::Config)
+		-> Result<Self, HandledOutcome>
+	{
+		let branch_name = match payload.ref_.strip_prefix("refs/heads/")
+		{
+			Some(branch_name) if config.protect_on_first_push && payload.created =>
+				branch_name.to_owned(),
+			_ => return Err(HandledOutcome::Ignored("not listening to this push event".into())),
+		};
+
+		check_fork(&payload.repository, config.protect_forks)?;
+
+		let is_default_branch =
+			payload.repository.default_branch.as_deref() == Some(branch_name.as_str());
+
+		check_branch_deny_patterns(&branch_name, &payload.repository.name, config)?;
+
+		if !(is_default_branch || config.protected_branch_rule(&branch_name).is_some())
+		{
+			return Err(HandledOutcome::Ignored("not listening to this push event".into()));
+		}
+
+		let target = Self
+		{
+			organization_name: payload.repository.owner.login,
+			repository_name: payload.repository.name,
+			branch_name,
+			is_default_branch,
+			creator_name: payload.sender.login,
+			received_at: std::time::Instant::now(),
+			event_type: "push",
+			policy: None,
+		};
+
+		log::info!("{} “{}” was pushed for the first time to repository “{}” in organization “{}”",
+			target.branch_description(), target.branch_name, target.repository_name,
+			target.organization_name);
+
+		Ok(target)
+	}
+
+	/// Decide whether to act on a branch protection rule event, returning the branch whose
+	/// protection rule was deleted or the outcome of ignoring the event. Only the deletion of rules
+	/// applying to the default branch or one of the explicitly configured protected branches is
+	/// of interest, while edits are only logged.
+	///
+	/// # Arguments
+	/// - `payload`: The decoded webhook event payload.
+	/// - `config`: The configuration, including the branches to protect in addition to the default
+	///   branch and whether branches of forked repositories are protected.
+	pub fn from_branch_protection_rule_event(payload: crate::BranchProtectionRuleEventPayload,
+		config: &crate::Config)
+		-> Result<Self, HandledOutcome>
+	{
+		let branch_name = payload.rule.name;
+
+		match payload.action
+		{
+			crate::BranchProtectionRuleAction::Deleted => (),
+			crate::BranchProtectionRuleAction::Edited =>
+			{
+				log::info!("branch protection rule for “{branch_name}” in repository “{}” was \
+					edited by “{}”", payload.repository.name, payload.sender.login);
+				return Err(HandledOutcome::Ignored(
+					"not acting on edited branch protection rules".into()));
+			},
+			crate::BranchProtectionRuleAction::Created => return Err(HandledOutcome::Ignored(
+				"not listening to this branch protection rule event".into())),
+		}
+
+		check_fork(&payload.repository, config.protect_forks)?;
+
+		// Rules may also apply to branch name patterns, which are only of interest if they match
+		// the name of a protected branch exactly, as configured patterns only apply to actual
+		// branches
+		let is_default_branch =
+			payload.repository.default_branch.as_deref() == Some(branch_name.as_str());
+
+		check_branch_deny_patterns(&branch_name, &payload.repository.name, config)?;
+
+		if !(is_default_branch || config.protected_branches.contains(&branch_name))
+		{
+			return Err(HandledOutcome::Ignored(
+				"deleted branch protection rule doesn’t apply to a protected branch".into()));
+		}
+
+		let target = Self
+		{
+			organization_name: payload.repository.owner.login,
+			repository_name: payload.repository.name,
+			branch_name,
+			is_default_branch,
+			creator_name: payload.sender.login,
+			received_at: std::time::Instant::now(),
+			event_type: "branch_protection_rule",
+			policy: None,
+		};
+
+		log::warn!("branch protection rule of {} “{}” in repository “{}” was deleted by “{}”",
+			target.branch_description(), target.branch_name, target.repository_name,
+			target.creator_name);
+
+		Ok(target)
+	}
+
+	/// Decide whether to act on a repository event, returning the default branch to protect or the
+	/// outcome of ignoring the event. Only repositories transferred into the organization are of
+	/// interest, as their default branches were never protected by this service.
+	///
+	/// # Arguments
+	/// - `payload`: The decoded webhook event payload.
+	/// - `config`: The configuration, including whether to protect branches of forked
+	///   repositories.
+	pub fn from_repository_event(payload: crate::RepositoryEventPayload, config: &crate::Config)
+		-> Result<Self, HandledOutcome>
+	{
+		if payload.action != crate::RepositoryAction::Transferred
+		{
+			return Err(HandledOutcome::Ignored("not listening to this repository event".into()));
+		}
+
+		check_fork(&payload.repository, config.protect_forks)?;
+
+		let branch_name = match payload.repository.default_branch
+		{
+			Some(branch_name) => branch_name,
+			None => return Err(HandledOutcome::Ignored(
+				"transferred repository doesn’t have a default branch".into())),
+		};
+
+		check_branch_deny_patterns(&branch_name, &payload.repository.name, config)?;
+
+		let previous_owner = payload.changes.and_then(|changes| changes.owner)
+			.and_then(|owner| owner.from.organization.map(|organization| organization.login)
+				.or(owner.from.user.map(|user| user.login)))
+			.unwrap_or_else(|| "an unknown owner".into());
+
+		let target = Self
+		{
+			organization_name: payload.repository.owner.login,
+			repository_name: payload.repository.name,
+			branch_name,
+			is_default_branch: true,
+			creator_name: payload.sender.login,
+			received_at: std::time::Instant::now(),
+			event_type: "repository",
+			policy: None,
+		};
+
+		log::info!("repository “{}” was transferred from “{previous_owner}” to organization “{}” \
+			by “{}”", target.repository_name, target.organization_name, target.creator_name);
+
+		Ok(target)
+	}
+
+	/// The branch protection settings to apply to this branch, which are the ones of the policy
+	/// selected by the decision hook, if any, or the ones configured for the branch or the
+	/// repository.
+	///
+	/// # Arguments
+	/// - `config`: The configuration, including the policies.
+	pub fn branch_protection<'a>(&self, config: &'a crate::Config)
+		-> &'a crate::BranchProtectionSettings
+	{
+		match &self.policy
+		{
+			Some(policy_name) => config.policies.get(policy_name)
+				.expect("this call is infallible because the selected policy was validated"),
+			None => config.branch_protection_for_branch(&self.repository_name, &self.branch_name,
+				self.is_default_branch),
+		}
+	}
+
+	/// Describe the kind of branch this is in prose (either “default branch” or “branch”).
+	pub fn branch_description(&self) -> &'static str
+	{
+		match self.is_default_branch
+		{
+			true => "default branch",
+			false => "branch",
+		}
+	}
+
+	/// The link to the branch relative to issues and pull requests of its repository, with each
+	/// segment of the branch name percent-encoded so that characters such as `)` or `#` can’t break
+	/// Markdown links.
+	pub fn branch_link(&self) -> String
+	{
+		let path: Vec<_> = self.branch_name.split('/')
+			.map(crate::github_api::encode_path_segment)
+			.collect();
+
+		format!("../tree/{}", path.join("/"))
+	}
+}
+
+/// Check whether branches of a repository may be protected, which isn’t the case for forks unless
+/// configured otherwise, returning the outcome of ignoring the event if not.
+///
+/// # Arguments
+/// - `repository`: The repository for which the event is reported.
+/// - `protect_forks`: Whether to protect branches of forked repositories.
+fn check_fork(repository: &crate::Repository, protect_forks: bool) -> Result<(), HandledOutcome>
+{
+	if repository.fork && !protect_forks
+	{
+		log::info!("ignoring event for repository “{}” in organization “{}”, which is a fork \
+			(protect_forks is disabled)", repository.name, repository.owner.login);
+		return Err(HandledOutcome::Ignored(
+			"not protecting branches of forked repositories".into()));
+	}
+
+	Ok(())
+}
+
+/// Check whether any of the configured predicates holds for a newly created branch other than the
+/// default branch, in which case it’s protected. Predicates are evaluated in the order of their
+/// GitHub API cost, so that lookups are skipped if a cheaper predicate holds already.
+///
+/// # Arguments
+/// - `config`: The configuration, including the predicates and the branches to protect.
+/// - `github_api_client`: The GitHub API client for evaluating predicates that need lookups.
+/// - `organization_name`: The organization owning the repository.
+/// - `repository_name`: The name of the repository.
+/// - `branch_name`: The name of the branch.
+async fn matches_branch_predicates(config: &crate::Config,
+	github_api_client: &crate::github_api::Client, organization_name: &str, repository_name: &str,
+	branch_name: &str)
+	-> Result<bool, HandledOutcome>
+{
+	let predicates = &config.branch_predicates;
+
+	if predicates.protect_if_matches_pattern && config.protected_branch_rule(branch_name).is_some()
+	{
+		return Ok(true);
+	}
+
+	if predicates.protect_if_has_open_pr && github_api_client.has_open_pull_request(
+		organization_name, repository_name, branch_name).await.map_err(HandledOutcome::Failed)?
+	{
+		log::info!("branch “{branch_name}” in repository “{repository_name}” is the head of an \
+			open pull request");
+		return Ok(true);
+	}
+
+	Ok(false)
+}
+
+/// Check whether a branch may be protected, which isn’t the case if it matches any of
+/// `branch_deny_patterns`, regardless of whether it’s a default branch or configured to be
+/// protected, returning the outcome of ignoring the event if not.
+///
+/// # Arguments
+/// - `branch_name`: The name of the branch.
+/// - `repository_name`: The name of the repository, which is logged.
+/// - `config`: The configuration, including the patterns of branches never to protect.
+fn check_branch_deny_patterns(branch_name: &str, repository_name: &str, config: &crate::Config)
+	-> Result<(), HandledOutcome>
+{
+	if let Some(pattern) = config.branch_deny_pattern(branch_name)
+	{
+		log::info!("ignoring event for branch “{branch_name}” in repository “{repository_name}”, \
+			which matches the deny pattern “{pattern}”");
+		return Err(HandledOutcome::Ignored(format!(
+			"branch matches the deny pattern “{pattern}”")));
+	}
+
+	Ok(())
+}
+
+/// Check whether a webhook event was caused by someone other than this GitHub App, unless
+/// configured to act on events caused by this GitHub App, too, returning the outcome of ignoring
+/// the event if not. If the GitHub App’s bot login is unknown, all events are acted on.
+///
+/// # Arguments
+/// - `sender`: The user causing the event.
+/// - `act_on_self`: Whether to act on events caused by this GitHub App.
+/// - `app_bot_login`: The login of the bot user this GitHub App acts as, if known.
+pub fn check_sender(sender: &crate::User, act_on_self: bool, app_bot_login: Option<&str>)
+	-> Result<(), HandledOutcome>
+{
+	if !act_on_self && app_bot_login == Some(sender.login.as_str())
+	{
+		log::debug!("ignoring event caused by this GitHub App itself (act_on_self is disabled)");
+		return Err(HandledOutcome::Ignored(
+			"not acting on events caused by this GitHub App".into()));
+	}
+
+	Ok(())
+}
+
+/// Run the decision hook if configured and act on its decision, returning the outcome of ignoring
+/// the event if the hook decided against acting on it. If the hook selects a policy, it’s recorded
+/// in the target to protect the branch with.
+///
+/// # Arguments
+/// - `config`: The configuration, including the decision hook and the policies.
+/// - `target`: The branch the webhook event asks us to protect.
+pub async fn consult_decision_hook(config: &crate::Config, target: &mut ProtectionTarget)
+	-> Result<(), HandledOutcome>
+{
+	let decision_hook = match &config.decision_hook
+	{
+		Some(decision_hook) => decision_hook,
+		None => return Ok(()),
+	};
+
+	let decision = crate::run_decision_hook(decision_hook, target.event_type, target).await
+		.map_err(HandledOutcome::Failed)?;
+
+	let reason = decision.reason.unwrap_or_else(|| "no reason given".into());
+
+	if !decision.protect
+	{
+		log::info!("decision hook decided not to act on {} “{}” in repository “{}”: {reason}",
+			target.branch_description(), target.branch_name, target.repository_name);
+		return Err(HandledOutcome::Ignored(format!("decision hook decided not to act: {reason}")));
+	}
+
+	if let Some(policy) = &decision.policy
+	{
+		if !config.policies.contains_key(policy)
+		{
+			return Err(HandledOutcome::Failed(
+				crate::Error::UnknownDecisionHookPolicy{policy: policy.clone()}));
+		}
+
+		log::info!("decision hook selected policy “{policy}” for {} “{}” in repository “{}”: \
+			{reason}", target.branch_description(), target.branch_name, target.repository_name);
+	}
+
+	target.policy = decision.policy;
+
+	Ok(())
+}
+
+/// Check whether a webhook event is recent enough to act on, judging by when the repository was
+/// last pushed to, returning the outcome of ignoring the event if not. Events without a timestamp
+/// are always acted on.
+///
+/// # Arguments
+/// - `repository`: The repository for which the event is reported.
+/// - `max_event_age`: The maximum age of events to act on, if limited.
+pub fn check_event_age(repository: &crate::Repository, max_event_age: Option<std::time::Duration>)
+	-> Result<(), HandledOutcome>
+{
+	let (max_event_age, pushed_at) = match (max_event_age, repository.pushed_at)
+	{
+		(Some(max_event_age), Some(pushed_at)) => (max_event_age, pushed_at),
+		_ => return Ok(()),
+	};
+
+	// Timestamps in the future due to clock skew result in an error here and aren’t stale
+	let event_age = match (chrono::Utc::now() - pushed_at).to_std()
+	{
+		Ok(event_age) => event_age,
+		Err(_) => return Ok(()),
+	};
+
+	if event_age > max_event_age
+	{
+		log::info!("ignoring stale event for repository “{}” in organization “{}”, which was \
+			pushed to {} seconds ago (max_event_age_seconds is {})", repository.name,
+			repository.owner.login, event_age.as_secs(), max_event_age.as_secs());
+		return Err(HandledOutcome::Ignored("not acting on stale webhook events".into()));
+	}
+
+	Ok(())
+}
+
+/// The outcome of handling a webhook event, independent of how it’s reported to GitHub.
+#[derive(Debug)]
+pub enum HandledOutcome
+{
+	/// The event was ignored for the given reason.
+	Ignored(String),
+	/// The branch was protected and its creator was notified in the given issue or discussion,
+	/// unless these are disabled for the repository.
+	Protected
+	{
+		issue_url: Option<url::Url>,
+	},
+	/// Protecting the branch was proposed in the given pull request adding a ruleset file, which
+	/// doesn’t protect the branch until the file is imported, or an open pull request was updated.
+	Proposed
+	{
+		pull_request_url: Option<url::Url>,
+	},
+	/// The branch was protected already but with settings differing from the configured ones,
+	/// which was reported in the given issue, unless issues are disabled for the repository.
+	DriftReported
+	{
+		issue_url: Option<url::Url>,
+	},
+	/// The branch protection rule of the branch was deleted, which was handled as configured by
+	/// restoring it and/or reporting this in the given issue.
+	DeletionHandled
+	{
+		restored: bool,
+		issue_url: Option<url::Url>,
+	},
+	/// In dry-run mode, protecting the branch would have made the given changes to its current
+	/// protection settings, but nothing was changed.
+	DryRun
+	{
+		changes: Vec<crate::ProtectionChange>,
+	},
+	/// Handling the event failed with the given error.
+	Failed(crate::Error),
+}
+
+impl HandledOutcome
+{
+	/// A short, machine-readable name of the kind of this outcome.
+	pub fn name(&self) -> &'static str
+	{
+		match self
+		{
+			Self::Ignored(_) => "ignored",
+			Self::Protected{..} => "protected",
+			Self::Proposed{..} => "proposed",
+			Self::DriftReported{..} => "drift_reported",
+			Self::DeletionHandled{..} => "deletion_handled",
+			Self::DryRun{..} => "dry_run",
+			Self::Failed(_) => "failed",
+		}
+	}
+
+	/// Details about this outcome, such as the reason for ignoring an event, the URL of a created
+	/// issue, or an error message.
+	pub fn detail(&self) -> String
+	{
+		match self
+		{
+			Self::Ignored(reason) => reason.clone(),
+			Self::Protected{issue_url} | Self::DriftReported{issue_url}
+				| Self::Proposed{pull_request_url: issue_url} =>
+				issue_url.as_ref().map(url::Url::to_string).unwrap_or_default(),
+			Self::DeletionHandled{restored, issue_url} => format!("restored={restored} \
+				issue_url={}", issue_url.as_ref().map(url::Url::as_str).unwrap_or_default()),
+			Self::DryRun{changes} =>
+				changes.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "),
+			Self::Failed(error) => error.describe_with_sources(),
+		}
+	}
+
+	/// Count this outcome in the metrics, record it in the configured event sink, post an alert if
+	/// it’s a failure and alerts are configured, and log it.
+	///
+	/// # Arguments
+	/// - `state`: The shared state, including the metrics, the event sink, and the configuration.
+	/// - `target`: The branch the webhook event asked us to protect.
+	pub async fn report(self, state: &crate::AppState, target: &ProtectionTarget)
+	{
+		state.metrics.record_outcome(&self);
+		state.event_sink.record(target, &self).await;
+
+		if let (Self::Failed(_), Some(failure_notification)) =
+			(&self, &state.config().failure_notification)
+		{
+			state.failure_notifier.notify(failure_notification, target, self.detail());
+		}
+
+		self.log(target);
+	}
+
+	/// Log this outcome of handling a webhook event that asked us to protect a given branch.
+	pub fn log(self, target: &ProtectionTarget)
+	{
+		match self
+		{
+			Self::Ignored(reason) => log::info!("not protecting branch “{}” in repository “{}”: \
+				{reason}", target.branch_name, target.repository_name),
+			Self::Protected{issue_url: Some(issue_url)} =>
+				log::info!("created issue informing about branch protection: {issue_url}"),
+			Self::Protected{issue_url: None} =>
+				log::info!("protected branch without creating an issue, as issues are disabled"),
+			Self::Proposed{pull_request_url: Some(pull_request_url)} => log::info!("opened pull \
+				request proposing branch protection: {pull_request_url}"),
+			Self::Proposed{pull_request_url: None} => log::info!("did not open a new pull request \
+				proposing branch protection for branch “{}” in repository “{}”",
+				target.branch_name, target.repository_name),
+			Self::DriftReported{issue_url: Some(issue_url)} => log::info!("created issue \
+				reporting differences from the configured branch protection settings: {issue_url}"),
+			Self::DriftReported{issue_url: None} => log::info!("could not report differences \
+				from the configured branch protection settings, as issues are disabled"),
+			Self::DeletionHandled{restored, issue_url} =>
+			{
+				if restored
+				{
+					log::info!("restored deleted branch protection rule");
+				}
+
+				if let Some(issue_url) = issue_url
+				{
+					log::info!("created issue reporting deleted branch protection rule: \
+						{issue_url}");
+				}
+			},
+			Self::DryRun{changes} if changes.is_empty() => log::info!("dry run: protecting branch \
+				“{}” in repository “{}” wouldn’t change anything", target.branch_name,
+				target.repository_name),
+			Self::DryRun{changes} =>
+			{
+				log::info!("dry run: protecting branch “{}” in repository “{}” would make {} \
+					changes", target.branch_name, target.repository_name, changes.len());
+
+				for change in changes
+				{
+					log::info!("dry run change: {}", serde_json::to_string(&change)
+						.expect("this call is infallible because changes are plain data"));
+				}
+			},
+			Self::Failed(error) => log::error!("{:?}", anyhow::Error::from(error)),
+		}
+	}
+}
+
+/// Protect a branch of a repository (usually its default branch) and notify the creator of the
+/// branch about this in an issue, in the order configured by `notification.order`. With
+/// `ruleset_pull_request` configured, the protection is proposed in a pull request instead, which
+/// only protects the branch once a separate process imports the merged ruleset file.
+///
+/// # Arguments
+/// - `state`: The shared state, including the GitHub API client and the configuration.
+/// - `target`: The branch to protect.
+pub async fn protect_default_branch(state: &crate::AppState, target: &ProtectionTarget)
+	-> HandledOutcome
+{
+	let repository_name = &target.repository_name;
+	let config = state.config();
+
+	// If configured, only protect repositories tagged with at least one of the required topics
+	if let Err(outcome) = check_required_topics(state, target).await
+	{
+		return outcome;
+	}
+
+	// If configured, skip repositories that opted out of being protected
+	if let Err(outcome) = check_opt_out(state, target).await
+	{
+		return outcome;
+	}
+
+	// If configured, leave branches without commits to the push event adding the first commit
+	if config.require_commits_before_protecting
+	{
+		if let Err(outcome) = check_branch_has_commits(state, target).await
+		{
+			return outcome;
+		}
+	}
+
+	if config.dry_run
+	{
+		return preview_branch_protection(state, target).await;
+	}
+
+	// If configured, propose the protection in a pull request, which replaces the notification
+	if let Some(ruleset_pull_request) = &config.ruleset_pull_request
+	{
+		return match crate::open_ruleset_pull_request(state, target, ruleset_pull_request).await
+		{
+			Ok(pull_request_url) => HandledOutcome::Proposed{pull_request_url},
+			Err(error) => HandledOutcome::Failed(error),
+		};
+	}
+
+	let (protect_result, notify_result) = match config.notification.order
+	{
+		crate::NotificationOrder::ProtectThenNotify =>
+		{
+			let applied_protection = match protect_branch(state, target).await
+			{
+				Ok(applied_protection) => applied_protection,
+				Err(error) => return HandledOutcome::Failed(error),
+			};
+
+			(Ok(()), notify_protection(state, target, Some(&applied_protection)).await)
+		},
+		// Protecting the branch is attempted even if the creator couldn’t be notified, who is
+		// notified about the configured settings, as it’s not known yet which ones GitHub accepts
+		crate::NotificationOrder::NotifyThenProtect =>
+		{
+			let notify_result = notify_protection(state, target, None).await;
+			(protect_branch(state, target).await.map(drop), notify_result)
+		},
+		crate::NotificationOrder::Concurrent =>
+		{
+			let (protect_result, notify_result) = tokio::join!(protect_branch(state, target),
+				notify_protection(state, target, None));
+			(protect_result.map(drop), notify_result)
+		},
+	};
+
+	// Failing to protect the branch is more severe than failing to notify about it
+	match (protect_result, notify_result)
+	{
+		(Err(error), notify_result) =>
+		{
+			if let Ok(Some(issue_url)) = notify_result
+			{
+				log::error!("could not protect branch “{}” in repository “{repository_name}”, \
+					although {issue_url} states that it was protected", target.branch_name);
+			}
+
+			HandledOutcome::Failed(error)
+		},
+		(Ok(()), Ok(issue_url)) => HandledOutcome::Protected{issue_url},
+		(Ok(()), Err(error)) => HandledOutcome::Failed(error),
+	}
+}
+
+/// Protect a branch and record this as configured, that is, in the time-to-protection metric and,
+/// if configured, in a check run. Returns the protection as it was applied.
+///
+/// # Arguments
+/// - `state`: The shared state, including the GitHub API client and the configuration.
+/// - `target`: The branch to protect.
+async fn protect_branch(state: &crate::AppState, target: &ProtectionTarget)
+	-> Result<AppliedProtection, crate::Error>
+{
+	let config = state.config();
+
+	let applied_protection = apply_branch_protection(state, target).await?;
+
+	state.metrics.record_time_to_protection(target.received_at.elapsed());
+
+	if config.report_check_run
+	{
+		report_check_run(state, target, &applied_protection).await;
+	}
+
+	Ok(applied_protection)
+}
+
+/// Notify the creator of a branch that it was protected, either in a new issue or, if configured,
+/// in the repository’s status issue. Returns the URL of the issue, if any.
+///
+/// # Arguments
+/// - `state`: The shared state, including the GitHub API client and the configuration.
+/// - `target`: The branch that is protected.
+/// - `applied_protection`: The protection as it was applied, including the settings GitHub
+///   rejected, or `None` to describe the configured settings if the branch isn’t protected yet.
+async fn notify_protection(state: &crate::AppState, target: &ProtectionTarget,
+	applied_protection: Option<&AppliedProtection>)
+	-> Result<Option<url::Url>, crate::Error>
+{
+	let ProtectionTarget{repository_name, branch_name, creator_name, ..} = target;
+	let config = state.config();
+	let dropped_settings = applied_protection
+		.map(|applied_protection| applied_protection.dropped_settings.as_slice())
+		.unwrap_or_default();
+
+	// If configured, record the protected branch in the repository’s status issue instead
+	if config.notification.status_issue
+	{
+		return update_status_issue(state, target, dropped_settings).await.inspect_err(|_|
+			log::error!("could not update branch protection status issue in repository \
+				“{repository_name}”"));
+	}
+
+	let configured_request;
+	let protect_branch_request = match applied_protection
+	{
+		Some(applied_protection) => &applied_protection.request,
+		None =>
+		{
+			configured_request = target.branch_protection(&config).protect_branch_request();
+			&configured_request
+		},
+	};
+
+	// Notify the user triggering the branch creation event of the newly set-up branch protection
+	// rules
+	let issue_title = "Branch protection automatically set up";
+	let required_approving_review_count = protect_branch_request.required_pull_request_reviews
+		.as_ref()
+		.map_or(0, |reviews| reviews.required_approving_review_count);
+	let review_requirement = match required_approving_review_count
+	{
+		0 => String::new(),
+		1 => "Every pull request needs to be approved by at least one person before it can be \
+			merged. ".into(),
+		count => format!("Every pull request needs to be approved by at least {count} people \
+			before it can be merged. "),
+	};
+	let branch_description = target.branch_description();
+	let branch_link = target.branch_link();
+	let mut issue_body = match &config.notification.issue_body_template
+	{
+		Some(template) => render_issue_body_template(template, target,
+			&rules_summary(protect_branch_request),
+			&chrono::Utc::now().with_timezone(&config.display_timezone())
+				.format(TIMESTAMP_FORMAT).to_string()),
+		None => format!(
+			"@{creator_name}: The {branch_description} [`{branch_name}`]({branch_link}) was \
+			automatically protected to comply with our corporate policies. Please submit pull \
+			requests in order to contribute changes, as direct pushes to this branch are not \
+			allowed. {review_requirement}Please review the [branch protection rules in the \
+			repository settings](../settings/branches) and extend them as necessary.\
+			\n\
+			\n\
+			This issue is just for your information and can be closed after reviewing the \
+			branch protection rules."),
+	};
+
+	issue_body.push_str(&dropped_settings_note(dropped_settings));
+
+	post_notification(state, target, issue_title, issue_body).await.inspect_err(|_|
+		log::error!("could not notify repository creator about new branch protection rules set \
+			up for repository “{repository_name}”"))
+}
+
+/// Protect a branch of a repository like [protect_default_branch] unless it’s protected already
+/// (for arguments, see [protect_default_branch]). If the branch is protected already but with
+/// settings differing from the configured ones, it’s handled according to the configured
+/// [crate::ExistingProtectionPolicy].
+pub async fn protect_default_branch_if_unprotected(state: &crate::AppState,
+	target: &ProtectionTarget)
+	-> HandledOutcome
+{
+	// Dry runs compare the current settings with the configured ones regardless of the policy
+	if state.config().dry_run
+	{
+		return protect_default_branch(state, target).await;
+	}
+
+	let existing_protection = match state.github_api_client.get_branch_protection(
+		&target.organization_name, &target.repository_name, &target.branch_name).await
+	{
+		Ok(None) => return protect_default_branch(state, target).await,
+		Ok(Some(existing_protection)) => existing_protection,
+		Err(error) =>
+		{
+			log::error!("could not check whether branch “{}” in repository “{}” is protected",
+				target.branch_name, target.repository_name);
+			return HandledOutcome::Failed(error);
+		},
+	};
+
+	let config = state.config();
+	let policy = config.existing_protection_policy;
+
+	if policy == crate::ExistingProtectionPolicy::Skip
+	{
+		return HandledOutcome::Ignored("branch is already protected".into());
+	}
+
+	let differences = target.branch_protection(&config).differences(&existing_protection);
+
+	if differences.is_empty()
+	{
+		return HandledOutcome::Ignored(
+			"branch is already protected with the configured settings".into());
+	}
+
+	log::info!("branch “{}” in repository “{}” is already protected, but its settings differ from \
+		the configured ones: {}", target.branch_name, target.repository_name,
+		differences.join("; "));
+
+	match policy
+	{
+		crate::ExistingProtectionPolicy::ReportDrift =>
+			report_protection_drift(state, target, &differences).await,
+		_ => protect_default_branch(state, target).await,
+	}
+}
+
+/// React to the branch protection rule of a protected branch being deleted manually by restoring it
+/// and/or opening an issue mentioning the user who deleted it, as configured.
+///
+/// # Arguments
+/// - `state`: The shared state, including the GitHub API client and the configuration.
+/// - `target`: The branch whose protection rule was deleted.
+pub async fn handle_deleted_protection(state: &crate::AppState, target: &ProtectionTarget)
+	-> HandledOutcome
+{
+	let ProtectionTarget{branch_name, creator_name, ..} = target;
+	let config = state.config();
+	let config = &config.deleted_protection;
+
+	if !config.reapply && !config.open_issue
+	{
+		return HandledOutcome::Ignored(
+			"not configured to act on deleted branch protection rules".into());
+	}
+
+	// Don’t act on repositories that wouldn’t be protected in the first place
+	if let Err(outcome) = check_required_topics(state, target).await
+	{
+		return outcome;
+	}
+
+	if let Err(outcome) = check_opt_out(state, target).await
+	{
+		return outcome;
+	}
+
+	if state.config().dry_run
+	{
+		return preview_branch_protection(state, target).await;
+	}
+
+	let mut dropped_settings = vec![];
+
+	if config.reapply
+	{
+		match apply_branch_protection(state, target).await
+		{
+			Ok(applied_protection) => dropped_settings = applied_protection.dropped_settings,
+			Err(error) => return HandledOutcome::Failed(error),
+		}
+	}
+
+	if !config.open_issue
+	{
+		return HandledOutcome::DeletionHandled{restored: true, issue_url: None};
+	}
+
+	let issue_title = "Branch protection rule removed";
+	let follow_up = match config.reapply
+	{
+		true => "As branch protection is required by our corporate policies, the rule was \
+			restored automatically.",
+		false => "As branch protection is required by our corporate policies, please restore the \
+			rule in the [repository settings](../settings/branches).",
+	};
+	let issue_body = format!(
+		"@{creator_name}: The branch protection rule of the {} [`{branch_name}`]({}) was \
+		removed. {follow_up}{}",
+		target.branch_description(), target.branch_link(),
+		dropped_settings_note(&dropped_settings));
+
+	match post_notification(state, target, issue_title, issue_body).await
+	{
+		Ok(issue_url) => HandledOutcome::DeletionHandled{restored: config.reapply, issue_url},
+		Err(error) =>
+		{
+			log::error!("could not report deleted branch protection rule in repository “{}”",
+				target.repository_name);
+			HandledOutcome::Failed(error)
+		}
+	}
+}
+
+/// Report in an issue that the protection settings of a branch differ from the configured ones
+/// without changing them.
+///
+/// # Arguments
+/// - `state`: The shared state, including the GitHub API client and the configuration.
+/// - `target`: The branch whose protection settings differ.
+/// - `differences`: Human-readable descriptions of the differences.
+async fn report_protection_drift(state: &crate::AppState, target: &ProtectionTarget,
+	differences: &[String])
+	-> HandledOutcome
+{
+	let ProtectionTarget{repository_name, branch_name, ..} = target;
+
+	// Don’t report drift in repositories that wouldn’t be protected in the first place
+	if let Err(outcome) = check_required_topics(state, target).await
+	{
+		return outcome;
+	}
+
+	if let Err(outcome) = check_opt_out(state, target).await
+	{
+		return outcome;
+	}
+
+	let issue_title = "Branch protection differs from our policy";
+	let difference_list: String = differences.iter()
+		.map(|difference| format!("- {difference}\n"))
+		.collect();
+	let issue_body = format!(
+		"The protection rules of the {} [`{branch_name}`]({}) differ from our corporate policies \
+		in the following ways:\
+		\n\
+		\n\
+		{difference_list}\
+		\n\
+		These settings were left unchanged. Please review the [branch protection rules in the \
+		repository settings](../settings/branches) and adjust them as necessary.",
+		target.branch_description(), target.branch_link());
+
+	match post_notification(state, target, issue_title, issue_body).await
+	{
+		Ok(issue_url) => HandledOutcome::DriftReported{issue_url},
+		Err(error) =>
+		{
+			log::error!("could not report differences from the configured branch protection \
+				settings in repository “{repository_name}”");
+			HandledOutcome::Failed(error)
+		}
+	}
+}
+
+/// Protect a branch with the settings configured for its repository by disallowing users from
+/// pushing directly and requiring pull request reviews as configured. Returns the protection as it
+/// was applied, which lacks the settings GitHub rejected if `best_effort_protection` is enabled.
+///
+/// # Arguments
+/// - `state`: The shared state, including the GitHub API client and the configuration.
+/// - `target`: The branch to protect.
+async fn apply_branch_protection(state: &crate::AppState, target: &ProtectionTarget)
+	-> Result<AppliedProtection, crate::Error>
+{
+	let ProtectionTarget{organization_name, repository_name, branch_name, ..} = target;
+
+	let config = state.config();
+	let mut protect_branch_request = target.branch_protection(&config).protect_branch_request();
+	let mut dropped_settings = vec![];
+
+	let retry = &config.protect_branch_retry;
+
+	for attempt in 1..
+	{
+		match state.github_api_client.protect_branch(organization_name, repository_name,
+			branch_name, &protect_branch_request).await
+		{
+			Ok(()) => break,
+			// If configured, retry validation errors that may be transient
+			Err(error) if retry.should_retry(&error, attempt) =>
+			{
+				log::warn!("could not set up branch protection rule for branch “{branch_name}” in \
+					repository “{repository_name}” (attempt {attempt} of {}), retrying: {error}",
+					retry.max_attempts);
+				tokio::time::sleep(retry.delay()).await;
+			},
+			// If configured, retry other validation errors naming optional settings without them
+			// one at a time, as GitHub doesn’t reliably report which of the settings it rejected
+			Err(error) if config.best_effort_protection
+				&& drop_rejected_setting(&mut protect_branch_request, &error,
+					&mut dropped_settings) =>
+				log::warn!("could not set up branch protection rule for branch “{branch_name}” in \
+					repository “{repository_name}”, retrying without {}: {error}",
+					dropped_settings.last().copied().unwrap_or_default()),
+			Err(error) =>
+			{
+				log::error!("could not set up branch protection rule for branch “{branch_name}” \
+					in repository “{repository_name}”");
+				return Err(error);
+			},
+		}
+	}
+
+	log::info!("set up branch protection rule for branch “{branch_name}” in repository \
+		“{repository_name}”");
+
+	if !dropped_settings.is_empty()
+	{
+		log::warn!("protected branch “{branch_name}” in repository “{repository_name}” without \
+			the settings {}, which GitHub rejected", dropped_settings.join(", "));
+	}
+
+	Ok(AppliedProtection{request: protect_branch_request, dropped_settings})
+}
+
+/// Remove the next optional setting named in a validation error from a request to protect a
+/// branch, from the least to the most essential one, and record its name. Only strict status
+/// checks, status checks, code owner reviews, and dismissing stale reviews are optional, so that
+/// the branch is never protected without requiring reviews. Returns `false` if the error isn’t a
+/// validation error, such as for branches that don’t exist, or no optional setting named in it is
+/// left, in which case protecting the branch fails.
+///
+/// # Arguments
+/// - `request`: The request to protect a branch, which is updated in place.
+/// - `error`: The error GitHub rejected the request with.
+/// - `dropped_settings`: The names of the settings removed so far, which is appended to.
+fn drop_rejected_setting(request: &mut crate::ProtectBranchRequest, error: &crate::Error,
+	dropped_settings: &mut Vec<&'static str>)
+	-> bool
+{
+	let response_body = match error
+	{
+		crate::Error::ReceivedGitHubApiClientError{status_code, response_body, ..}
+			if *status_code == reqwest::StatusCode::UNPROCESSABLE_ENTITY => response_body,
+		_ => return false,
+	};
+
+	// Match whole words only, as “strict” is also part of “restrictions”, for example
+	let is_named = |names: &[&str]| response_body
+		.split(|character: char| !character.is_ascii_alphanumeric() && character != '_')
+		.any(|word| names.contains(&word));
+
+	let setting = if let Some(status_checks) = request.required_status_checks.as_mut()
+		.filter(|status_checks| status_checks.strict && is_named(&["strict"]))
+	{
+		status_checks.strict = false;
+		"strict_status_checks"
+	}
+	else if request.required_status_checks.is_some()
+		&& is_named(&["required_status_checks", "contexts"])
+	{
+		request.required_status_checks = None;
+		"required_status_checks"
+	}
+	else if let Some(reviews) = request.required_pull_request_reviews.as_mut()
+		.filter(|reviews| reviews.require_code_owner_reviews
+			&& is_named(&["require_code_owner_reviews"]))
+	{
+		reviews.require_code_owner_reviews = false;
+		"require_code_owner_reviews"
+	}
+	else if let Some(reviews) = request.required_pull_request_reviews.as_mut()
+		.filter(|reviews| reviews.dismiss_stale_reviews && is_named(&["dismiss_stale_reviews"]))
+	{
+		reviews.dismiss_stale_reviews = false;
+		"dismiss_stale_reviews"
+	}
+	else
+	{
+		return false;
+	};
+
+	dropped_settings.push(setting);
+
+	true
+}
+
+/// Describe the settings a branch was protected without, as GitHub rejected them, in a paragraph
+/// to append to notifications, which is empty if all settings were applied.
+///
+/// # Arguments
+/// - `dropped_settings`: The names of the settings that couldn’t be applied.
+fn dropped_settings_note(dropped_settings: &[&str]) -> String
+{
+	if dropped_settings.is_empty()
+	{
+		return String::new();
+	}
+
+	format!("\n\nGitHub rejected some of the settings required by our corporate policies, so the \
+		branch was protected without the following settings: {}. Please review the [branch \
+		protection rules in the repository settings](../settings/branches) and apply the missing \
+		settings if possible.", setting_list(dropped_settings))
+}
+
+/// Format the names of settings as a comma-separated list of code spans for Markdown.
+///
+/// # Arguments
+/// - `settings`: The names of the settings.
+fn setting_list(settings: &[&str]) -> String
+{
+	settings.iter()
+		.map(|setting| format!("`{setting}`"))
+		.collect::<Vec<_>>()
+		.join(", ")
+}
+
+/// Post a notification in the repository of a branch as an issue, a discussion, or a comment on the
+/// commit the branch points to, as configured, appending the attribution of this service to the
+/// body, which is truncated if it exceeds `notification.max_issue_body_length`. Returns the URL of
+/// the issue, discussion, or comment, or `None` without failing if issues or discussions are
+/// disabled for the repository, the branch has no commits to comment on, or the repository
+/// received `notification.max_per_hour` notifications within the last hour already, as the
+/// notification shouldn’t affect the outcome of protecting a branch.
+///
+/// # Arguments
+/// - `state`: The shared state, including the GitHub API client and the configuration.
+/// - `target`: The branch in whose repository to post the notification.
+/// - `title`: The title of the notification.
+/// - `body`: The body of the notification in Markdown format.
+async fn post_notification(state: &crate::AppState, target: &ProtectionTarget, title: &str,
+	mut body: String)
+	-> Result<Option<url::Url>, crate::Error>
+{
+	let config = state.config();
+
+	// If configured, stop posting notifications in repositories that received too many recently
+	if let Some(max_per_hour) = config.notification.max_per_hour
+	{
+		let repository = format!("{}/{}", target.organization_name, target.repository_name);
+
+		if !state.notification_limiter.try_acquire(&repository, max_per_hour)
+		{
+			log::warn!("skipping notification “{title}” in repository “{repository}”, which \
+				received {max_per_hour} notifications within the last hour already");
+			return Ok(None);
+		}
+	}
+
+	// Commit comments have no title of their own, so they start with the title in bold, which
+	// needs to fit within the limit, too
+	let notification_target = config.notification.target;
+
+	if notification_target == crate::NotificationTarget::CommitComment
+	{
+		body = format!("**{title}**\n\n{body}");
+	}
+
+	// If configured, mention teams so that they’re notified, too
+	let notify_teams = &config.notification.notify_teams;
+	let mut suffix = String::new();
+
+	if !notify_teams.is_empty()
+	{
+		let mentions: Vec<_> = notify_teams.iter()
+			.map(|team_slug| format!("@{}/{team_slug}", target.organization_name))
+			.collect();
+
+		suffix.push_str(&format!("\n\ncc {}", mentions.join(" ")));
+	}
+
+	// State that this notification was created by this service and mark it as such for tooling
+	suffix.push_str(&issue_attribution(notification_target));
+
+	// Truncate the body rather than the suffix, so that the mentions and the marker are kept
+	let max_length = config.notification.max_issue_body_length
+		.saturating_sub(suffix.chars().count());
+
+	if truncate_issue_body(&mut body, max_length)
+	{
+		log::warn!("truncated body of notification “{title}” in repository “{}” to \
+			{max_length} characters", target.repository_name);
+	}
+
+	body.push_str(&suffix);
+
+	if notification_target == crate::NotificationTarget::CommitComment
+	{
+		return post_commit_comment(state, target, title, &body).await;
+	}
+
+	if notification_target == crate::NotificationTarget::Discussion
+	{
+		let category_name = config.notification.discussion_category.as_deref()
+			.expect("this call is infallible because the discussion category was validated");
+
+		return match state.github_api_client.create_discussion(&target.organization_name,
+			&target.repository_name, category_name, title, &body).await
+		{
+			Ok(discussion) => Ok(Some(discussion.url)),
+			Err(crate::Error::DiscussionsDisabled{..}) =>
+			{
+				log::warn!("discussions are disabled for repository “{}”, skipping notification \
+					“{title}”", target.repository_name);
+				Ok(None)
+			},
+			Err(error) => Err(error),
+		};
+	}
+
+	let create_issue_request_body = crate::CreateIssueRequest
+	{
+		title,
+		body: Some(&body),
+	};
+
+	match state.github_api_client.create_issue(&target.organization_name, &target.repository_name,
+		&create_issue_request_body).await
+	{
+		Ok(created_issue) =>
+		{
+			if config.notification.pin_issue
+			{
+				pin_issue(state, &created_issue).await;
+			}
+
+			Ok(Some(created_issue.html_url))
+		},
+		// GitHub responds with 410 Gone for repositories with issues disabled
+		Err(error) if error.is_client_error_with_status(reqwest::StatusCode::GONE) =>
+		{
+			log::warn!("issues are disabled for repository “{}”, skipping notification “{title}”",
+				target.repository_name);
+			Ok(None)
+		},
+		Err(error) => Err(error),
+	}
+}
+
+/// Post a notification as a comment on the commit a branch points to, whose body starts with the
+/// title in bold already, as commit comments have no title of their own. Branches without commits
+/// are skipped with a warning (for arguments and the return value, see [post_notification]).
+async fn post_commit_comment(state: &crate::AppState, target: &ProtectionTarget, title: &str,
+	body: &str)
+	-> Result<Option<url::Url>, crate::Error>
+{
+	let ProtectionTarget{organization_name, repository_name, branch_name, ..} = target;
+
+	let head_sha = match branch_head_sha(state, target).await?
+	{
+		Some(head_sha) => head_sha,
+		None =>
+		{
+			log::warn!("branch “{branch_name}” in repository “{repository_name}” has no commits \
+				to comment on, skipping notification “{title}”");
+			return Ok(None);
+		},
+	};
+
+	let comment = state.github_api_client.create_commit_comment(organization_name,
+		repository_name, &head_sha, &crate::CreateCommitCommentRequest{body}).await?;
+
+	Ok(Some(comment.html_url))
+}
+
+/// Look up the SHA of the commit a branch points to, returning `None` if the branch has no commits
+/// yet, such as in empty repositories, for which GitHub doesn’t find the branch.
+///
+/// # Arguments
+/// - `state`: The shared state, including the GitHub API client.
+/// - `target`: The branch to look up.
+async fn branch_head_sha(state: &crate::AppState, target: &ProtectionTarget)
+	-> Result<Option<String>, crate::Error>
+{
+	let ProtectionTarget{organization_name, repository_name, branch_name, ..} = target;
+
+	match state.github_api_client.get_branch(organization_name, repository_name, branch_name).await
+	{
+		Ok(branch) => Ok(branch.commit.map(|commit| commit.sha)),
+		Err(error) if error.is_client_error_with_status(reqwest::StatusCode::NOT_FOUND) => Ok(None),
+		Err(error) => Err(error),
+	}
+}
+
+/// Add a line about a newly protected branch to the repository’s status issue, which is created
+/// if there isn’t an open one yet. The status issue is found via GitHub’s issue search by its
+/// title and [STATUS_ISSUE_MARKER], only considering issues opened by this GitHub App, so that
+/// others can’t have their issues edited by copying the marker. Updates are serialized per
+/// repository so that concurrent events don’t overwrite each other’s lines. As the search index
+/// lags behind briefly, branches protected within a few seconds of the status issue’s creation may
+/// still create another status issue. Once a status issue would exceed
+/// `notification.max_issue_body_length`, a new one referring to it is created instead, and the full
+/// one is closed. Returns the URL of the status issue, or `None` if issues are disabled for the
+/// repository.
+///
+/// # Arguments
+/// - `state`: The shared state, including the GitHub API client and the configuration.
+/// - `target`: The branch that was protected.
+/// - `dropped_settings`: The names of the settings GitHub rejected, which are listed in the line.
+async fn update_status_issue(state: &crate::AppState, target: &ProtectionTarget,
+	dropped_settings: &[&str])
+	-> Result<Option<url::Url>, crate::Error>
+{
+	let ProtectionTarget{organization_name, repository_name, branch_name, creator_name, ..} =
+		target;
+
+	let dropped_settings = match dropped_settings.is_empty()
+	{
+		true => String::new(),
+		false => format!(" without the rejected settings {}", setting_list(dropped_settings)),
+	};
+	let status_line = format!("- {} [`{branch_name}`]({}) protected{dropped_settings} on {} \
+		(created by {creator_name})", target.branch_description(), target.branch_link(),
+		chrono::Utc::now().with_timezone(&state.config().display_timezone()).format("%Y-%m-%d"));
+
+	let bot_login = state.github_api_client.app_metadata().await?.bot_login();
+	let _lock = state.status_issue_locks.lock(organization_name, repository_name).await;
+
+	let query = format!("repo:{organization_name}/{repository_name} is:issue is:open in:title \
+		\"{STATUS_ISSUE_TITLE}\"");
+	let status_issue = state.github_api_client.search_issues(&query).await?.into_iter()
+		.find(|issue| issue.user.as_ref().is_some_and(|user| user.login == bot_login)
+			&& issue.body.as_deref().is_some_and(|body| body.contains(STATUS_ISSUE_MARKER)));
+
+	let status_issue = match status_issue
+	{
+		Some(status_issue) => status_issue,
+		None =>
+		{
+			let body = new_status_issue_body(&status_line, None);
+			return post_notification(state, target, STATUS_ISSUE_TITLE, body).await;
+		},
+	};
+
+	// New lines are inserted right before the marker, which ends the list of protected branches
+	let body = status_issue.body.as_deref().unwrap_or_default()
+		.replacen(STATUS_ISSUE_MARKER, &format!("{status_line}\n{STATUS_ISSUE_MARKER}"), 1);
+
+	// Rather than truncating the list of protected branches, continue it in a new status issue and
+	// close the full one, so that it isn’t found again
+	if body.chars().count() > state.config().notification.max_issue_body_length
+	{
+		let body = new_status_issue_body(&status_line, Some(&status_issue.html_url));
+		let new_status_issue_url =
+			post_notification(state, target, STATUS_ISSUE_TITLE, body).await?;
+
+		if new_status_issue_url.is_some()
+		{
+			state.github_api_client.update_issue(organization_name, repository_name,
+				status_issue.number, &crate::UpdateIssueRequest{body: None, state: Some("closed")})
+				.await?;
+
+			log::info!("closed full status issue {}", status_issue.html_url);
+		}
+
+		return Ok(new_status_issue_url);
+	}
+
+	state.github_api_client.update_issue(organization_name, repository_name, status_issue.number,
+		&crate::UpdateIssueRequest{body: Some(&body), state: None}).await?;
+
+	log::info!("added branch “{branch_name}” to status issue {}", status_issue.html_url);
+
+	Ok(Some(status_issue.html_url))
+}
+
+/// Build the body of a new status issue listing a single protected branch.
+///
+/// # Arguments
+/// - `status_line`: The line about the protected branch.
+/// - `previous_status_issue_url`: The URL of the full status issue this one continues, if any.
+fn new_status_issue_body(status_line: &str, previous_status_issue_url: Option<&url::Url>)
+	-> String
+{
+	let continuation = previous_status_issue_url
+		.map(|url| format!(" This continues the list of branches in {url}."))
+		.unwrap_or_default();
+
+	format!("This issue keeps track of the branches in this repository that were automatically \
+		protected to comply with our corporate policies. Please submit pull requests in order to \
+		contribute changes to these branches, as direct pushes are not allowed. Please review the \
+		[branch protection rules in the repository settings](../settings/branches) and extend them \
+		as necessary.{continuation}\
+		\n\
+		\n\
+		{status_line}\n\
+		{STATUS_ISSUE_MARKER}")
+}
+
+/// Pin a created issue, logging failures instead of returning them, as pinning is only for
+/// visibility and fails regularly once a repository has the maximum number of pinned issues.
+///
+/// # Arguments
+/// - `state`: The shared state, including the GitHub API client.
+/// - `issue`: The created issue.
+async fn pin_issue(state: &crate::AppState, issue: &crate::CreateIssueResponse)
+{
+	match state.github_api_client.pin_issue(&issue.node_id).await
+	{
+		Ok(()) => log::info!("pinned issue {}", issue.html_url),
+		Err(crate::Error::GraphQlRequestFailed(message))
+			if message.to_lowercase().contains(PINNED_ISSUE_LIMIT_MESSAGE) =>
+			log::warn!("could not pin issue {}, as the repository already has the maximum number \
+				of pinned issues", issue.html_url),
+		Err(error) => log::warn!("could not pin issue {}: {error}", issue.html_url),
+	}
+}
+
+/// Truncate the body of an issue or discussion to a maximum number of characters (not bytes),
+/// ending it with [TRUNCATION_MARKER] if it was truncated, and return whether this was the case.
+///
+/// # Arguments
+/// - `body`: The body in Markdown format.
+/// - `max_length`: The maximum number of characters, including the truncation marker.
+fn truncate_issue_body(body: &mut String, max_length: usize) -> bool
+{
+	if body.chars().count() <= max_length
+	{
+		return false;
+	}
+
+	let kept_length = max_length.saturating_sub(TRUNCATION_MARKER.chars().count());
+
+	// Cut at the byte offset of the first dropped character, which is always a char boundary
+	let end = body.char_indices().nth(kept_length).map_or(body.len(), |(index, _)| index);
+	body.truncate(end);
+	body.push_str(TRUNCATION_MARKER);
+
+	true
+}
+
+/// Substitute the placeholders in a configured issue body template in a single pass, so that
+/// placeholders contained in substituted values, such as branch names, are kept as is. Unknown
+/// placeholders and all other text, including whitespace and Markdown task lists, are kept
+/// unchanged.
+///
+/// # Arguments
+/// - `template`: The issue body template.
+/// - `target`: The branch that was protected.
+/// - `rules_summary`: The summary of the applied branch protection rules (see [rules_summary]).
+/// - `timestamp`: When the branch was protected, formatted for display.
+fn render_issue_body_template(template: &str, target: &ProtectionTarget, rules_summary: &str,
+	timestamp: &str)
+	-> String
+{
+	let mut body = String::with_capacity(template.len());
+	let mut remainder = template;
+
+	while let Some(start) = remainder.find('{')
+	{
+		body.push_str(&remainder[..start]);
+		remainder = &remainder[start..];
+
+		let placeholder = remainder.find('}').and_then(|end|
+		{
+			let value: &str = match &remainder[1..end]
+			{
+				"creator" => &target.creator_name,
+				"organization" => &target.organization_name,
+				"repository" => &target.repository_name,
+				"branch" => &target.branch_name,
+				"rules_summary" => rules_summary,
+				"timestamp" => timestamp,
+				_ => return None,
+			};
+
+			Some((value, end))
+		});
+
+		match placeholder
+		{
+			Some((value, end)) =>
+			{
+				body.push_str(value);
+				remainder = &remainder[end + 1..];
+			},
+			None =>
+			{
+				body.push('{');
+				remainder = &remainder[1..];
+			},
+		}
+	}
+
+	body.push_str(remainder);
+
+	body
+}
+
+/// Summarize the rules a branch protection request enforces in prose for notifications, such as
+/// “requires 2 approving reviews, enforces the rules for administrators, requires the status
+/// checks ci/build”. Rules that aren’t set are described as not being enforced, so that the
+/// summary is accurate for all requests.
+///
+/// # Arguments
+/// - `request`: The applied branch protection request.
+fn rules_summary(request: &crate::ProtectBranchRequest) -> String
+{
+	let mut rules = vec![];
+
+	match &request.required_pull_request_reviews
+	{
+		Some(reviews) =>
+		{
+			rules.push(match reviews.required_approving_review_count
+			{
+				0 => "requires pull requests without approving reviews".to_string(),
+				1 => "requires 1 approving review".to_string(),
+				count => format!("requires {count} approving reviews"),
+			});
+
+			if reviews.dismiss_stale_reviews
+			{
+				rules.push("dismisses stale reviews".to_string());
+			}
+
+			if reviews.require_code_owner_reviews
+			{
+				rules.push("requires reviews by code owners".to_string());
+			}
+		},
+		None => rules.push("doesn’t require pull requests".to_string()),
+	}
+
+	match request.enforce_admins
+	{
+		Some(true) => rules.push("enforces the rules for administrators".to_string()),
+		_ => rules.push("doesn’t enforce the rules for administrators".to_string()),
+	}
+
+	match &request.required_status_checks
+	{
+		Some(status_checks) if !status_checks.contexts.is_empty() =>
+		{
+			let up_to_date = match status_checks.strict
+			{
+				true => " on up-to-date branches",
+				false => "",
+			};
+
+			rules.push(format!("requires the status checks {}{up_to_date}",
+				status_checks.contexts.join(", ")));
+		},
+		_ => rules.push("doesn’t require status checks".to_string()),
+	}
+
+	rules.join(", ")
+}
+
+/// Determine the changes protecting a branch would make to its current protection settings without
+/// applying anything, for dry runs.
+///
+/// # Arguments
+/// - `state`: The shared state, including the GitHub API client and the configuration.
+/// - `target`: The branch that would be protected.
+async fn preview_branch_protection(state: &crate::AppState, target: &ProtectionTarget)
+	-> HandledOutcome
+{
+	let ProtectionTarget{organization_name, repository_name, branch_name, ..} = target;
+
+	let protect_branch_request = target.branch_protection(&state.config())
+		.protect_branch_request();
+
+	log::info!("dry run: would protect branch “{branch_name}” in repository “{repository_name}” \
+		with {}", serde_json::to_string(&protect_branch_request)
+			.expect("this call is infallible because branch protection requests are plain data"));
+
+	match state.github_api_client.get_branch_protection(organization_name, repository_name,
+		branch_name).await
+	{
+		Ok(current) => HandledOutcome::DryRun
+		{
+			changes: crate::protection_changes(current.as_ref(), &protect_branch_request),
+		},
+		// The failure is logged when the outcome is reported
+		Err(error) => HandledOutcome::Failed(error),
+	}
+}
+
+/// Record a check run with the applied settings on the commit a freshly protected branch points to.
+/// Failures are only logged, as the check run is merely informational.
+///
+/// # Arguments
+/// - `state`: The shared state, including the GitHub API client and the configuration.
+/// - `target`: The branch that was protected.
+/// - `applied_protection`: The protection as it was applied.
+async fn report_check_run(state: &crate::AppState, target: &ProtectionTarget,
+	applied_protection: &AppliedProtection)
+{
+	let ProtectionTarget{organization_name, repository_name, branch_name, ..} = target;
+	let github_api_client = &state.github_api_client;
+
+	let head_sha = match branch_head_sha(state, target).await
+	{
+		Ok(Some(head_sha)) => head_sha,
+		Ok(None) =>
+		{
+			log::warn!("could not report check run, as the commit of branch “{branch_name}” in \
+				repository “{repository_name}” is unknown");
+			return;
+		},
+		Err(error) =>
+		{
+			log::warn!("could not look up branch “{branch_name}” in repository \
+				“{repository_name}” to report check run: {error}");
+			return;
+		},
+	};
+
+	let mut summary = format!("The branch `{branch_name}` was protected with the following rules: \
+		it {}.", rules_summary(&applied_protection.request));
+
+	if !applied_protection.dropped_settings.is_empty()
+	{
+		summary.push_str(&format!(" GitHub rejected the settings {}, which were left out.",
+			setting_list(&applied_protection.dropped_settings)));
+	}
+
+	match github_api_client.create_check_run(organization_name, repository_name, &head_sha,
+		"success", &summary).await
+	{
+		Ok(check_run) => log::info!("reported check run: {}", check_run.html_url),
+		// GitHub responds with 403 Forbidden if the GitHub App lacks the checks:write permission
+		Err(error) if error.is_client_error_with_status(reqwest::StatusCode::FORBIDDEN) =>
+			log::warn!("could not report check run in repository “{repository_name}”, make sure \
+				that the GitHub App has the “Checks” permission set to “Read & write”"),
+		Err(error) =>
+			log::warn!("could not report check run in repository “{repository_name}”: {error}"),
+	}
+}
+
+/// Check whether a repository is tagged with at least one of the required topics if configured,
+/// returning the outcome of ignoring the event otherwise.
+///
+/// # Arguments
+/// - `state`: The shared state, including the GitHub API client and the configuration.
+/// - `target`: The branch to protect.
+async fn check_required_topics(state: &crate::AppState, target: &ProtectionTarget)
+	-> Result<(), HandledOutcome>
+{
+	let config = state.config();
+	let required_topics = &config.required_topics;
+
+	if required_topics.is_empty()
+	{
+		return Ok(());
+	}
+
+	let topics = match repository_topics(&state.github_api_client, &state.topics_cache,
+		&target.organization_name, &target.repository_name).await
+	{
+		Ok(topics) => topics,
+		Err(error) =>
+		{
+			log::error!("could not look up topics of repository “{}”", target.repository_name);
+			return Err(HandledOutcome::Failed(error));
+		}
+	};
+
+	match topics.iter().any(|topic| required_topics.contains(topic))
+	{
+		true => Ok(()),
+		false => Err(HandledOutcome::Ignored(
+			"repository isn’t tagged with any of the required topics".into())),
+	}
+}
+
+/// Check whether a repository opted out of being protected by containing the opt-out file if
+/// configured, returning the outcome of ignoring the event if so. Results are cached briefly, as
+/// several branches of a repository may be handled in short succession.
+///
+/// # Arguments
+/// - `state`: The shared state, including the GitHub API client and the configuration.
+/// - `target`: The branch to protect.
+async fn check_opt_out(state: &crate::AppState, target: &ProtectionTarget)
+	-> Result<(), HandledOutcome>
+{
+	let config = state.config();
+
+	let opt_out_file = match &config.opt_out_file
+	{
+		Some(opt_out_file) => opt_out_file,
+		None => return Ok(()),
+	};
+
+	let cache_key = format!("{}/{}", target.organization_name, target.repository_name);
+
+	let opted_out = match state.opt_out_cache.get(&cache_key)
+	{
+		Some(opted_out) => opted_out,
+		None =>
+		{
+			let result = state.github_api_client.file_exists(&target.organization_name,
+				&target.repository_name, opt_out_file).await;
+
+			match result
+			{
+				Ok(opted_out) =>
+				{
+					state.opt_out_cache.insert(cache_key, opted_out);
+					opted_out
+				},
+				Err(error) =>
+				{
+					log::error!("could not look up opt-out file in repository “{}”",
+						target.repository_name);
+					return Err(HandledOutcome::Failed(error));
+				},
+			}
+		},
+	};
+
+	if opted_out
+	{
+		log::info!("not protecting branch “{}” in repository “{}”, which opted out by containing \
+			“{opt_out_file}”", target.branch_name, target.repository_name);
+		return Err(HandledOutcome::Ignored(
+			"repository opted out of automatic branch protection".into()));
+	}
+
+	Ok(())
+}
+
+/// Check whether a branch has a head commit, returning the outcome of ignoring the event
+/// otherwise, so that the branch is protected once it’s pushed to instead (if
+/// `protect_on_first_push` is enabled).
+///
+/// # Arguments
+/// - `state`: The shared state, including the GitHub API client and the configuration.
+/// - `target`: The branch to protect.
+async fn check_branch_has_commits(state: &crate::AppState, target: &ProtectionTarget)
+	-> Result<(), HandledOutcome>
+{
+	let result = state.github_api_client.get_branch(&target.organization_name,
+		&target.repository_name, &target.branch_name).await;
+
+	let has_commits = match result
+	{
+		Ok(branch) => branch.commit.is_some(),
+		// GitHub doesn’t know branches without commits yet
+		Err(error) if error.is_client_error_with_status(reqwest::StatusCode::NOT_FOUND) => false,
+		Err(error) =>
+		{
+			log::error!("could not look up branch “{}” in repository “{}”", target.branch_name,
+				target.repository_name);
+			return Err(HandledOutcome::Failed(error));
+		},
+	};
+
+	match has_commits
+	{
+		true => Ok(()),
+		false =>
+		{
+			log::info!("not protecting branch “{}” in repository “{}” yet, as it doesn’t have \
+				any commits", target.branch_name, target.repository_name);
+			Err(HandledOutcome::Ignored("branch doesn’t have any commits yet".into()))
+		},
+	}
+}
+
+/// Build the attribution appended to the body of all issues and discussions created by this
+/// service. Apart from a human-readable note, this includes [ISSUE_MARKER] to reliably identify
+/// them later.
+fn issue_attribution(notification_target: crate::NotificationTarget) -> String
+{
+	let kind = match notification_target
+	{
+		crate::NotificationTarget::Issue => "issue",
+		crate::NotificationTarget::Discussion => "discussion",
+		crate::NotificationTarget::CommitComment => "comment",
+	};
+
+	format!("\n\n---\n<sub>This {kind} was created automatically by [{}]({}) {}.</sub>\n\
+		{ISSUE_MARKER}",
+		env!("CARGO_PKG_NAME"), env!("CARGO_PKG_HOMEPAGE"), env!("CARGO_PKG_VERSION"))
+}
+
+/// Look up the topics a repository is tagged with, using recently cached results if available.
+///
+/// # Arguments
+/// - `github_api_client`: A handle to the GitHub API client.
+/// - `topics_cache`: A handle to the cache of recently looked-up repository topics.
+/// - `organization_name`: The organization owning the repository.
+/// - `repository_name`: The name of the repository.
+async fn repository_topics(
+	github_api_client: &crate::github_api::Client,
+	topics_cache: &crate::ExpiringCache<String, Vec<String>>,
+	organization_name: &str,
+	repository_name: &str)
+	-> Result<Vec<String>, crate::Error>
+{
+	let cache_key = format!("{organization_name}/{repository_name}");
+
+	if let Some(topics) = topics_cache.get(&cache_key)
+	{
+		return Ok(topics);
+	}
+
+	let response =
+		github_api_client.get_repository_topics(organization_name, repository_name).await?;
+
+	topics_cache.insert(cache_key, response.names.clone());
+
+	Ok(response.names)
+}
+
+/// Machine-readable marker embedded into the body of all issues created by this service (as an
+/// HTML comment, which GitHub doesn’t render). Tooling can search for this marker to find these
+/// issues.
+pub const ISSUE_MARKER: &str = "<!-- branch-autoprotector:v1 -->";
+
+/// Machine-readable marker embedded into the body of status issues (see
+/// `notification.status_issue`) right after the list of protected branches, which identifies
+/// status issues and marks where to add further branches.
+pub const STATUS_ISSUE_MARKER: &str = "<!-- branch-autoprotector:status:v1 -->";
+
+/// Appended to issue and discussion bodies that were truncated to
+/// `notification.max_issue_body_length`.
+const TRUNCATION_MARKER: &str = "\n\n…(truncated)";
+
+/// The format of the `{timestamp}` placeholder of issue body templates, such as
+/// “2024-03-01 14:30 CET”.
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M %Z";
+
+/// The title of status issues (see `notification.status_issue`).
+const STATUS_ISSUE_TITLE: &str = "Branch protection status";
+
+/// Part of the message GitHub responds with when pinning an issue in a repository that already has
+/// the maximum number of pinned issues (in lowercase).
+const PINNED_ISSUE_LIMIT_MESSAGE: &str = "pinned issues";
+
+/// How long the topics of a repository are cached after looking them up.
+pub const TOPICS_CACHE_TIME_TO_LIVE: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
+/// How long whether a repository contains the opt-out file is cached after looking it up, which is
+/// short so that opting out takes effect quickly.
+pub const OPT_OUT_CACHE_TIME_TO_LIVE: std::time::Duration = std::time::Duration::from_secs(60);
+
+#[cfg(test)]
+mod tests
+{
+	/// Decode a push event creating a branch in a repository whose default branch is `main`.
+	fn push_event(branch_name: &str) -> crate::PushEventPayload
+	{
+		serde_json::from_value(serde_json::json!(
+		{
+			"ref": format!("refs/heads/{branch_name}"),
+			"created": true,
+			"repository":
+			{
+				"name": "example-repository",
+				"owner": {"login": "example-organization"},
+				"default_branch": "main",
+			},
+			"sender": {"login": "octocat"},
+		}))
+		.unwrap()
+	}
+
+	#[test]
+	fn push_event_ignores_pushes_not_creating_default_branch()
+	{
+		let config =
+			crate::test_support::config(serde_json::json!({"protect_on_first_push": true}));
+
+		let target = super::ProtectionTarget::from_push_event(push_event("main"), &config)
+			.unwrap();
+		assert_eq!(target.organization_name, "example-organization");
+		assert_eq!(target.repository_name, "example-repository");
+		assert_eq!(target.creator_name, "octocat");
+
+		let mut existing_branch_push = push_event("main");
+		existing_branch_push.created = false;
+		let mut tag_push = push_event("main");
+		tag_push.ref_ = "refs/tags/main".into();
+
+		for payload in [existing_branch_push, tag_push, push_event("feature")]
+		{
+			let outcome = super::ProtectionTarget::from_push_event(payload, &config);
+			assert!(matches!(outcome, Err(super::HandledOutcome::Ignored(_))), "{outcome:?}");
+		}
+
+		// Pushes are only acted on if configured
+		let outcome = super::ProtectionTarget::from_push_event(push_event("main"),
+			&crate::test_support::config(serde_json::json!({})));
+		assert!(matches!(outcome, Err(super::HandledOutcome::Ignored(_))), "{outcome:?}");
+	}
+
+	#[test]
+	fn push_event_protects_explicitly_configured_branches_only()
+	{
+		let config = crate::test_support::config(serde_json::json!(
+		{
+			"protect_on_first_push": true,
+			"protected_branches": ["release"],
+		}));
+
+		let target = super::ProtectionTarget::from_push_event(push_event("release"), &config)
+			.unwrap();
+		assert_eq!(target.branch_name, "release");
+		assert!(!target.is_default_branch);
+
+		let target = super::ProtectionTarget::from_push_event(push_event("main"), &config)
+			.unwrap();
+		assert!(target.is_default_branch);
+
+		for branch_name in ["Release", "release/1.0", "feature"]
+		{
+			assert!(matches!(super::ProtectionTarget::from_push_event(push_event(branch_name),
+				&config), Err(super::HandledOutcome::Ignored(_))), "{branch_name}");
+		}
+	}
+
+	#[test]
+	fn push_event_ignores_branches_matching_deny_patterns()
+	{
+		let config = crate::test_support::config(serde_json::json!(
+		{
+			"protect_on_first_push": true,
+			"protected_branch_patterns": ["release/*", "tmp/**"],
+			"branch_deny_patterns": ["*/experimental", "main"],
+		}));
+
+		assert!(super::ProtectionTarget::from_push_event(push_event("release/1.0"), &config)
+			.is_ok());
+
+		// Deny patterns take precedence over both protected branch patterns and default branches
+		for (branch_name, deny_pattern) in
+			[("release/experimental", "*/experimental"), ("main", "main")]
+		{
+			let outcome =
+				super::ProtectionTarget::from_push_event(push_event(branch_name), &config);
+			assert!(matches!(&outcome, Err(super::HandledOutcome::Ignored(reason))
+				if reason.contains(deny_pattern)), "{outcome:?}");
+		}
+
+		// Deny patterns match with the same syntax as protected branch patterns
+		assert!(super::ProtectionTarget::from_push_event(push_event("tmp/a/experimental"),
+			&config).is_ok());
+	}
+
+	#[test]
+	fn push_event_protects_forks_only_if_configured()
+	{
+		let push_event_in_fork = ||
+		{
+			let mut payload = push_event("main");
+			payload.repository.fork = true;
+			payload
+		};
+
+		let config =
+			crate::test_support::config(serde_json::json!({"protect_on_first_push": true}));
+		let outcome = super::ProtectionTarget::from_push_event(push_event_in_fork(), &config);
+		assert!(matches!(outcome, Err(super::HandledOutcome::Ignored(_))), "{outcome:?}");
+
+		let config = crate::test_support::config(serde_json::json!(
+			{"protect_on_first_push": true, "protect_forks": true}));
+		let target = super::ProtectionTarget::from_push_event(push_event_in_fork(), &config)
+			.unwrap();
+		assert!(target.is_default_branch);
+	}
+
+	/// Decode a create event for a branch in the example repository without the name of the
+	/// default branch, as sent by some webhook proxies.
+	fn create_event_without_default_branch(branch_name: &str) -> crate::RefCreationEventPayload
+	{
+		serde_json::from_value(serde_json::json!(
+		{
+			"ref": branch_name,
+			"ref_type": "branch",
+			"repository":
+			{
+				"name": "example-repository",
+				"owner": {"login": "example-organization"},
+			},
+			"sender": {"login": "octocat"},
+		}))
+		.unwrap()
+	}
+
+	#[tokio::test]
+	async fn create_event_without_default_branch_looks_up_default_branch()
+	{
+		let mock_github = crate::test_support::MockGitHub::new();
+		mock_github.respond("GET", REPOSITORY_ENDPOINT, 200, serde_json::json!(
+		{
+			"name": "example-repository",
+			"owner": {"login": "example-organization"},
+			"default_branch": "main",
+		}));
+		let state = mock_github.state(serde_json::json!({})).await;
+
+		let mut creation = super::ProtectionTarget::from_ref_creation_event(
+			create_event_without_default_branch("main"), &state.config()).unwrap();
+
+		// The default branch is only looked up after the event has been acknowledged
+		assert!(mock_github.requests("GET", REPOSITORY_ENDPOINT).is_empty());
+
+		creation.resolve(&state.config(), &state.github_api_client).await.unwrap();
+
+		assert_eq!(creation.target.branch_name, "main");
+		assert!(creation.target.is_default_branch);
+		assert_eq!(mock_github.requests("GET", REPOSITORY_ENDPOINT).len(), 1);
+
+		let mut creation = super::ProtectionTarget::from_ref_creation_event(
+			create_event_without_default_branch("feature"), &state.config()).unwrap();
+		let outcome = creation.resolve(&state.config(), &state.github_api_client).await;
+
+		assert!(matches!(outcome, Err(super::HandledOutcome::Ignored(_))), "{outcome:?}");
+	}
+
+	#[tokio::test]
+	async fn create_event_checks_for_open_pull_requests_only_when_resolving()
+	{
+		let create_event = serde_json::from_value(serde_json::json!(
+		{
+			"ref": "feature",
+			"ref_type": "branch",
+			"master_branch": "main",
+			"repository":
+			{
+				"name": "example-repository",
+				"owner": {"login": "example-organization"},
+			},
+			"sender": {"login": "octocat"},
+		}))
+		.unwrap();
+		let pull_requests_endpoint = format!(
+			"{REPOSITORY_ENDPOINT}/pulls?state=open&head={}&per_page=1",
+			crate::github_api::encode_path_segment("example-organization:feature"));
+
+		let mock_github = crate::test_support::MockGitHub::new();
+		mock_github.respond("GET", &pull_requests_endpoint, 200, serde_json::json!([]));
+		let state = mock_github.state(serde_json::json!(
+			{"branch_predicates": {"protect_if_has_open_pr": true}})).await;
+
+		let mut creation = super::ProtectionTarget::from_ref_creation_event(create_event,
+			&state.config()).unwrap();
+
+		assert!(mock_github.requests("GET", &pull_requests_endpoint).is_empty());
+
+		let outcome = creation.resolve(&state.config(), &state.github_api_client).await;
+
+		assert!(matches!(outcome, Err(super::HandledOutcome::Ignored(_))), "{outcome:?}");
+		assert_eq!(mock_github.requests("GET", &pull_requests_endpoint).len(), 1);
+	}
+
+	#[tokio::test]
+	async fn create_event_for_tag_named_like_branch_protects_only_branch()
+	{
+		let create_event = |ref_type: &str| -> crate::RefCreationEventPayload
+		{
+			serde_json::from_value(serde_json::json!(
+			{
+				"ref": "release/1.0",
+				"ref_type": ref_type,
+				"master_branch": "release/1.0",
+				"repository":
+				{
+					"name": "example-repository",
+					"owner": {"login": "example-organization"},
+				},
+				"sender": {"login": "octocat"},
+			}))
+			.unwrap()
+		};
+
+		let branch_protection_endpoint = format!("{REPOSITORY_ENDPOINT}/branches/{}/protection",
+			crate::github_api::encode_path_segment("release/1.0"));
+		assert_eq!(branch_protection_endpoint,
+			"repos/example-organization/example-repository/branches/release%2F1.0/protection");
+
+		let mock_github = crate::test_support::MockGitHub::new();
+		mock_github
+			.respond("PUT", &branch_protection_endpoint, 200, serde_json::json!({}))
+			.respond("POST", ISSUES_ENDPOINT, 201, serde_json::json!(
+			{
+				"html_url": "https://github.com/example-organization/example-repository/issues/1",
+				"node_id": "I_1",
+			}));
+		let state = mock_github.state(serde_json::json!({})).await;
+
+		let outcome = super::ProtectionTarget::from_ref_creation_event(create_event("tag"),
+			&state.config());
+
+		assert!(matches!(outcome, Err(super::HandledOutcome::Ignored(_))), "{outcome:?}");
+
+		let mut creation = super::ProtectionTarget::from_ref_creation_event(
+			create_event("branch"), &state.config()).unwrap();
+		creation.resolve(&state.config(), &state.github_api_client).await.unwrap();
+		let outcome = super::protect_default_branch(&state, &creation.target).await;
+
+		assert!(matches!(outcome, super::HandledOutcome::Protected{..}), "{outcome:?}");
+		assert_eq!(mock_github.requests("PUT", &branch_protection_endpoint).len(), 1);
+	}
+
+	/// The default branch of the example repository, as created by `octocat`.
+	fn default_branch_target() -> super::ProtectionTarget
+	{
+		super::ProtectionTarget
+		{
+			organization_name: "example-organization".into(),
+			repository_name: "example-repository".into(),
+			branch_name: "main".into(),
+			is_default_branch: true,
+			creator_name: "octocat".into(),
+			received_at: std::time::Instant::now(),
+			event_type: "create",
+			policy: None,
+		}
+	}
+
+	#[test]
+	fn branch_link_encodes_each_segment_of_branch_name()
+	{
+		let mut target = default_branch_target();
+		assert_eq!(target.branch_link(), "../tree/main");
+
+		target.branch_name = "feature/fix-(#42)".into();
+		assert_eq!(target.branch_link(), "../tree/feature/fix-%28%2342%29");
+	}
+
+	/// Answer the requests protecting the default branch of the example repository, which is
+	/// protected already without enforcing the rules for administrators, and creating an issue.
+	fn respond_to_protecting_existing_protection(mock_github: &crate::test_support::MockGitHub)
+	{
+		mock_github
+			.respond("GET", BRANCH_PROTECTION_ENDPOINT, 200, serde_json::json!(
+			{
+				"required_pull_request_reviews": {"required_approving_review_count": 1},
+				"enforce_admins": {"enabled": false},
+			}))
+			.respond("PUT", BRANCH_PROTECTION_ENDPOINT, 200, serde_json::json!({}))
+			.respond("POST", ISSUES_ENDPOINT, 201, serde_json::json!(
+			{
+				"html_url": "https://github.com/example-organization/example-repository/issues/1",
+				"node_id": "I_1",
+			}));
+	}
+
+	#[tokio::test]
+	async fn existing_protection_policy_skip_leaves_existing_protection()
+	{
+		let mock_github = crate::test_support::MockGitHub::new();
+		respond_to_protecting_existing_protection(&mock_github);
+		let state = mock_github.state(serde_json::json!(
+			{"existing_protection_policy": "skip"})).await;
+
+		let outcome =
+			super::protect_default_branch_if_unprotected(&state, &default_branch_target()).await;
+
+		assert!(matches!(outcome, super::HandledOutcome::Ignored(_)), "{outcome:?}");
+		assert!(mock_github.requests("PUT", BRANCH_PROTECTION_ENDPOINT).is_empty());
+		assert!(mock_github.requests("POST", ISSUES_ENDPOINT).is_empty());
+	}
+
+	#[tokio::test]
+	async fn existing_protection_policy_overwrite_replaces_differing_protection()
+	{
+		let mock_github = crate::test_support::MockGitHub::new();
+		respond_to_protecting_existing_protection(&mock_github);
+		let state = mock_github.state(serde_json::json!(
+			{"existing_protection_policy": "overwrite"})).await;
+
+		let outcome =
+			super::protect_default_branch_if_unprotected(&state, &default_branch_target()).await;
+
+		assert!(matches!(outcome, super::HandledOutcome::Protected{issue_url: Some(_)}),
+			"{outcome:?}");
+
+		let requests = mock_github.requests("PUT", BRANCH_PROTECTION_ENDPOINT);
+		assert_eq!(requests.len(), 1);
+		assert_eq!(requests[0]["enforce_admins"], true);
+	}
+
+	#[tokio::test]
+	async fn existing_protection_policy_overwrite_leaves_matching_protection()
+	{
+		let mock_github = crate::test_support::MockGitHub::new();
+		mock_github.respond("GET", BRANCH_PROTECTION_ENDPOINT, 200, serde_json::json!(
+		{
+			"required_pull_request_reviews": {"required_approving_review_count": 1},
+			"enforce_admins": {"enabled": true},
+		}));
+		let state = mock_github.state(serde_json::json!(
+			{"existing_protection_policy": "overwrite"})).await;
+
+		let outcome =
+			super::protect_default_branch_if_unprotected(&state, &default_branch_target()).await;
+
+		assert!(matches!(outcome, super::HandledOutcome::Ignored(_)), "{outcome:?}");
+		assert!(mock_github.requests("PUT", BRANCH_PROTECTION_ENDPOINT).is_empty());
+	}
+
+	#[tokio::test]
+	async fn existing_protection_policy_report_drift_opens_issue_listing_differences()
+	{
+		let mock_github = crate::test_support::MockGitHub::new();
+		respond_to_protecting_existing_protection(&mock_github);
+		let state = mock_github.state(serde_json::json!(
+			{"existing_protection_policy": "report_drift"})).await;
+
+		let outcome =
+			super::protect_default_branch_if_unprotected(&state, &default_branch_target()).await;
+
+		assert!(matches!(outcome, super::HandledOutcome::DriftReported{issue_url: Some(_)}),
+			"{outcome:?}");
+		assert!(mock_github.requests("PUT", BRANCH_PROTECTION_ENDPOINT).is_empty());
+
+		let requests = mock_github.requests("POST", ISSUES_ENDPOINT);
+		assert_eq!(requests.len(), 1);
+		assert!(requests[0]["body"].as_str().unwrap().contains(
+			"- enforcing the rules for administrators is disabled instead of enabled\n"));
+	}
+
+	#[tokio::test]
+	async fn disabled_issues_dont_fail_protecting_branch()
+	{
+		let mock_github = crate::test_support::MockGitHub::new();
+		mock_github
+			.respond("PUT", BRANCH_PROTECTION_ENDPOINT, 200, serde_json::json!({}))
+			.respond("POST", ISSUES_ENDPOINT, 410, serde_json::json!(
+				{"message": "Issues are disabled for this repo"}));
+		let state = mock_github.state(serde_json::json!({})).await;
+
+		let outcome = super::protect_default_branch(&state, &default_branch_target()).await;
+
+		assert!(matches!(outcome, super::HandledOutcome::Protected{issue_url: None}),
+			"{outcome:?}");
+		assert_eq!(mock_github.requests("PUT", BRANCH_PROTECTION_ENDPOINT).len(), 1);
+		assert_eq!(mock_github.requests("POST", ISSUES_ENDPOINT).len(), 1);
+	}
+
+	/// A validation error as GitHub responds with to a request to protect a branch.
+	///
+	/// # Arguments
+	/// - `response_body`: The body of the response.
+	fn validation_error(response_body: &str) -> crate::Error
+	{
+		crate::Error::ReceivedGitHubApiClientError
+		{
+			status_code: reqwest::StatusCode::UNPROCESSABLE_ENTITY,
+			url: format!("https://api.github.com/{BRANCH_PROTECTION_ENDPOINT}").parse().unwrap(),
+			response_body: response_body.into(),
+		}
+	}
+
+	#[test]
+	fn drop_rejected_setting_drops_optional_settings_in_order()
+	{
+		let settings = crate::BranchProtectionSettings
+		{
+			required_approving_review_count: 2,
+			dismiss_stale_reviews: true,
+			require_code_owner_reviews: true,
+			enforce_admins: true,
+			required_status_checks: vec!["ci/test".into()],
+			strict_status_checks: true,
+		};
+		let mut request = settings.protect_branch_request();
+		let mut dropped_settings = vec![];
+		let error = validation_error(r#"{"message": "Validation Failed", "errors": [
+			"required_status_checks.strict", "required_status_checks.contexts",
+			"required_pull_request_reviews.require_code_owner_reviews",
+			"required_pull_request_reviews.dismiss_stale_reviews", "enforce_admins",
+			"required_pull_request_reviews.required_approving_review_count"]}"#);
+
+		while super::drop_rejected_setting(&mut request, &error, &mut dropped_settings) {}
+
+		assert_eq!(dropped_settings, ["strict_status_checks", "required_status_checks",
+			"require_code_owner_reviews", "dismiss_stale_reviews"]);
+		assert!(request.required_status_checks.is_none());
+		assert_eq!(request.enforce_admins, Some(true));
+		assert_eq!(request.required_pull_request_reviews
+			.map(|reviews| reviews.required_approving_review_count), Some(2));
+	}
+
+	#[test]
+	fn drop_rejected_setting_only_drops_named_settings()
+	{
+		let settings = crate::BranchProtectionSettings
+		{
+			dismiss_stale_reviews: true,
+			required_status_checks: vec!["ci/test".into()],
+			strict_status_checks: true,
+			..Default::default()
+		};
+		let mut request = settings.protect_branch_request();
+		let mut dropped_settings = vec![];
+
+		for response_body in [r#"{"message": "Branch not found"}"#,
+			r#"{"message": "Validation Failed", "errors": ["restrictions"]}"#]
+		{
+			assert!(!super::drop_rejected_setting(&mut request, &validation_error(response_body),
+				&mut dropped_settings), "{response_body}");
+		}
+
+		assert!(super::drop_rejected_setting(&mut request,
+			&validation_error(r#"{"message": "Invalid dismiss_stale_reviews"}"#),
+			&mut dropped_settings));
+		assert_eq!(dropped_settings, ["dismiss_stale_reviews"]);
+		assert!(request.required_status_checks.is_some_and(|status_checks| status_checks.strict));
+	}
+
+	#[tokio::test]
+	async fn best_effort_protection_notifies_about_applied_settings_once()
+	{
+		let mock_github = crate::test_support::MockGitHub::new();
+		mock_github
+			.respond("PUT", BRANCH_PROTECTION_ENDPOINT, 422, serde_json::json!(
+			{
+				"message": "Validation Failed",
+				"errors": ["dismiss_stale_reviews"],
+			}))
+			.respond("PUT", BRANCH_PROTECTION_ENDPOINT, 200, serde_json::json!({}))
+			.respond("POST", ISSUES_ENDPOINT, 201, serde_json::json!(
+			{
+				"html_url": "https://github.com/example-organization/example-repository/issues/1",
+				"node_id": "I_1",
+			}));
+		let state = mock_github.state(serde_json::json!(
+		{
+			"best_effort_protection": true,
+			"branch_protection": {"dismiss_stale_reviews": true},
+			"notification": {"issue_body_template": "Rules: {rules_summary}"},
+		})).await;
+
+		let outcome = super::protect_default_branch(&state, &default_branch_target()).await;
+
+		assert!(matches!(outcome, super::HandledOutcome::Protected{issue_url: Some(_)}),
+			"{outcome:?}");
+
+		let requests = mock_github.requests("POST", ISSUES_ENDPOINT);
+		assert_eq!(requests.len(), 1);
+
+		let body = requests[0]["body"].as_str().unwrap();
+		assert!(!body.contains("dismisses stale reviews"), "{body}");
+		assert!(body.contains("without the following settings: `dismiss_stale_reviews`"),
+			"{body}");
+	}
+
+	#[tokio::test]
+	async fn best_effort_protection_fails_with_nothing_left_to_drop()
+	{
+		let mock_github = crate::test_support::MockGitHub::new();
+		mock_github.respond("PUT", BRANCH_PROTECTION_ENDPOINT, 422, serde_json::json!(
+		{
+			"message": "Validation Failed",
+			"errors": ["dismiss_stale_reviews", "enforce_admins"],
+		}));
+		let state = mock_github.state(serde_json::json!(
+		{
+			"best_effort_protection": true,
+			"branch_protection": {"dismiss_stale_reviews": true},
+		})).await;
+
+		let outcome = super::protect_default_branch(&state, &default_branch_target()).await;
+
+		assert!(matches!(outcome, super::HandledOutcome::Failed(_)), "{outcome:?}");
+
+		let requests = mock_github.requests("PUT", BRANCH_PROTECTION_ENDPOINT);
+		assert_eq!(requests.len(), 2);
+		assert_eq!(requests[1]["required_pull_request_reviews"]["dismiss_stale_reviews"], false);
+		assert_eq!(requests[1]["enforce_admins"], true);
+		assert!(mock_github.requests("POST", ISSUES_ENDPOINT).is_empty());
+	}
+
+	#[tokio::test]
+	async fn status_issue_is_only_updated_if_opened_by_this_github_app()
+	{
+		let search_endpoint = format!("search/issues?q={}&per_page=100",
+			crate::github_api::encode_path_segment(&format!("repo:example-organization/\
+				example-repository is:issue is:open in:title \"{}\"", super::STATUS_ISSUE_TITLE)));
+		let status_issue = |number: u64, login: &str| serde_json::json!(
+		{
+			"number": number,
+			"html_url": format!(
+				"https://github.com/example-organization/example-repository/issues/{number}"),
+			"body": format!("- Default branch `release`\n{}", super::STATUS_ISSUE_MARKER),
+			"user": {"login": login},
+		});
+
+		let mock_github = crate::test_support::MockGitHub::new();
+		mock_github
+			.respond("PUT", BRANCH_PROTECTION_ENDPOINT, 200, serde_json::json!({}))
+			.respond("GET", &search_endpoint, 200, serde_json::json!(
+			{
+				"items": [status_issue(1, "octocat"), status_issue(2, "branch-autoprotector[bot]")],
+			}))
+			.respond("PATCH", &format!("{ISSUES_ENDPOINT}/2"), 200,
+				status_issue(2, "branch-autoprotector[bot]"));
+		let state = mock_github.state(serde_json::json!(
+		{
+			"notification": {"status_issue": true},
+		})).await;
+
+		let outcome = super::protect_default_branch(&state, &default_branch_target()).await;
+
+		assert!(matches!(outcome, super::HandledOutcome::Protected{issue_url: Some(_)}),
+			"{outcome:?}");
+		assert!(mock_github.requests("PATCH", &format!("{ISSUES_ENDPOINT}/1")).is_empty());
+
+		let requests = mock_github.requests("PATCH", &format!("{ISSUES_ENDPOINT}/2"));
+		assert_eq!(requests.len(), 1);
+		assert!(requests[0]["body"].as_str().unwrap().contains("[`main`](../tree/main)"),
+			"{}", requests[0]["body"]);
+	}
+
+	#[tokio::test]
+	async fn full_status_issue_is_continued_in_new_status_issue()
+	{
+		let search_endpoint = format!("search/issues?q={}&per_page=100",
+			crate::github_api::encode_path_segment(&format!("repo:example-organization/\
+				example-repository is:issue is:open in:title \"{}\"", super::STATUS_ISSUE_TITLE)));
+		let full_status_issue = serde_json::json!(
+		{
+			"number": 1,
+			"html_url": "https://github.com/example-organization/example-repository/issues/1",
+			"body": format!("{}\n{}", "- Default branch `release`\n".repeat(100),
+				super::STATUS_ISSUE_MARKER),
+			"user": {"login": "branch-autoprotector[bot]"},
+		});
+
+		let mock_github = crate::test_support::MockGitHub::new();
+		mock_github
+			.respond("PUT", BRANCH_PROTECTION_ENDPOINT, 200, serde_json::json!({}))
+			.respond("GET", &search_endpoint, 200,
+				serde_json::json!({"items": [full_status_issue.clone()]}))
+			.respond("PATCH", &format!("{ISSUES_ENDPOINT}/1"), 200, full_status_issue)
+			.respond("POST", ISSUES_ENDPOINT, 201, serde_json::json!(
+			{
+				"html_url": "https://github.com/example-organization/example-repository/issues/2",
+				"node_id": "I_2",
+			}));
+		let state = mock_github.state(serde_json::json!(
+		{
+			"notification": {"status_issue": true, "max_issue_body_length": 2048},
+		})).await;
+
+		let outcome = super::protect_default_branch(&state, &default_branch_target()).await;
+
+		assert!(matches!(outcome, super::HandledOutcome::Protected{issue_url: Some(_)}),
+			"{outcome:?}");
+
+		let requests = mock_github.requests("POST", ISSUES_ENDPOINT);
+		assert_eq!(requests.len(), 1);
+		assert!(requests[0]["body"].as_str().unwrap()
+			.contains("example-repository/issues/1"), "{}", requests[0]["body"]);
+
+		let requests = mock_github.requests("PATCH", &format!("{ISSUES_ENDPOINT}/1"));
+		assert_eq!(requests, vec![serde_json::json!({"state": "closed"})]);
+	}
+
+	#[tokio::test]
+	async fn commit_comment_including_title_is_truncated_to_limit()
+	{
+		let comments_endpoint = format!("{REPOSITORY_ENDPOINT}/commits/abc123/comments");
+
+		let mock_github = crate::test_support::MockGitHub::new();
+		mock_github
+			.respond("PUT", BRANCH_PROTECTION_ENDPOINT, 200, serde_json::json!({}))
+			.respond("GET", &format!("{REPOSITORY_ENDPOINT}/branches/main"), 200,
+				serde_json::json!({"name": "main", "commit": {"sha": "abc123"}}))
+			.respond("POST", &comments_endpoint, 201, serde_json::json!(
+			{
+				"html_url": "https://github.com/example-organization/example-repository/\
+					commit/abc123#commitcomment-1",
+			}));
+		let state = mock_github.state(serde_json::json!(
+		{
+			"notification":
+			{
+				"target": "commit_comment",
+				"max_issue_body_length": 1024,
+				"issue_body_template": "x".repeat(2000),
+			},
+		})).await;
+
+		let outcome = super::protect_default_branch(&state, &default_branch_target()).await;
+
+		assert!(matches!(outcome, super::HandledOutcome::Protected{issue_url: Some(_)}),
+			"{outcome:?}");
+
+		let requests = mock_github.requests("POST", &comments_endpoint);
+		assert_eq!(requests.len(), 1);
+
+		let body = requests[0]["body"].as_str().unwrap();
+		assert!(body.starts_with("**"), "{body}");
+		assert!(body.contains(super::TRUNCATION_MARKER), "{body}");
+		assert_eq!(body.chars().count(), 1024);
+	}
+
+	#[tokio::test]
+	async fn issue_body_template_keeps_checklist_unchanged()
+	{
+		let mock_github = crate::test_support::MockGitHub::new();
+		mock_github
+			.respond("PUT", BRANCH_PROTECTION_ENDPOINT, 200, serde_json::json!({}))
+			.respond("POST", ISSUES_ENDPOINT, 201, serde_json::json!(
+			{
+				"html_url": "https://github.com/example-organization/example-repository/issues/1",
+				"node_id": "I_1",
+			}));
+		let state = mock_github.state(serde_json::json!(
+		{
+			"notification": {"issue_body_template": "\
+				@{creator}: `{branch}` in {organization}/{repository} was protected.\n\
+				\n\
+				- [ ] Review branch protection settings\n  \
+				  - [ ] Check the {unknown} placeholder\n\
+				- [x] Protect `{branch}`\n\n"},
+		})).await;
+
+		let outcome = super::protect_default_branch(&state, &default_branch_target()).await;
+
+		assert!(matches!(outcome, super::HandledOutcome::Protected{issue_url: Some(_)}),
+			"{outcome:?}");
+
+		let requests = mock_github.requests("POST", ISSUES_ENDPOINT);
+		assert_eq!(requests.len(), 1);
+		assert!(requests[0]["body"].as_str().unwrap().starts_with("\
+			@octocat: `main` in example-organization/example-repository was protected.\n\
+			\n\
+			- [ ] Review branch protection settings\n  \
+			  - [ ] Check the {unknown} placeholder\n\
+			- [x] Protect `main`\n\n"), "{}", requests[0]["body"]);
+	}
+
+	/// The API endpoint of the example repository.
+	const REPOSITORY_ENDPOINT: &str = "repos/example-organization/example-repository";
+
+	/// The API endpoint of the protection settings of the example repository’s default branch.
+	const BRANCH_PROTECTION_ENDPOINT: &str =
+		"repos/example-organization/example-repository/branches/main/protection";
+
+	/// The API endpoint of the issues of the example repository.
+	const ISSUES_ENDPOINT: &str = "repos/example-organization/example-repository/issues";
+}
@@ -0,0 +1,204 @@
+/// Reply to a request before it’s encoded in the format preferred by the client, which is JSON
+/// unless plain text is requested with the `Accept` header.
+#[derive(Debug)]
+pub struct EventReply
+{
+	/// The HTTP status code to respond with.
+	pub status_code: warp::http::StatusCode,
+	/// The message included in the response body, if any.
+	pub body: Option<ReplyBody>,
+	/// Details about a webhook event that was acknowledged to be handled in the background, if
+	/// applicable.
+	pub acknowledgment: Option<Acknowledgment>,
+	/// How long it took to reply to the request, if it should be included.
+	pub processing_time: Option<std::time::Duration>,
+}
+
+/// Details about a webhook event that was acknowledged to be handled in the background.
+#[derive(Debug, serde::Serialize)]
+pub struct Acknowledgment
+{
+	/// A stable, machine-readable code for systems parsing the response.
+	pub code: String,
+	/// The full name of the repository acted on (example: `example-organization/example`).
+	pub repository: String,
+	/// The name of the branch acted on.
+	pub branch: String,
+}
+
+/// Message included in the body of a reply.
+#[derive(Debug)]
+pub enum ReplyBody
+{
+	/// Human-readable information about how a request was handled.
+	Info(String),
+	/// Human-readable explanation as to why a request failed.
+	Error(String),
+}
+
+impl EventReply
+{
+	/// Create a reply with an info message.
+	pub fn info(status_code: warp::http::StatusCode, message: impl Into<String>) -> Self
+	{
+		Self
+		{
+			status_code,
+			body: Some(ReplyBody::Info(message.into())),
+			acknowledgment: None,
+			processing_time: None,
+		}
+	}
+
+	/// Create a reply with an error message.
+	pub fn error(status_code: warp::http::StatusCode, message: impl Into<String>) -> Self
+	{
+		Self
+		{
+			status_code,
+			body: Some(ReplyBody::Error(message.into())),
+			acknowledgment: None,
+			processing_time: None,
+		}
+	}
+
+	/// Create a reply without a body.
+	pub fn empty(status_code: warp::http::StatusCode) -> Self
+	{
+		Self
+		{
+			status_code,
+			body: None,
+			acknowledgment: None,
+			processing_time: None,
+		}
+	}
+
+	/// Add details about a webhook event acknowledged to be handled in the background to this
+	/// reply.
+	///
+	/// # Arguments
+	/// - `code`: A stable, machine-readable code for systems parsing the response.
+	/// - `target`: The branch acted on.
+	pub fn with_acknowledgment(mut self, code: &str, target: &crate::ProtectionTarget) -> Self
+	{
+		self.acknowledgment = Some(Acknowledgment
+		{
+			code: code.to_owned(),
+			repository: format!("{}/{}", target.organization_name, target.repository_name),
+			branch: target.branch_name.clone(),
+		});
+
+		self
+	}
+
+	/// Include how long it took to reply to the request in this reply, which is only encoded for
+	/// info messages in JSON.
+	///
+	/// # Arguments
+	/// - `processing_time`: How long it took from receiving the request until replying.
+	pub fn with_processing_time(mut self, processing_time: std::time::Duration) -> Self
+	{
+		self.processing_time = Some(processing_time);
+		self
+	}
+
+	/// Encode this reply as an HTTP response in the given format.
+	pub fn into_response(self, format: ResponseFormat) -> warp::reply::Response
+	{
+		use warp::Reply as _;
+
+		let acknowledgment = self.acknowledgment.as_ref();
+		let processing_ms = self.processing_time.map(|processing_time| processing_time.as_millis());
+
+		let response = match (self.body, format)
+		{
+			(None, _) => warp::reply().into_response(),
+			(Some(ReplyBody::Info(info)), ResponseFormat::Json) =>
+				warp::reply::json(&InfoResponse{info: &info, acknowledgment, processing_ms})
+					.into_response(),
+			(Some(ReplyBody::Error(error)), ResponseFormat::Json) =>
+				warp::reply::json(&ErrorResponse{error: &error}).into_response(),
+			(Some(ReplyBody::Info(message) | ReplyBody::Error(message)),
+				ResponseFormat::PlainText) => match acknowledgment
+			{
+				Some(Acknowledgment{code, repository, branch}) => format!("{message} (code: \
+					{code}, repository: {repository}, branch: {branch})\n").into_response(),
+				None => format!("{message}\n").into_response(),
+			},
+		};
+
+		warp::reply::with_status(response, self.status_code).into_response()
+	}
+}
+
+/// Formats in which replies can be encoded.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ResponseFormat
+{
+	Json,
+	PlainText,
+}
+
+impl ResponseFormat
+{
+	/// Choose the response format from the value of an `Accept` header. The first supported media
+	/// type listed wins (quality values are ignored), and JSON is used if the header is absent or
+	/// only lists unsupported media types or wildcards such as `*/*`.
+	fn from_accept_header(accept: Option<&str>) -> Self
+	{
+		let media_types = accept.into_iter()
+			.flat_map(|accept| accept.split(','))
+			.map(|media_type| media_type.split(';').next().unwrap_or_default().trim());
+
+		for media_type in media_types
+		{
+			if media_type.eq_ignore_ascii_case("application/json")
+			{
+				return Self::Json;
+			}
+
+			if media_type.eq_ignore_ascii_case("text/plain")
+			{
+				return Self::PlainText;
+			}
+		}
+
+		Self::Json
+	}
+}
+
+/// [warp] filter extracting the response format preferred by the client from the `Accept` header.
+/// This filter never rejects requests, falling back to JSON if the header can’t be read.
+pub fn with_response_format()
+	-> impl warp::Filter<Extract = (ResponseFormat,), Error = std::convert::Infallible> + Clone
+{
+	use warp::Filter as _;
+
+	warp::header::headers_cloned()
+		.map(|headers: warp::http::HeaderMap|
+			ResponseFormat::from_accept_header(headers.get(warp::http::header::ACCEPT)
+				.and_then(|accept| accept.to_str().ok())))
+}
+
+/// Response type acknowledging successfully handled webhook events (serialized to JSON).
+#[derive(serde::Serialize)]
+struct InfoResponse<'a>
+{
+	/// Info message with human-readable information about how this request was handled.
+	info: &'a str,
+	/// Details about a webhook event acknowledged to be handled in the background, if applicable.
+	#[serde(flatten, skip_serializing_if = "Option::is_none")]
+	acknowledgment: Option<&'a Acknowledgment>,
+	/// How long it took to reply to this request in milliseconds, if configured to be included.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	processing_ms: Option<u128>,
+}
+
+/// Response type informing about errors while handling webhook events (serialized to JSON).
+#[derive(serde::Serialize)]
+struct ErrorResponse<'a>
+{
+	/// Error message with a human-readable explanation as to why this request failed.
+	error: &'a str,
+}
@@ -0,0 +1,367 @@
+/// Retry protecting the branches of all failed records in a file of event records as written by
+/// the event sink (see [crate::EventSink]), such as the records collected from the standard output
+/// during an outage, and print how many branches were recovered.
+///
+/// Each branch is retried once, however many of its records failed. Branches that are protected by
+/// now, including those protected in the meantime, count as recovered, and their failed records
+/// are removed from the file, while all other lines are kept as is. Branches that wouldn’t be
+/// protected anymore are skipped and their records removed, too, as decided by the same checks as
+/// for webhook events: `branch_deny_patterns`, `protect_forks`, `required_topics`,
+/// `opt_out_file`, and the decision hook. Records of organizations other than the one the GitHub
+/// API client is authenticated for and records of events that don’t protect branches, such as
+/// deleted branch protection rules unless `deleted_protection.reapply` is enabled, are kept
+/// without being retried. Unlike handling webhook events, this doesn’t open issues, and with
+/// `dry_run` enabled, neither branches nor the file are changed. Requests that exceed the GitHub
+/// API rate limit are retried after waiting for the rate limit to recover, and only a bounded
+/// number of branches is processed at the same time. If invoked from GitHub Actions, the outcome
+/// for each branch is also listed in the job summary.
+///
+/// # Arguments
+/// - `github_api_client`: A handle to the GitHub API client.
+/// - `config`: The configuration, including the settings of the branches to protect.
+/// - `records_path`: The file containing the event records, one JSON object per line, which is
+///   rewritten without the records of recovered branches.
+/// - `concurrency`: How many branches to process at the same time (at least 1).
+/// - `fail_on_error`: Whether to return an error if any branch still couldn’t be protected.
+pub async fn retry_failed(github_api_client: &crate::github_api::Client,
+	config: std::sync::Arc<crate::Config>, records_path: &std::path::Path, concurrency: usize,
+	fail_on_error: bool)
+	-> Result<(), crate::Error>
+{
+	let organization_name = github_api_client.organization();
+
+	let contents = std::fs::read_to_string(records_path).map_err(crate::Error::ReadEventRecords)?;
+
+	// Retry each branch only once, even if handling several events for it failed, on behalf of the
+	// last of these events
+	let mut branches = std::collections::BTreeMap::new();
+	let mut other_organization_count = 0;
+	let mut not_protecting_count = 0;
+
+	for record in contents.lines().filter_map(failed_record)
+	{
+		if record.organization != organization_name
+		{
+			other_organization_count += 1;
+			continue;
+		}
+
+		let event_type = record.event_type();
+
+		if event_type == "branch_protection_rule" && !config.deleted_protection.reapply
+		{
+			not_protecting_count += 1;
+			continue;
+		}
+
+		branches.insert((record.repository, record.branch), (event_type, record.sender));
+	}
+
+	let branch_count = branches.len();
+
+	log::info!("retrying {branch_count} branches with failed records, {concurrency} at a time");
+
+	let mut counts = RetryCounts::default();
+	let mut recovered_branches = std::collections::HashSet::new();
+	let mut summary_rows = Vec::new();
+	let mut tasks = tokio::task::JoinSet::new();
+	let mut branches = branches.into_iter();
+	let mut processed_count = 0;
+
+	loop
+	{
+		// Keep up to the given number of branches in progress, starting the next one as soon as
+		// another one is done
+		while tasks.len() < concurrency
+		{
+			match branches.next()
+			{
+				Some(((repository_name, branch_name), (event_type, sender_name))) =>
+					tasks.spawn(retry_branch(github_api_client.clone(), config.clone(),
+						repository_name, branch_name, event_type, sender_name)),
+				None => break,
+			};
+		}
+
+		let (repository_name, branch_name, result) = match tasks.join_next().await
+		{
+			Some(result) =>
+				result.expect("this call is infallible because retrying doesn’t panic"),
+			None => break,
+		};
+
+		let (is_recovered, outcome, detail) = match result
+		{
+			Ok(RetryOutcome::Protected) =>
+			{
+				log::info!("protected branch “{branch_name}” in repository “{repository_name}”");
+				counts.protected += 1;
+				(true, "protected", String::new())
+			},
+			Ok(RetryOutcome::AlreadyProtected) =>
+			{
+				log::info!("branch “{branch_name}” in repository “{repository_name}” is protected \
+					already");
+				counts.already_protected += 1;
+				(true, "ignored", "branch is protected already".into())
+			},
+			Ok(RetryOutcome::Denied) =>
+			{
+				log::info!("skipping branch “{branch_name}” in repository “{repository_name}”, \
+					which matches a deny pattern");
+				counts.denied += 1;
+				(true, "ignored", "branch matches a deny pattern".into())
+			},
+			Ok(RetryOutcome::Skipped(reason)) =>
+			{
+				log::info!("skipping branch “{branch_name}” in repository “{repository_name}”: \
+					{reason}");
+				counts.skipped += 1;
+				(true, "ignored", reason)
+			},
+			Ok(RetryOutcome::DryRun) =>
+			{
+				log::info!("dry run: would protect branch “{branch_name}” in repository \
+					“{repository_name}”");
+				counts.protected += 1;
+				(false, "dry_run", "would protect branch".into())
+			},
+			Err(error) =>
+			{
+				log::error!("could not protect branch “{branch_name}” in repository \
+					“{repository_name}”: {error}");
+				counts.failed += 1;
+				(false, "failed", error.describe_with_sources())
+			},
+		};
+
+		summary_rows.push(crate::StepSummaryRow
+		{
+			repository: Some(format!("{organization_name}/{repository_name}")),
+			branch: Some(branch_name.clone()),
+			outcome,
+			detail,
+		});
+
+		if is_recovered
+		{
+			recovered_branches.insert((repository_name, branch_name));
+		}
+
+		processed_count += 1;
+
+		log::info!("processed {processed_count} of {branch_count} branches");
+	}
+
+	if !config.dry_run && !recovered_branches.is_empty()
+	{
+		let is_recovered_record = |line: &str| failed_record(line).is_some_and(|record|
+			record.organization == organization_name
+				&& recovered_branches.contains(&(record.repository, record.branch)));
+
+		let remaining_records: String = contents.lines()
+			.filter(|line| !is_recovered_record(line))
+			.flat_map(|line| [line, "\n"])
+			.collect();
+
+		write_records(records_path, &remaining_records)?;
+	}
+
+	let protected_description = match config.dry_run
+	{
+		true => "would be protected",
+		false => "protected",
+	};
+
+	println!("branches {protected_description}: {}", counts.protected);
+	println!("branches protected already: {}", counts.already_protected);
+	println!("branches skipped because they match a deny pattern: {}", counts.denied);
+	println!("branches skipped for other reasons: {}", counts.skipped);
+	println!("branches still failing: {}", counts.failed);
+	println!("records of other organizations skipped: {other_organization_count}");
+	println!("records of events that don’t protect branches skipped: {not_protecting_count}");
+
+	crate::append_step_summary(&summary_rows);
+
+	if fail_on_error && counts.failed > 0
+	{
+		return Err(crate::Error::RetryingFailedBranchesFailed{branch_count: counts.failed});
+	}
+
+	Ok(())
+}
+
+/// Retry protecting a single branch as described for [retry_failed], returning the names of the
+/// repository and the branch along with the outcome.
+///
+/// # Arguments
+/// - `github_api_client`: A handle to the GitHub API client.
+/// - `config`: The configuration, including the settings of the branches to protect.
+/// - `repository_name`: The name of the repository.
+/// - `branch_name`: The name of the branch.
+/// - `event_type`: The type of the webhook event whose handling failed (example: `create`).
+/// - `sender_name`: The handle of the user who caused the webhook event.
+#[doc(hidden)]
+async fn retry_branch(github_api_client: crate::github_api::Client,
+	config: std::sync::Arc<crate::Config>, repository_name: String, branch_name: String,
+	event_type: &'static str, sender_name: String)
+	-> (String, String, Result<RetryOutcome, crate::Error>)
+{
+	let result = retry_branch_protection(&github_api_client, &config, &repository_name,
+		&branch_name, event_type, sender_name).await;
+
+	(repository_name, branch_name, result)
+}
+
+/// Protect a branch unless it’s protected already or wouldn’t be protected anymore (for
+/// arguments, see [retry_branch]).
+#[doc(hidden)]
+async fn retry_branch_protection(github_api_client: &crate::github_api::Client,
+	config: &crate::Config, repository_name: &str, branch_name: &str, event_type: &'static str,
+	sender_name: String)
+	-> Result<RetryOutcome, crate::Error>
+{
+	let organization_name = github_api_client.organization();
+
+	if config.branch_deny_pattern(branch_name).is_some()
+	{
+		return Ok(RetryOutcome::Denied);
+	}
+
+	let branch = crate::retry_if_rate_limited(
+		|| github_api_client.get_branch(organization_name, repository_name, branch_name)).await?;
+
+	if branch.protected
+	{
+		return Ok(RetryOutcome::AlreadyProtected);
+	}
+
+	// The default branch and the repository’s topics may have changed since the record was
+	// written, so look them up again
+	let repository = crate::retry_if_rate_limited(
+		|| github_api_client.get_repository(organization_name, repository_name)).await?;
+
+	if let Some(reason) =
+		crate::repository_skip_reason(github_api_client, config, &repository).await?
+	{
+		return Ok(RetryOutcome::Skipped(reason));
+	}
+
+	let mut target = crate::ProtectionTarget
+	{
+		organization_name: organization_name.to_owned(),
+		repository_name: repository_name.to_owned(),
+		branch_name: branch_name.to_owned(),
+		is_default_branch: repository.default_branch.as_deref() == Some(branch_name),
+		creator_name: sender_name,
+		received_at: std::time::Instant::now(),
+		event_type,
+		policy: None,
+	};
+
+	// If configured, let the decision hook veto protecting the branch or select a policy
+	match crate::consult_decision_hook(config, &mut target).await
+	{
+		Ok(()) => (),
+		Err(crate::HandledOutcome::Failed(error)) => return Err(error),
+		Err(outcome) => return Ok(RetryOutcome::Skipped(outcome.detail())),
+	}
+
+	if config.dry_run
+	{
+		return Ok(RetryOutcome::DryRun);
+	}
+
+	let protect_branch_request = target.branch_protection(config).protect_branch_request();
+
+	crate::retry_if_rate_limited(|| github_api_client.protect_branch(organization_name,
+		repository_name, branch_name, &protect_branch_request)).await?;
+
+	Ok(RetryOutcome::Protected)
+}
+
+/// Decode a line of a file of event records, returning the record if it’s a record of a failure.
+/// Lines that aren’t records of handled webhook events, such as lifecycle records, are skipped.
+///
+/// # Arguments
+/// - `line`: A line of the file, which should contain a JSON object.
+#[doc(hidden)]
+fn failed_record(line: &str) -> Option<FailedRecord>
+{
+	serde_json::from_str::<FailedRecord>(line).ok()
+		.filter(|record| record.outcome == FAILED_OUTCOME)
+}
+
+/// Replace the contents of a file of event records by writing a temporary file next to it first,
+/// so that the records aren’t lost if writing fails halfway.
+///
+/// # Arguments
+/// - `records_path`: The file containing the event records.
+/// - `contents`: The new contents of the file.
+#[doc(hidden)]
+fn write_records(records_path: &std::path::Path, contents: &str) -> Result<(), crate::Error>
+{
+	let mut temporary_path = records_path.as_os_str().to_owned();
+	temporary_path.push(".tmp");
+
+	std::fs::write(&temporary_path, contents).map_err(crate::Error::WriteEventRecords)?;
+	std::fs::rename(&temporary_path, records_path).map_err(crate::Error::WriteEventRecords)
+}
+
+/// Partial data model of a record of handling a webhook event as written by the event sink.
+#[derive(serde::Deserialize)]
+struct FailedRecord
+{
+	/// The organization owning the repository.
+	organization: String,
+	/// The name of the repository.
+	repository: String,
+	/// The name of the branch.
+	branch: String,
+	/// The type of the webhook event, which older records don’t include.
+	#[serde(default)]
+	event: Option<String>,
+	/// The handle of the user who caused the webhook event, which older records don’t include.
+	#[serde(default)]
+	sender: String,
+	/// The kind of outcome (see [crate::HandledOutcome::name]).
+	outcome: String,
+	// We don’t need the other fields, so ignore them
+}
+
+impl FailedRecord
+{
+	/// The type of the webhook event whose handling failed. Records written before the event type
+	/// was recorded are treated like ref creation events, which most records are about.
+	fn event_type(&self) -> &'static str
+	{
+		crate::HANDLED_EVENT_TYPES.iter()
+			.find(|event_type| self.event.as_deref() == Some(**event_type))
+			.copied()
+			.unwrap_or("create")
+	}
+}
+
+/// The outcome of retrying to protect a branch.
+enum RetryOutcome
+{
+	Protected,
+	AlreadyProtected,
+	Denied,
+	Skipped(String),
+	DryRun,
+}
+
+/// How many branches were retried with which outcome.
+#[derive(Default)]
+struct RetryCounts
+{
+	protected: usize,
+	already_protected: usize,
+	denied: usize,
+	skipped: usize,
+	failed: usize,
+}
+
+/// The outcome name of records of failures (see [crate::HandledOutcome::name]).
+const FAILED_OUTCOME: &str = "failed";
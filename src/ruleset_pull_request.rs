@@ -0,0 +1,197 @@
+/// Configuration of proposing branch protection as a ruleset file in a pull request, so that it’s
+/// reviewed and version-controlled, instead of applying it via the branch protection API.
+///
+/// Merging the pull request doesn’t protect the branch by itself: GitHub doesn’t read rulesets from
+/// files, so a separate process syncing the files of the repository must import them.
+#[derive(Clone, Debug, PartialEq, schemars::JsonSchema, serde::Deserialize)]
+pub struct RulesetPullRequestConfig
+{
+	/// The directory of the ruleset files relative to the root of the repository, which contains
+	/// one file per protected branch named after the percent-encoded branch name, so that
+	/// `release/1.0` is stored in `release%2F1.0.json` (optional, default: `.github/rulesets`).
+	#[serde(default = "default_ruleset_directory")]
+	pub directory: String,
+	/// The prefix of the names of the branches pull requests are opened from, which is followed by
+	/// the name of the protected branch (optional, default: `branch-autoprotector/`).
+	#[serde(default = "default_ruleset_branch_prefix")]
+	pub branch_prefix: String,
+}
+
+#[doc(hidden)]
+fn default_ruleset_directory() -> String
+{
+	".github/rulesets".into()
+}
+
+#[doc(hidden)]
+fn default_ruleset_branch_prefix() -> String
+{
+	"branch-autoprotector/".into()
+}
+
+impl RulesetPullRequestConfig
+{
+	/// Check the configuration for invalid values that can’t be detected while parsing.
+	pub fn validate(&self) -> Result<(), crate::Error>
+	{
+		// Without a prefix, pull requests would be opened from the protected branch itself
+		if self.branch_prefix.is_empty()
+		{
+			return Err(crate::Error::InvalidConfig(
+				"ruleset_pull_request.branch_prefix must not be empty".to_string()));
+		}
+
+		if self.directory.trim_matches('/').is_empty()
+		{
+			return Err(crate::Error::InvalidConfig(
+				"ruleset_pull_request.directory must not be empty".to_string()));
+		}
+
+		Ok(())
+	}
+}
+
+/// Propose protecting a branch by opening a pull request against the default branch that adds or
+/// updates a ruleset file with the settings configured for the branch, returning the URL of the
+/// pull request. The branch is only protected once the merged file is imported by a separate
+/// process, as GitHub doesn’t apply rulesets from files.
+///
+/// The file is committed to a branch named after the protected branch, which is created from the
+/// default branch unless it exists already from an earlier proposal. If a pull request from this
+/// branch is open already, it’s updated by the commit, and `None` is returned. Repositories whose
+/// default branch has no commits yet are skipped with a warning, also returning `None`.
+///
+/// # Arguments
+/// - `state`: The shared state, including the GitHub API client and the configuration.
+/// - `target`: The branch to protect.
+/// - `config`: Where to write the ruleset file and which branch to open the pull request from.
+pub async fn open_ruleset_pull_request(state: &crate::AppState, target: &crate::ProtectionTarget,
+	config: &RulesetPullRequestConfig)
+	-> Result<Option<url::Url>, crate::Error>
+{
+	let crate::ProtectionTarget{organization_name, repository_name, branch_name, creator_name, ..} =
+		target;
+	let github_api_client = &state.github_api_client;
+
+	// Rulesets are committed to the default branch, like other repository settings as code
+	let base_branch = match target.is_default_branch
+	{
+		true => Some(branch_name.clone()),
+		false => github_api_client.get_repository(organization_name, repository_name).await?
+			.default_branch,
+	};
+
+	let base_commit = match &base_branch
+	{
+		Some(base_branch) => github_api_client.get_branch(organization_name, repository_name,
+			base_branch).await?.commit,
+		None => None,
+	};
+
+	let (base_branch, base_sha) = match (base_branch, base_commit)
+	{
+		(Some(base_branch), Some(base_commit)) => (base_branch, base_commit.sha),
+		_ =>
+		{
+			log::warn!("could not propose ruleset for branch “{branch_name}” in repository \
+				“{repository_name}”, as the default branch has no commits yet");
+			return Ok(None);
+		},
+	};
+
+	let head_branch = format!("{}{branch_name}", config.branch_prefix);
+
+	match github_api_client.create_branch(organization_name, repository_name, &head_branch,
+		&base_sha).await
+	{
+		Ok(()) => (),
+		// The branch is left over from an earlier proposal, so update the file in it instead
+		Err(error)
+			if error.is_client_error_with_status(reqwest::StatusCode::UNPROCESSABLE_ENTITY) =>
+			log::debug!("branch “{head_branch}” exists already in repository “{repository_name}”"),
+		Err(error) => return Err(error),
+	}
+
+	let app_config = state.config();
+	let ruleset = target.branch_protection(&app_config).ruleset(branch_name);
+	let content = serde_json::to_string_pretty(&ruleset)
+		.expect("this call is infallible because rulesets are plain data") + "\n";
+
+	let path = ruleset_path(config, branch_name);
+
+	// Updating a file requires the SHA of the version it replaces
+	let existing_sha = github_api_client.get_file_sha(organization_name, repository_name, &path,
+		&head_branch).await?;
+
+	github_api_client.put_file_contents(organization_name, repository_name, &path,
+		&format!("Protect branch `{branch_name}`"), content.as_bytes(), &head_branch,
+		existing_sha.as_deref()).await?;
+
+	log::info!("committed ruleset “{path}” for branch “{branch_name}” to branch “{head_branch}” in \
+		repository “{repository_name}”");
+
+	let body = format!("@{creator_name}: To comply with our corporate policies, the \
+		{} [`{branch_name}`]({}) needs to be protected. This pull request adds the ruleset \
+		`{path}` with the required settings so that the protection is reviewed and \
+		version-controlled. Please review and merge it, after which the ruleset takes effect \
+		once it’s imported from the merged file.",
+		target.branch_description(), target.branch_link());
+
+	let request = crate::CreatePullRequestRequest
+	{
+		title: &format!("Protect branch “{branch_name}”"),
+		head: &head_branch,
+		base: &base_branch,
+		body: &body,
+	};
+
+	match github_api_client.create_pull_request(organization_name, repository_name, &request).await
+	{
+		Ok(pull_request) =>
+		{
+			log::info!("proposed protecting branch “{branch_name}” in {}", pull_request.html_url);
+			Ok(Some(pull_request.html_url))
+		},
+		// GitHub responds with 422 Unprocessable Entity if a pull request from the branch is open
+		Err(error)
+			if error.is_client_error_with_status(reqwest::StatusCode::UNPROCESSABLE_ENTITY) =>
+		{
+			log::info!("updated the open pull request from branch “{head_branch}” in repository \
+				“{repository_name}”");
+			Ok(None)
+		},
+		Err(error) => Err(error),
+	}
+}
+
+/// Build the path of the ruleset file of a branch, whose name is percent-encoded so that branches
+/// such as `release/1.0` and `release-1.0` don’t share a file.
+///
+/// # Arguments
+/// - `config`: The directory to store the ruleset file in.
+/// - `branch_name`: The name of the protected branch.
+fn ruleset_path(config: &RulesetPullRequestConfig, branch_name: &str) -> String
+{
+	format!("{}/{}.json", config.directory.trim_matches('/'),
+		crate::github_api::encode_path_segment(branch_name))
+}
+
+#[cfg(test)]
+mod tests
+{
+	#[test]
+	fn ruleset_paths_of_similar_branch_names_differ()
+	{
+		let config = super::RulesetPullRequestConfig
+		{
+			directory: "/.github/rulesets/".into(),
+			branch_prefix: super::default_ruleset_branch_prefix(),
+		};
+
+		assert_eq!(super::ruleset_path(&config, "main"), ".github/rulesets/main.json");
+		assert_eq!(super::ruleset_path(&config, "release/1.0"),
+			".github/rulesets/release%2F1.0.json");
+		assert_eq!(super::ruleset_path(&config, "release-1.0"),
+			".github/rulesets/release-1.0.json");
+	}
+}
@@ -0,0 +1,144 @@
+/// Configuration of the allow list of source IP addresses from which webhook events are accepted.
+#[derive(Clone, PartialEq, schemars::JsonSchema, serde::Deserialize)]
+pub struct SourceIpAllowListConfig
+{
+	/// Accept webhook events from these IP address ranges in CIDR notation (optional).
+	#[serde(default)]
+	#[schemars(with = "Vec<String>")]
+	pub ranges: Vec<ipnet::IpNet>,
+	/// Additionally accept webhook events from the IP address ranges GitHub publishes for webhook
+	/// deliveries, which are looked up via the GitHub API and cached for an hour (optional,
+	/// default: `true`).
+	#[serde(default = "default_allow_github_hook_ranges")]
+	pub allow_github_hook_ranges: bool,
+}
+
+#[doc(hidden)]
+fn default_allow_github_hook_ranges() -> bool
+{
+	true
+}
+
+/// Allow list of source IP addresses from which webhook events are accepted, as a defense in depth
+/// in addition to verifying payload signatures.
+///
+/// The allow list can safely be shared between threads, as cloning it only clones handles to the
+/// underlying data.
+#[derive(Clone)]
+pub struct SourceIpAllowList
+{
+	#[doc(hidden)]
+	ranges: std::sync::Arc<Vec<ipnet::IpNet>>,
+	#[doc(hidden)]
+	// The IP address ranges GitHub publishes for webhook deliveries if they should be accepted
+	github_hook_ranges: Option<crate::ExpiringCache<(), Vec<ipnet::IpNet>>>,
+	#[doc(hidden)]
+	github_api_client: crate::github_api::Client,
+}
+
+impl SourceIpAllowList
+{
+	/// Create a new allow list from its configuration.
+	///
+	/// # Arguments
+	/// - `config`: The configuration of the allow list.
+	/// - `github_api_client`: A handle to the GitHub API client for looking up the IP address
+	///   ranges GitHub publishes for webhook deliveries.
+	pub fn new(config: &SourceIpAllowListConfig, github_api_client: crate::github_api::Client)
+		-> Self
+	{
+		let github_hook_ranges = config.allow_github_hook_ranges
+			.then(|| crate::ExpiringCache::new(GITHUB_HOOK_RANGES_CACHE_TIME_TO_LIVE));
+
+		Self
+		{
+			ranges: std::sync::Arc::new(config.ranges.clone()),
+			github_hook_ranges,
+			github_api_client,
+		}
+	}
+
+	/// Check whether webhook events from a given IP address are accepted.
+	pub async fn allows(&self, address: std::net::IpAddr) -> Result<bool, crate::Error>
+	{
+		if self.ranges.iter().any(|range| range.contains(&address))
+		{
+			return Ok(true);
+		}
+
+		let github_hook_ranges_cache = match &self.github_hook_ranges
+		{
+			Some(github_hook_ranges_cache) => github_hook_ranges_cache,
+			None => return Ok(false),
+		};
+
+		let github_hook_ranges = match github_hook_ranges_cache.get(&())
+		{
+			Some(github_hook_ranges) => github_hook_ranges,
+			None =>
+			{
+				log::debug!("looking up IP address ranges of GitHub webhook deliveries");
+
+				let response: crate::GitHubMetaResponse = self.github_api_client.get("meta").await?;
+				github_hook_ranges_cache.insert((), response.hooks.clone());
+
+				response.hooks
+			},
+		};
+
+		Ok(github_hook_ranges.iter().any(|range| range.contains(&address)))
+	}
+}
+
+/// [warp] filter rejecting requests whose source IP address isn’t included in the allow list with
+/// [crate::Error::SourceIpAddressNotAllowed]. If no allow list is configured, all requests pass.
+///
+/// Note that behind a reverse proxy, the source IP address is the proxy’s address, so the allow
+/// list should only be used if webhook events are delivered directly.
+///
+/// # Arguments
+/// - `allow_list`: The allow list of source IP addresses, if configured.
+pub fn with_allowed_source_ip_address(allow_list: Option<SourceIpAllowList>)
+	-> impl warp::Filter<Extract = (), Error = warp::Rejection> + Clone
+{
+	use warp::Filter as _;
+
+	warp::addr::remote()
+		.and_then(
+			move |remote_address: Option<std::net::SocketAddr>|
+			{
+				let allow_list = allow_list.clone();
+
+				async move
+				{
+					let allow_list = match allow_list
+					{
+						Some(allow_list) => allow_list,
+						None => return Ok(()),
+					};
+
+					// If the source address is unknown, it can’t be on the allow list either
+					let address = remote_address.map(|remote_address| remote_address.ip())
+						.ok_or(crate::Error::SourceIpAddressNotAllowed(None))
+						.map_err(warp::reject::custom)?;
+
+					match allow_list.allows(address).await.map_err(warp::reject::custom)?
+					{
+						true => Ok(()),
+						false =>
+						{
+							log::warn!("rejected webhook event from IP address {address}, which \
+								isn’t on the allow list");
+							Err(warp::reject::custom(
+								crate::Error::SourceIpAddressNotAllowed(Some(address))))
+						},
+					}
+				}
+			})
+		// The last call returned an empty tuple, which shouldn’t be passed on to subsequent filters
+		.untuple_one()
+}
+
+/// How long the IP address ranges GitHub publishes for webhook deliveries are cached.
+const GITHUB_HOOK_RANGES_CACHE_TIME_TO_LIVE: std::time::Duration =
+	std::time::Duration::from_secs(60 * 60);
@@ -0,0 +1,156 @@
+/// State shared by all request handlers, consisting of the GitHub API client, the configuration,
+/// caches, and metrics.
+///
+/// The state can safely be shared between threads, as cloning it only clones handles to the
+/// underlying data.
+#[derive(Clone)]
+pub struct AppState
+{
+	/// A handle to the GitHub API client.
+	pub github_api_client: crate::github_api::Client,
+	#[doc(hidden)]
+	// Swapped as a whole when the configuration is reloaded, so that handlers keep working with a
+	// consistent snapshot
+	config: std::sync::Arc<std::sync::RwLock<std::sync::Arc<crate::Config>>>,
+	/// A handle to the cache of recently looked-up repository topics.
+	pub topics_cache: crate::ExpiringCache<String, Vec<String>>,
+	/// A handle to the cache of whether repositories were recently found to contain the opt-out
+	/// file.
+	pub opt_out_cache: crate::ExpiringCache<String, bool>,
+	/// A handle to the metrics about received webhook events.
+	pub metrics: crate::Metrics,
+	/// A handle to the sink records of handled webhook events are written to.
+	pub event_sink: crate::EventSink,
+	/// A handle to the capture of raw payloads, if configured.
+	pub payload_capture: Option<crate::PayloadCapture>,
+	/// A handle to the limiter of how many webhook events are handled at the same time.
+	pub concurrency_limiter: crate::ConcurrencyLimiter,
+	/// A handle to the tracker of webhook events being handled in the background.
+	pub pending_events: crate::PendingEvents,
+	/// A handle to the locks serializing updates of the status issue of each repository.
+	pub status_issue_locks: crate::RepositoryLocks,
+	/// A handle to the notifier posting alerts about failures, if configured.
+	pub failure_notifier: crate::FailureNotifier,
+	/// A handle to the limiter of how many notifications are posted per repository.
+	pub notification_limiter: crate::NotificationLimiter,
+	/// A handle to the flag indicating whether this service is degraded, which is shared with the
+	/// GitHub API client.
+	pub degradation: crate::Degradation,
+}
+
+impl AppState
+{
+	/// Create the shared state from the configuration and an initialized GitHub API client.
+	///
+	/// # Arguments
+	/// - `config`: The validated configuration of this application.
+	/// - `github_api_client`: The GitHub API client.
+	pub fn new(config: crate::Config, github_api_client: crate::github_api::Client)
+		-> Result<Self, crate::Error>
+	{
+		let event_sink = crate::EventSink::new(config.event_sink.clone());
+		let concurrency_limiter = crate::ConcurrencyLimiter::new(config.max_concurrent_events,
+			config.max_concurrent_events_per_organization.or(config.max_concurrent_events));
+		let payload_capture = config.payload_capture_dir.clone()
+			.map(|directory|
+				crate::PayloadCapture::new(directory, config.payload_capture_max_files));
+
+		let degradation = github_api_client.degradation().clone();
+
+		let metrics = match &config.statsd_address
+		{
+			Some(statsd_address) => crate::Metrics::default().with_statsd(statsd_address)?,
+			None => Default::default(),
+		};
+
+		let github_api_client = github_api_client.with_metrics(metrics.clone());
+
+		Ok(Self
+		{
+			github_api_client,
+			config: std::sync::Arc::new(std::sync::RwLock::new(std::sync::Arc::new(config))),
+			// Remember the topics of repositories for a few minutes so as not to look them up
+			// repeatedly
+			topics_cache: crate::ExpiringCache::new(crate::TOPICS_CACHE_TIME_TO_LIVE),
+			opt_out_cache: crate::ExpiringCache::new(crate::OPT_OUT_CACHE_TIME_TO_LIVE),
+			metrics,
+			event_sink,
+			payload_capture,
+			concurrency_limiter,
+			pending_events: Default::default(),
+			status_issue_locks: Default::default(),
+			failure_notifier: Default::default(),
+			notification_limiter: Default::default(),
+			degradation,
+		})
+	}
+
+	/// A snapshot of the current validated configuration of this application, which isn’t affected
+	/// by later reloads.
+	pub fn config(&self) -> std::sync::Arc<crate::Config>
+	{
+		// A poisoned lock can’t leave the configuration half-replaced, as it’s swapped as a whole
+		self.config.read().unwrap_or_else(std::sync::PoisonError::into_inner).clone()
+	}
+
+	/// Replace the configuration of this application, which applies to webhook events handled from
+	/// now on.
+	///
+	/// # Arguments
+	/// - `config`: The newly validated configuration.
+	pub fn replace_config(&self, config: crate::Config)
+	{
+		*self.config.write().unwrap_or_else(std::sync::PoisonError::into_inner) =
+			std::sync::Arc::new(config);
+	}
+}
+
+/// [warp] filter retrieving and verifying the payload of a webhook event (see
+/// [crate::github_api::with_verified_payload]), capturing it if configured, and passing it on
+/// decoded along with a handle to the shared state in that order.
+///
+/// # Arguments
+/// - `state`: The shared state.
+pub fn with_validated_payload_and_state<T>(state: AppState)
+	-> impl warp::Filter<Extract = (T, AppState), Error = warp::Rejection> + Clone
+where
+	T: serde::de::DeserializeOwned + Send,
+{
+	use warp::Filter as _;
+
+	crate::github_api::with_verified_payload(state.github_api_client.clone())
+		.and(warp::header::headers_cloned())
+		.and(warp::any().map(move || state.clone()))
+		.and_then(
+			|bytes: warp::hyper::body::Bytes, headers: warp::http::HeaderMap, state: AppState|
+			async move
+			{
+				// Only events of the types listed in the routes get here, albeit in any case
+				let event_type = headers.get("x-github-event")
+					.and_then(|event_type| event_type.to_str().ok())
+					.unwrap_or("unknown")
+					.to_lowercase();
+
+				state.metrics.record_event(&event_type);
+				state.metrics.record_payload_size(&event_type, bytes.len());
+
+				// Capture the payload before decoding it so that malformed payloads are kept, too.
+				// Writing files blocks, so this happens on a thread meant for blocking operations
+				if let Some(payload_capture) = state.payload_capture.clone()
+				{
+					let captured_bytes = bytes.clone();
+
+					if let Err(error) = tokio::task::spawn_blocking(move ||
+						payload_capture.capture(&headers, &captured_bytes)).await
+					{
+						log::warn!("could not capture payload: {error}");
+					}
+				}
+
+				let payload = crate::github_api::decode_payload(&bytes)
+					.map_err(warp::reject::custom)?;
+
+				Ok::<_, warp::Rejection>((payload, state))
+			})
+		.untuple_one()
+}
@@ -0,0 +1,90 @@
+/// A row of the job summary, describing the action taken on a branch or repository.
+pub struct StepSummaryRow
+{
+	/// The repository acted on, including the organization, if known.
+	pub repository: Option<String>,
+	/// The branch acted on, if known or if the action concerned a single branch.
+	pub branch: Option<String>,
+	/// The kind of outcome (see [crate::HandledOutcome::name]).
+	pub outcome: &'static str,
+	/// Details about the outcome, such as an error message.
+	pub detail: String,
+}
+
+impl StepSummaryRow
+{
+	/// Describe the outcome of handling a webhook event in a row of the job summary.
+	///
+	/// # Arguments
+	/// - `target`: The branch acted on, if known.
+	/// - `outcome`: The outcome of handling the webhook event.
+	pub fn from_outcome(target: Option<&crate::ProtectionTarget>,
+		outcome: &crate::HandledOutcome)
+		-> Self
+	{
+		Self
+		{
+			repository: target.map(|target|
+				format!("{}/{}", target.organization_name, target.repository_name)),
+			branch: target.map(|target| target.branch_name.clone()),
+			outcome: outcome.name(),
+			detail: outcome.detail(),
+		}
+	}
+}
+
+/// Append a Markdown table of the actions taken on branches to the job summary of the GitHub
+/// Actions workflow run this is invoked from, which is detected by the environment variable
+/// `GITHUB_STEP_SUMMARY` pointing to the summary file. Outside of GitHub Actions, this does
+/// nothing.
+///
+/// Failures to write the summary are logged but otherwise ignored, as they don’t affect the actions
+/// taken.
+///
+/// # Arguments
+/// - `rows`: The actions taken, one per row.
+pub fn append_step_summary(rows: &[StepSummaryRow])
+{
+	let path = match std::env::var_os(STEP_SUMMARY_ENVIRONMENT_VARIABLE)
+	{
+		Some(path) => std::path::PathBuf::from(path),
+		None => return,
+	};
+
+	if let Err(error) = write_step_summary(&path, rows)
+	{
+		log::warn!("could not write job summary to {}: {error}", path.display());
+	}
+}
+
+/// Append the table of actions to the summary file.
+#[doc(hidden)]
+fn write_step_summary(path: &std::path::Path, rows: &[StepSummaryRow]) -> std::io::Result<()>
+{
+	use std::io::Write as _;
+
+	let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+
+	writeln!(file, "| Repository | Branch | Outcome | Detail |")?;
+	writeln!(file, "| --- | --- | --- | --- |")?;
+
+	for row in rows
+	{
+		writeln!(file, "| {} | {} | {} | {} |",
+			escape_table_cell(row.repository.as_deref().unwrap_or("–")),
+			escape_table_cell(row.branch.as_deref().unwrap_or("–")), row.outcome,
+			escape_table_cell(&row.detail))?;
+	}
+
+	writeln!(file)
+}
+
+/// Escape a value so that it can be included in a cell of a Markdown table.
+#[doc(hidden)]
+fn escape_table_cell(value: &str) -> String
+{
+	value.replace('|', "\\|").replace('\n', " ")
+}
+
+/// The environment variable GitHub Actions sets to the path of the job summary file.
+const STEP_SUMMARY_ENVIRONMENT_VARIABLE: &str = "GITHUB_STEP_SUMMARY";
@@ -0,0 +1,65 @@
+/// Set up exporting a trace per webhook event with spans for each GitHub API request to an
+/// OpenTelemetry collector via OTLP, if configured. Without an endpoint, this does nothing, and
+/// all spans are discarded at negligible cost.
+///
+/// # Arguments
+/// - `endpoint`: The URL of the OTLP/gRPC endpoint of the collector, if configured.
+pub fn init_tracing(endpoint: Option<&url::Url>) -> Result<(), crate::Error>
+{
+	use opentelemetry_otlp::WithExportConfig as _;
+	use tracing_subscriber::layer::SubscriberExt as _;
+
+	let endpoint = match endpoint
+	{
+		Some(endpoint) => endpoint,
+		None => return Ok(()),
+	};
+
+	let resource = opentelemetry_sdk::Resource::new([
+		opentelemetry::KeyValue::new("service.name", env!("CARGO_PKG_NAME")),
+		opentelemetry::KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+	]);
+
+	// Export spans in batches in the background so that handling webhook events isn’t delayed
+	let tracer = opentelemetry_otlp::new_pipeline()
+		.tracing()
+		.with_exporter(opentelemetry_otlp::new_exporter().tonic().with_endpoint(endpoint.as_str()))
+		.with_trace_config(opentelemetry_sdk::trace::config().with_resource(resource))
+		.install_batch(opentelemetry_sdk::runtime::Tokio)
+		.map_err(crate::Error::InitializeTracing)?;
+
+	let subscriber = tracing_subscriber::registry()
+		.with(tracing_opentelemetry::layer().with_tracer(tracer));
+
+	tracing::subscriber::set_global_default(subscriber)
+		.expect("this call is infallible because tracing is only initialized once");
+
+	log::info!("exporting traces to {endpoint}");
+
+	Ok(())
+}
+
+/// Create the span covering the handling of a webhook event in the background, which becomes the
+/// root of the event’s trace, with the GitHub API requests made as child spans.
+///
+/// # Arguments
+/// - `event_type`: The type of the webhook event.
+/// - `delivery_id`: The ID of the webhook delivery, if provided.
+/// - `target`: The branch acted on.
+pub fn webhook_event_span(event_type: &str, delivery_id: Option<&str>,
+	target: &crate::ProtectionTarget)
+	-> tracing::Span
+{
+	tracing::info_span!("webhook_event",
+		event_type,
+		delivery_id = delivery_id.unwrap_or("unknown"),
+		organization = target.organization_name.as_str(),
+		repository = target.repository_name.as_str(),
+		branch = target.branch_name.as_str())
+}
+
+/// Export all spans that weren’t exported yet before exiting, if tracing was set up.
+pub fn shutdown_tracing()
+{
+	opentelemetry::global::shutdown_tracer_provider();
+}
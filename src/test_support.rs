@@ -0,0 +1,235 @@
+/// Build a configuration for tests from the given options on top of a minimal valid one. Options
+/// under `github_api` are merged into the minimal GitHub API configuration. The configuration isn’t
+/// validated, so that tests can focus on the options they’re about.
+///
+/// # Arguments
+/// - `options`: The top-level configuration options as a JSON object.
+pub fn config(options: serde_json::Value) -> crate::Config
+{
+	let mut config = serde_json::json!(
+	{
+		"github_api":
+		{
+			"organization": "example-organization",
+			"private_key_path": TEST_PRIVATE_KEY_PATH,
+			"app_id": 1,
+		},
+	});
+
+	if let serde_json::Value::Object(options) = options
+	{
+		for (key, value) in options
+		{
+			match (key.as_str(), value)
+			{
+				("github_api", serde_json::Value::Object(github_api)) =>
+					config["github_api"].as_object_mut()
+						.expect("this call is infallible because the defaults are an object")
+						.extend(github_api),
+				(_, value) =>
+				{
+					config[key] = value;
+				},
+			}
+		}
+	}
+
+	serde_json::from_value(config).expect("test configuration should be valid")
+}
+
+/// A private key for the GitHub App that is only used in tests, where JWTs are signed but never
+/// checked.
+pub const TEST_PRIVATE_KEY_PATH: &str =
+	concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/test-private-key.pem");
+
+/// A fake GitHub API server for tests, which answers requests with canned responses and records
+/// them. Requests without a canned response are answered with 404 Not Found, like unknown resources
+/// on GitHub. The requests for obtaining an access token and the GitHub App’s metadata are answered
+/// already.
+///
+/// The server can safely be shared between threads, as cloning it only clones handles to the
+/// underlying data.
+#[derive(Clone)]
+pub struct MockGitHub
+{
+	#[doc(hidden)]
+	responses: std::sync::Arc<std::sync::Mutex<Vec<CannedResponse>>>,
+	#[doc(hidden)]
+	requests: std::sync::Arc<std::sync::Mutex<Vec<RecordedRequest>>>,
+}
+
+/// A response the fake GitHub API server answers a request with.
+#[doc(hidden)]
+struct CannedResponse
+{
+	method: warp::http::Method,
+	path: String,
+	status: warp::http::StatusCode,
+	body: serde_json::Value,
+}
+
+/// A request received by the fake GitHub API server.
+#[doc(hidden)]
+struct RecordedRequest
+{
+	method: warp::http::Method,
+	path: String,
+	body: serde_json::Value,
+}
+
+impl MockGitHub
+{
+	/// Create a fake GitHub API server that only answers the requests for obtaining an access
+	/// token and the GitHub App’s metadata.
+	pub fn new() -> Self
+	{
+		let mock_github = Self
+		{
+			responses: Default::default(),
+			requests: Default::default(),
+		};
+
+		mock_github
+			.respond("GET", "orgs/example-organization/installation", 200,
+				serde_json::json!({"id": 1}))
+			.respond("POST", "app/installations/1/access_tokens", 201,
+				serde_json::json!({"token": "test-token"}))
+			.respond("GET", "app", 200, serde_json::json!(
+			{
+				"slug": "branch-autoprotector",
+				"permissions": {"administration": "write", "issues": "write"},
+				"events": ["create"],
+			}));
+
+		mock_github
+	}
+
+	/// Answer requests with a canned response. If several responses are registered for the same
+	/// request, they’re used once each in the order of registration, except for the last one,
+	/// which answers all further requests.
+	///
+	/// # Arguments
+	/// - `method`: The HTTP method of the request (example: `GET`).
+	/// - `path`: The API endpoint including the query, if any, without leading slash (example:
+	///   `repos/example-organization/example-repository`).
+	/// - `status`: The HTTP status code of the response.
+	/// - `body`: The JSON body of the response.
+	pub fn respond(&self, method: &str, path: &str, status: u16, body: serde_json::Value)
+		-> &Self
+	{
+		self.responses.lock().unwrap().push(CannedResponse
+		{
+			method: method.parse().expect("test HTTP methods should be valid"),
+			path: path.to_owned(),
+			status: warp::http::StatusCode::from_u16(status)
+				.expect("test status codes should be valid"),
+			body,
+		});
+
+		self
+	}
+
+	/// The JSON bodies of the requests received so far for an API endpoint, in the order they were
+	/// received, which are `null` for requests without a body (for arguments, see
+	/// [Self::respond]).
+	pub fn requests(&self, method: &str, path: &str) -> Vec<serde_json::Value>
+	{
+		self.requests.lock().unwrap().iter()
+			.filter(|request| request.method == method && request.path == path)
+			.map(|request| request.body.clone())
+			.collect()
+	}
+
+	/// Start serving the fake GitHub API on an unused local port and create a GitHub API client
+	/// talking to it with the given GitHub API options (see [config]).
+	pub async fn client(&self, github_api_options: serde_json::Value) -> crate::github_api::Client
+	{
+		let config = self.serve(serde_json::json!({"github_api": github_api_options}));
+
+		crate::github_api::Client::from_config(config.github_api).await
+			.expect("the test GitHub API client should be created")
+	}
+
+	/// Start serving the fake GitHub API on an unused local port and create the shared state of
+	/// this service talking to it with the given configuration options (see [config]).
+	pub async fn state(&self, options: serde_json::Value) -> crate::AppState
+	{
+		let config = self.serve(options);
+		let github_api_client = crate::github_api::Client::from_config(config.github_api.clone())
+			.await
+			.expect("the test GitHub API client should be created");
+
+		crate::AppState::new(config, github_api_client).expect("the test state should be created")
+	}
+
+	/// Start serving the fake GitHub API and build a configuration pointing to it, which defers
+	/// requesting an access token until the first request (see [config]).
+	#[doc(hidden)]
+	fn serve(&self, options: serde_json::Value) -> crate::Config
+	{
+		use warp::Filter as _;
+
+		let mock_github = self.clone();
+
+		let route = warp::method()
+			.and(warp::path::full())
+			.and(warp::query::raw().or(warp::any().map(String::new)).unify())
+			.and(warp::body::bytes())
+			.map(move |method, path: warp::path::FullPath, query: String,
+				body: warp::hyper::body::Bytes|
+			{
+				let path = match query.is_empty()
+				{
+					true => path.as_str()[1..].to_owned(),
+					false => format!("{}?{query}", &path.as_str()[1..]),
+				};
+
+				let (status, body) = mock_github.answer(method, path, &body);
+
+				warp::reply::with_status(warp::reply::json(&body), status)
+			});
+
+		let (address, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+		tokio::spawn(server);
+
+		// Options given by the test are kept except for where the server is listening, and null
+		// values are turned into objects by indexing them
+		let mut options = options;
+		options["github_api"]["base_url"] = serde_json::json!(format!("http://{address}/"));
+		options["github_api"]["lazy_token"] = serde_json::json!(true);
+
+		config(options)
+	}
+
+	/// Record a request and look up the canned response to answer it with.
+	#[doc(hidden)]
+	fn answer(&self, method: warp::http::Method, path: String, body: &[u8])
+		-> (warp::http::StatusCode, serde_json::Value)
+	{
+		let mut responses = self.responses.lock().unwrap();
+
+		let matches = |response: &CannedResponse|
+			response.method == method && response.path == path;
+
+		let answer = match responses.iter().position(matches)
+		{
+			Some(index) if responses[index + 1..].iter().any(matches) =>
+			{
+				let response = responses.remove(index);
+				(response.status, response.body)
+			},
+			Some(index) => (responses[index].status, responses[index].body.clone()),
+			None => (warp::http::StatusCode::NOT_FOUND,
+				serde_json::json!({"message": "Not Found"})),
+		};
+
+		self.requests.lock().unwrap().push(RecordedRequest
+		{
+			method,
+			path,
+			body: serde_json::from_slice(body).unwrap_or_default(),
+		});
+
+		answer
+	}
+}